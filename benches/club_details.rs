@@ -0,0 +1,107 @@
+/*
+Compares the old one-club-at-a-time way of building ClubDetails with
+the aggregated queries used by the club endpoints. Needs DATABASE_URL
+pointing at a migrated database, the fixture is rolled back afterwards.
+
+    cargo bench --bench club_details
+*/
+use saturn::prelude::*;
+use saturn::models::clubs_md::MAX_PAGE_SIZE;
+use std::time::{Duration, Instant};
+use diesel::connection::SimpleConnection;
+
+const FIXTURE: &str = include_str!("fixtures/clubs.sql");
+//Only the fixture's clubs are compared, whatever else the database holds.
+const BENCH_CLUBS: &str = "Bench Club %";
+
+fn main() {
+    dotenv().ok();
+    let conn = PgConnection::establish(&env::var("DATABASE_URL").expect("DATABASE_URL must be set")).expect("database connection");
+    conn.begin_test_transaction().expect("test transaction");
+    conn.batch_execute(FIXTURE).expect("benchmark fixture");
+
+    let user_id = {
+        use saturn::schema::users::dsl::{users, id, email};
+        users.filter(email.eq("bench-0@example.com")).select(id).first::<i32>(&conn).expect("benchmark user")
+    };
+    let term_id = {
+        use saturn::schema::terms::dsl::{terms, id, name};
+        terms.filter(name.eq("Bench Term")).select(id).first::<i32>(&conn).expect("benchmark term")
+    };
+
+    let (legacy_count, legacy_list) = time(|| legacy_list(&conn, user_id));
+    let (count, list) = time(|| aggregated_list(&conn, user_id, term_id));
+    assert_eq!(legacy_count, count, "Both strategies should return the same clubs.");
+    report("list all clubs", count, legacy_list, list);
+
+    let club_ids = {
+        use saturn::schema::clubs::dsl::{clubs, id, name};
+        clubs.filter(name.like(BENCH_CLUBS)).select(id).order(id).limit(100).load::<i32>(&conn).expect("club ids")
+    };
+
+    let (_, legacy_details) = time(|| club_ids.iter().filter(|club_id| legacy_details(&conn, user_id, **club_id).is_some()).count());
+    let (found, details) = time(|| club_ids.iter().filter(|club_id| ClubDetails::get_for_user(&conn, club_id, Some(user_id)).unwrap().is_some()).count());
+    report("club details", found, legacy_details, details);
+}
+
+fn time<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+fn report(name: &str, count: usize, legacy: Duration, aggregated: Duration) {
+    println!(
+        "{:<16} {:>6} clubs  legacy {:>10.2?}  aggregated {:>10.2?}  ({:.1}x)",
+        name, count, legacy, aggregated, legacy.as_secs_f64() / aggregated.as_secs_f64()
+    );
+}
+
+//What get_all used to do: load every club then run several queries per club.
+fn legacy_list(conn: &PgConnection, user_id: i32) -> usize {
+    use saturn::schema::clubs::dsl::{clubs, name};
+
+    let club_load = clubs.filter(name.like(BENCH_CLUBS)).load::<Club>(conn).expect("clubs");
+    club_load.into_iter().filter_map(|club| legacy_from_club(conn, user_id, club)).count()
+}
+
+//What get_club_details used to do: load every club and keep the one asked for.
+fn legacy_details(conn: &PgConnection, user_id: i32, club_id: i32) -> Option<ClubDetails> {
+    use saturn::schema::clubs::dsl::{clubs};
+
+    let club_load = clubs.load::<Club>(conn).expect("clubs");
+    club_load.into_iter().filter(|club| club.id == club_id).filter_map(|club| legacy_from_club(conn, user_id, club)).next()
+}
+
+fn legacy_from_club(conn: &PgConnection, user_id: i32, club: Club) -> Option<ClubDetails> {
+    use saturn::schema::club_members::dsl::{club_members, club_id, user_id as member_user_id};
+
+    let member = club_members.filter(member_user_id.eq(user_id)).filter(club_id.eq(club.id)).first::<ClubMember>(conn);
+    let member = member.unwrap_or(ClubMember {
+        id: -1,
+        user_id: -1,
+        club_id: -1,
+        is_moderator: "false".to_owned(),
+    });
+    ClubDetails::from_join((member, club), user_id, conn)
+}
+
+fn aggregated_list(conn: &PgConnection, user_id: i32, term_id: i32) -> usize {
+    let mut query = ClubQuery {
+        term: Some(term_id),
+        sort: Some(ClubSort::Newest),
+        limit: Some(MAX_PAGE_SIZE),
+        ..Default::default()
+    };
+    let mut count = 0;
+
+    loop {
+        let page = Club::search(conn, Some(user_id), &query).expect("club page");
+        count += page.clubs.len();
+
+        match page.next_cursor {
+            Some(cursor) => query.cursor = Some(cursor),
+            None => return count,
+        }
+    }
+}
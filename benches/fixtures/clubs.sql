@@ -0,0 +1,43 @@
+-- 5000 clubs shared between 1000 users, each club with a head moderator
+-- and roughly ten members. Loaded inside a rolled back transaction by
+-- benches/club_details.rs so it never touches real data.
+INSERT INTO users (email, picture, first_name, last_name)
+SELECT 'bench-' || n || '@example.com', 'https://example.com/bench-' || n || '.png', 'Bench', 'User ' || n
+FROM generate_series(0, 999) AS n;
+
+-- Approved so they are listed, new clubs wait for review by default.
+INSERT INTO clubs (name, body, publish_date, expiry_date, approval_status)
+SELECT 'Bench Club ' || n,
+       'A club generated for benchmarking, number ' || n || '. Members meet weekly to talk about chess, hiking and robots.',
+       NOW() - (n || ' minutes')::INTERVAL,
+       NOW() + (n || ' minutes')::INTERVAL,
+       'approved'
+FROM generate_series(0, 4999) AS n;
+
+INSERT INTO club_members (user_id, club_id, is_moderator)
+SELECT users.id, clubs.id, 'head'
+FROM clubs
+INNER JOIN users ON users.email = 'bench-' || (clubs.id % 1000) || '@example.com'
+WHERE clubs.name LIKE 'Bench Club %';
+
+INSERT INTO club_members (user_id, club_id, is_moderator)
+SELECT users.id, clubs.id, 'false'
+FROM clubs
+CROSS JOIN users
+WHERE clubs.name LIKE 'Bench Club %'
+  AND users.email LIKE 'bench-%'
+  AND users.email <> 'bench-' || (clubs.id % 1000) || '@example.com'
+  AND (users.id + clubs.id) % 97 = 0;
+
+-- Every bench club is registered for a term of its own. The benchmark
+-- lists that term so neither other clubs nor a running term change what
+-- it compares.
+INSERT INTO terms (name, start_date, end_date)
+VALUES ('Bench Term', '1970-01-01T00:00:00Z', '1970-06-01T00:00:00Z');
+
+INSERT INTO club_registrations (club_id, term_id, status)
+SELECT clubs.id, terms.id, 'registered'
+FROM clubs
+CROSS JOIN terms
+WHERE clubs.name LIKE 'Bench Club %'
+  AND terms.name = 'Bench Term';
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER users_notify_change ON users;
+DROP TRIGGER club_members_notify_change ON club_members;
+DROP TRIGGER clubs_notify_change ON clubs;
+DROP FUNCTION saturn_notify_change();
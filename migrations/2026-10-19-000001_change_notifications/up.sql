@@ -0,0 +1,35 @@
+-- Publishes every row change on the shared tables to the `saturn_events`
+-- channel so each Saturn instance can invalidate caches and forward
+-- realtime events without an external broker.
+CREATE OR REPLACE FUNCTION saturn_notify_change() RETURNS trigger AS $$
+DECLARE
+  rec RECORD;
+BEGIN
+  IF TG_OP = 'DELETE' THEN
+    rec := OLD;
+  ELSE
+    rec := NEW;
+  END IF;
+
+  PERFORM pg_notify('saturn_events', json_build_object(
+    'table', TG_TABLE_NAME,
+    'op', TG_OP,
+    'id', rec.id,
+    'club_id', (to_jsonb(rec)->>'club_id')::INT
+  )::TEXT);
+
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER clubs_notify_change
+  AFTER INSERT OR UPDATE OR DELETE ON clubs
+  FOR EACH ROW EXECUTE PROCEDURE saturn_notify_change();
+
+CREATE TRIGGER club_members_notify_change
+  AFTER INSERT OR UPDATE OR DELETE ON club_members
+  FOR EACH ROW EXECUTE PROCEDURE saturn_notify_change();
+
+CREATE TRIGGER users_notify_change
+  AFTER INSERT OR UPDATE OR DELETE ON users
+  FOR EACH ROW EXECUTE PROCEDURE saturn_notify_change();
@@ -0,0 +1,10 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX club_members_user_id_idx;
+DROP INDEX club_members_club_id_idx;
+DROP INDEX clubs_expiry_date_idx;
+DROP INDEX clubs_publish_date_idx;
+DROP INDEX clubs_name_trgm_idx;
+DROP INDEX clubs_search_vector_idx;
+DROP TRIGGER clubs_search_vector_update ON clubs;
+DROP FUNCTION clubs_update_search_vector();
+ALTER TABLE clubs DROP COLUMN search_vector;
@@ -0,0 +1,28 @@
+-- Full text search over club names and bodies with trigram typo tolerance.
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+ALTER TABLE clubs ADD COLUMN search_vector tsvector NOT NULL DEFAULT ''::tsvector;
+
+CREATE OR REPLACE FUNCTION clubs_update_search_vector() RETURNS trigger AS $$
+BEGIN
+  NEW.search_vector :=
+    setweight(to_tsvector('english', coalesce(NEW.name, '')), 'A') ||
+    setweight(to_tsvector('english', coalesce(NEW.body, '')), 'B');
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER clubs_search_vector_update
+  BEFORE INSERT OR UPDATE OF name, body ON clubs
+  FOR EACH ROW EXECUTE PROCEDURE clubs_update_search_vector();
+
+UPDATE clubs SET search_vector =
+  setweight(to_tsvector('english', name), 'A') ||
+  setweight(to_tsvector('english', body), 'B');
+
+CREATE INDEX clubs_search_vector_idx ON clubs USING GIN (search_vector);
+CREATE INDEX clubs_name_trgm_idx ON clubs USING GIN (name gin_trgm_ops);
+CREATE INDEX clubs_publish_date_idx ON clubs (publish_date DESC, id DESC);
+CREATE INDEX clubs_expiry_date_idx ON clubs (expiry_date, id);
+CREATE INDEX club_members_club_id_idx ON club_members (club_id);
+CREATE INDEX club_members_user_id_idx ON club_members (user_id);
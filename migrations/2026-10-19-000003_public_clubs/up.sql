@@ -0,0 +1,2 @@
+-- Public clubs can be browsed by guests who haven't signed in.
+ALTER TABLE clubs ADD COLUMN is_public BOOLEAN NOT NULL DEFAULT true;
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE club_tags;
+DROP TABLE tags;
+ALTER TABLE clubs DROP COLUMN category_id;
+DROP TABLE categories;
@@ -0,0 +1,29 @@
+-- Admin curated categories, one per club.
+CREATE TABLE categories (
+  id SERIAL PRIMARY KEY,
+  name TEXT NOT NULL UNIQUE
+);
+
+ALTER TABLE clubs ADD COLUMN category_id INT;
+ALTER TABLE clubs ADD CONSTRAINT club_category_id_exists FOREIGN KEY(category_id) REFERENCES categories(id) ON DELETE SET NULL;
+CREATE INDEX clubs_category_id_idx ON clubs (category_id);
+
+-- Free form tags applied by moderators, stored normalised (lowercase, single spaced).
+CREATE TABLE tags (
+  id SERIAL PRIMARY KEY,
+  name TEXT NOT NULL UNIQUE
+);
+
+CREATE INDEX tags_name_pattern_idx ON tags (name text_pattern_ops);
+
+CREATE TABLE club_tags (
+  club_id INT NOT NULL,
+  tag_id INT NOT NULL,
+  PRIMARY KEY (club_id, tag_id),
+  CONSTRAINT club_tag_club_id_exists FOREIGN KEY(club_id) REFERENCES clubs(id) ON DELETE CASCADE,
+  CONSTRAINT club_tag_tag_id_exists FOREIGN KEY(tag_id) REFERENCES tags(id) ON DELETE CASCADE
+);
+
+CREATE INDEX club_tags_tag_id_idx ON club_tags (tag_id);
+
+INSERT INTO categories (name) VALUES ('Academic'), ('Cultural'), ('Gaming'), ('Sports');
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE notifications;
+DROP INDEX clubs_active_expiry_date_idx;
+ALTER TABLE clubs DROP COLUMN archived_at;
+ALTER TABLE clubs DROP COLUMN expiry_warned_at;
+ALTER TABLE clubs DROP COLUMN renewal_days;
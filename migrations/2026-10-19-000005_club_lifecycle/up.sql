@@ -0,0 +1,22 @@
+-- Per club override of the global renewal period, in days.
+ALTER TABLE clubs ADD COLUMN renewal_days INT CHECK (renewal_days > 0);
+-- Set once moderators have been warned about the upcoming expiry, cleared on renewal.
+ALTER TABLE clubs ADD COLUMN expiry_warned_at timestamp with TIME ZONE;
+-- Expired clubs are archived instead of deleted, they drop out of listings but keep their data.
+ALTER TABLE clubs ADD COLUMN archived_at timestamp with TIME ZONE;
+
+CREATE INDEX clubs_active_expiry_date_idx ON clubs (expiry_date) WHERE archived_at IS NULL;
+
+CREATE TABLE notifications (
+  id SERIAL PRIMARY KEY,
+  user_id INT NOT NULL,
+  club_id INT,
+  kind TEXT NOT NULL,
+  message TEXT NOT NULL,
+  created_at timestamp with TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  read_at timestamp with TIME ZONE,
+  CONSTRAINT notification_user_id_exists FOREIGN KEY(user_id) REFERENCES users(id) ON DELETE CASCADE,
+  CONSTRAINT notification_club_id_exists FOREIGN KEY(club_id) REFERENCES clubs(id) ON DELETE SET NULL
+);
+
+CREATE INDEX notifications_user_id_idx ON notifications (user_id, created_at DESC);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE club_registrations;
+DROP TABLE terms;
@@ -0,0 +1,28 @@
+-- Admin defined academic terms. Clubs re-register for every term.
+CREATE TABLE terms (
+  id SERIAL PRIMARY KEY,
+  name TEXT NOT NULL UNIQUE,
+  start_date timestamp with TIME ZONE NOT NULL,
+  end_date timestamp with TIME ZONE NOT NULL,
+  CONSTRAINT term_ends_after_start CHECK (end_date > start_date)
+);
+
+CREATE INDEX terms_dates_idx ON terms (start_date, end_date);
+
+-- A club's standing for one term: pending until an admin approves, registered, or lapsed.
+CREATE TABLE club_registrations (
+  id SERIAL PRIMARY KEY,
+  club_id INT NOT NULL,
+  term_id INT NOT NULL,
+  status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'registered', 'lapsed')),
+  officer_ids INT[] NOT NULL DEFAULT '{}',
+  submitted_by INT,
+  submitted_at timestamp with TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  decided_at timestamp with TIME ZONE,
+  CONSTRAINT registration_club_id_exists FOREIGN KEY(club_id) REFERENCES clubs(id) ON DELETE CASCADE,
+  CONSTRAINT registration_term_id_exists FOREIGN KEY(term_id) REFERENCES terms(id) ON DELETE CASCADE,
+  CONSTRAINT registration_submitted_by_exists FOREIGN KEY(submitted_by) REFERENCES users(id) ON DELETE SET NULL,
+  CONSTRAINT one_registration_per_term UNIQUE (club_id, term_id)
+);
+
+CREATE INDEX club_registrations_term_status_idx ON club_registrations (term_id, status);
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX clubs_review_queue_idx;
+ALTER TABLE clubs DROP COLUMN is_verified;
+ALTER TABLE clubs DROP COLUMN reviewed_at;
+ALTER TABLE clubs DROP COLUMN review_note;
+ALTER TABLE clubs DROP COLUMN approval_status;
@@ -0,0 +1,11 @@
+-- Clubs that already exist were published before reviews, so they start out approved. New clubs wait for an admin.
+ALTER TABLE clubs ADD COLUMN approval_status TEXT NOT NULL DEFAULT 'approved'
+  CHECK (approval_status IN ('pending', 'approved', 'rejected', 'changes_requested'));
+ALTER TABLE clubs ALTER COLUMN approval_status SET DEFAULT 'pending';
+-- The reason given with a rejection or a request for changes.
+ALTER TABLE clubs ADD COLUMN review_note TEXT;
+ALTER TABLE clubs ADD COLUMN reviewed_at timestamp with TIME ZONE;
+-- Officially recognized clubs.
+ALTER TABLE clubs ADD COLUMN is_verified BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX clubs_review_queue_idx ON clubs (publish_date) WHERE approval_status = 'pending';
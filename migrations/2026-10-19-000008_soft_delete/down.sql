@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX clubs_deleted_at_idx;
+ALTER TABLE clubs DROP CONSTRAINT club_deleted_by_exists;
+ALTER TABLE clubs DROP COLUMN deleted_by;
+ALTER TABLE clubs DROP COLUMN deleted_at;
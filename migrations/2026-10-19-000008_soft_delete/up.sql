@@ -0,0 +1,6 @@
+-- Deleted clubs are kept around for the restore window before the purge job removes them for good.
+ALTER TABLE clubs ADD COLUMN deleted_at timestamp with TIME ZONE;
+ALTER TABLE clubs ADD COLUMN deleted_by INT;
+ALTER TABLE clubs ADD CONSTRAINT club_deleted_by_exists FOREIGN KEY(deleted_by) REFERENCES users(id) ON DELETE SET NULL;
+
+CREATE INDEX clubs_deleted_at_idx ON clubs (deleted_at) WHERE deleted_at IS NOT NULL;
@@ -0,0 +1,21 @@
+-- Every version of a club's name and body, newest has the highest id.
+CREATE TABLE club_revisions (
+  id SERIAL PRIMARY KEY,
+  club_id INT NOT NULL,
+  name TEXT NOT NULL,
+  body TEXT NOT NULL,
+  author_id INT,
+  -- Set when the revision came from reverting to an older one.
+  reverted_from INT,
+  created_at timestamp with TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  CONSTRAINT revision_club_id_exists FOREIGN KEY(club_id) REFERENCES clubs(id) ON DELETE CASCADE,
+  CONSTRAINT revision_author_id_exists FOREIGN KEY(author_id) REFERENCES users(id) ON DELETE SET NULL,
+  CONSTRAINT revision_reverted_from_exists FOREIGN KEY(reverted_from) REFERENCES club_revisions(id) ON DELETE SET NULL
+);
+
+CREATE INDEX club_revisions_club_id_idx ON club_revisions (club_id, id DESC);
+
+-- Existing clubs start their history with what they look like now, credited to the head moderator.
+INSERT INTO club_revisions (club_id, name, body, author_id, created_at)
+SELECT clubs.id, clubs.name, clubs.body, head.user_id, clubs.publish_date
+FROM clubs LEFT JOIN club_members head ON head.club_id = clubs.id AND head.is_moderator = 'head';
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER clubs_version_update ON clubs;
+DROP FUNCTION clubs_bump_version();
+ALTER TABLE clubs DROP COLUMN version;
@@ -0,0 +1,17 @@
+-- Bumped whenever the editable details of a club change, exposed as its ETag.
+ALTER TABLE clubs ADD COLUMN version INT NOT NULL DEFAULT 1;
+
+CREATE OR REPLACE FUNCTION clubs_bump_version() RETURNS trigger AS $$
+BEGIN
+  IF NEW.name IS DISTINCT FROM OLD.name
+    OR NEW.body IS DISTINCT FROM OLD.body
+    OR NEW.is_public IS DISTINCT FROM OLD.is_public THEN
+    NEW.version := OLD.version + 1;
+  END IF;
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER clubs_version_update
+  BEFORE UPDATE ON clubs
+  FOR EACH ROW EXECUTE PROCEDURE clubs_bump_version();
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX club_members_moderators_idx;
+DROP INDEX one_head_per_club;
+ALTER TABLE club_members DROP CONSTRAINT valid_moderator_rank;
+ALTER TABLE club_members DROP CONSTRAINT one_membership_per_club;
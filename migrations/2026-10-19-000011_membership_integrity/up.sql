@@ -0,0 +1,22 @@
+-- Keep one membership per user and club, the highest ranked and then the oldest.
+DELETE FROM club_members WHERE id IN (
+  SELECT id FROM (
+    SELECT id, ROW_NUMBER() OVER (
+      PARTITION BY user_id, club_id
+      ORDER BY CASE is_moderator WHEN 'head' THEN 0 WHEN 'true' THEN 1 ELSE 2 END, id
+    ) AS position FROM club_members
+  ) ranked WHERE position > 1
+);
+
+-- Clubs with more than one head keep the oldest, the others become moderators.
+UPDATE club_members SET is_moderator = 'true'
+WHERE is_moderator = 'head' AND id NOT IN (
+  SELECT MIN(id) FROM club_members WHERE is_moderator = 'head' GROUP BY club_id
+);
+
+ALTER TABLE club_members ADD CONSTRAINT one_membership_per_club UNIQUE (user_id, club_id);
+ALTER TABLE club_members ADD CONSTRAINT valid_moderator_rank CHECK (is_moderator IN ('false', 'true', 'head'));
+CREATE UNIQUE INDEX one_head_per_club ON club_members (club_id) WHERE is_moderator = 'head';
+
+-- Moderator lookups, e.g. when notifying a club's moderators.
+CREATE INDEX club_members_moderators_idx ON club_members (club_id) WHERE is_moderator <> 'false';
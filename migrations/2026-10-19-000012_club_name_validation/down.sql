@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX clubs_normalized_name_idx;
+DROP FUNCTION normalize_club_name(TEXT);
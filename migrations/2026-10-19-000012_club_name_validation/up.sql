@@ -0,0 +1,6 @@
+-- Club names compare equal once case and runs of whitespace are ignored, mirrors validation::normalize_name.
+CREATE FUNCTION normalize_club_name(TEXT) RETURNS TEXT AS $$
+  SELECT lower(regexp_replace(btrim($1), '\s+', ' ', 'g'));
+$$ LANGUAGE SQL IMMUTABLE;
+
+CREATE INDEX clubs_normalized_name_idx ON clubs (normalize_club_name(name));
@@ -0,0 +1,2 @@
+-- Sanitized HTML rendered from body, filled in for existing clubs on startup.
+ALTER TABLE clubs ADD COLUMN body_html TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,3 @@
+-- Goes into logo URLs so browsers don't keep showing a replaced logo, 0 while the club has none.
+-- Logos uploaded before this are picked up on startup.
+ALTER TABLE clubs ADD COLUMN logo_version INT NOT NULL DEFAULT 0;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE clubs ADD COLUMN logo_version INT NOT NULL DEFAULT 0;
+UPDATE clubs SET logo_version = 1 WHERE logo_key IS NOT NULL;
+ALTER TABLE clubs DROP COLUMN logo_key;
@@ -0,0 +1,4 @@
+-- Storage key of the club's logo, content addressed so it also does what logo_version did.
+-- Logos still in the uploads directory are moved into storage on startup.
+ALTER TABLE clubs ADD COLUMN logo_key TEXT;
+ALTER TABLE clubs DROP COLUMN logo_version;
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE clubs DROP COLUMN storage_quota_bytes;
+ALTER TABLE clubs DROP COLUMN document_upload_role;
+ALTER TABLE clubs DROP COLUMN photo_upload_role;
+DROP TABLE club_attachments;
@@ -0,0 +1,31 @@
+-- Photos for a club's gallery and documents like its constitution or minutes.
+CREATE TABLE club_attachments (
+  id SERIAL PRIMARY KEY,
+  club_id INT NOT NULL,
+  kind TEXT NOT NULL CHECK (kind IN ('photo', 'document')),
+  storage_key TEXT NOT NULL,
+  -- Photos only.
+  thumbnail_key TEXT,
+  file_name TEXT NOT NULL,
+  content_type TEXT NOT NULL,
+  size_bytes BIGINT NOT NULL,
+  caption TEXT NOT NULL DEFAULT '',
+  -- Order within the club's photos or documents, lowest first.
+  position INT NOT NULL,
+  visibility TEXT NOT NULL DEFAULT 'public' CHECK (visibility IN ('public', 'members')),
+  uploaded_by INT,
+  created_at timestamp with TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  CONSTRAINT attachment_club_id_exists FOREIGN KEY(club_id) REFERENCES clubs(id) ON DELETE CASCADE,
+  CONSTRAINT attachment_uploaded_by_exists FOREIGN KEY(uploaded_by) REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE INDEX club_attachments_club_id_idx ON club_attachments (club_id, kind, position);
+CREATE INDEX club_attachments_storage_key_idx ON club_attachments (storage_key);
+
+-- Who may upload, one of member, moderator or head.
+ALTER TABLE clubs ADD COLUMN photo_upload_role TEXT NOT NULL DEFAULT 'moderator'
+  CHECK (photo_upload_role IN ('member', 'moderator', 'head'));
+ALTER TABLE clubs ADD COLUMN document_upload_role TEXT NOT NULL DEFAULT 'moderator'
+  CHECK (document_upload_role IN ('member', 'moderator', 'head'));
+-- Overrides CLUB_STORAGE_QUOTA_MB for the club.
+ALTER TABLE clubs ADD COLUMN storage_quota_bytes BIGINT;
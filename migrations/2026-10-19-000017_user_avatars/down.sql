@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE users DROP COLUMN avatar_source;
+ALTER TABLE users DROP COLUMN avatar_key;
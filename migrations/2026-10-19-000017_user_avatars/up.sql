@@ -0,0 +1,4 @@
+-- Copy of the user's Google picture in asset storage, NULL while there is none and the generated avatar is used.
+-- avatar_source is the picture URL that was last fetched or tried, so changed pictures are noticed.
+ALTER TABLE users ADD COLUMN avatar_key TEXT;
+ALTER TABLE users ADD COLUMN avatar_source TEXT;
@@ -0,0 +1,15 @@
+-- This file should undo anything in `up.sql`
+DROP FUNCTION are_club_mates(INT, INT);
+ALTER TABLE users DROP CONSTRAINT valid_graduation_year;
+ALTER TABLE users DROP CONSTRAINT valid_profile_visibility;
+ALTER TABLE users DROP COLUMN graduation_year_visibility;
+ALTER TABLE users DROP COLUMN major_visibility;
+ALTER TABLE users DROP COLUMN bio_visibility;
+ALTER TABLE users DROP COLUMN pronouns_visibility;
+ALTER TABLE users DROP COLUMN display_name_visibility;
+ALTER TABLE users DROP COLUMN email_visibility;
+ALTER TABLE users DROP COLUMN graduation_year;
+ALTER TABLE users DROP COLUMN major;
+ALTER TABLE users DROP COLUMN bio;
+ALTER TABLE users DROP COLUMN pronouns;
+ALTER TABLE users DROP COLUMN display_name;
@@ -0,0 +1,32 @@
+-- Optional profile fields, each shown to everyone, club_mates (people sharing a club) or only_me.
+ALTER TABLE users ADD COLUMN display_name TEXT;
+ALTER TABLE users ADD COLUMN pronouns TEXT;
+ALTER TABLE users ADD COLUMN bio TEXT NOT NULL DEFAULT '';
+ALTER TABLE users ADD COLUMN major TEXT;
+ALTER TABLE users ADD COLUMN graduation_year INT;
+ALTER TABLE users ADD COLUMN email_visibility TEXT NOT NULL DEFAULT 'club_mates';
+ALTER TABLE users ADD COLUMN display_name_visibility TEXT NOT NULL DEFAULT 'everyone';
+ALTER TABLE users ADD COLUMN pronouns_visibility TEXT NOT NULL DEFAULT 'everyone';
+ALTER TABLE users ADD COLUMN bio_visibility TEXT NOT NULL DEFAULT 'everyone';
+ALTER TABLE users ADD COLUMN major_visibility TEXT NOT NULL DEFAULT 'club_mates';
+ALTER TABLE users ADD COLUMN graduation_year_visibility TEXT NOT NULL DEFAULT 'club_mates';
+
+ALTER TABLE users ADD CONSTRAINT valid_profile_visibility CHECK (
+    email_visibility IN ('everyone', 'club_mates', 'only_me')
+    AND display_name_visibility IN ('everyone', 'club_mates', 'only_me')
+    AND pronouns_visibility IN ('everyone', 'club_mates', 'only_me')
+    AND bio_visibility IN ('everyone', 'club_mates', 'only_me')
+    AND major_visibility IN ('everyone', 'club_mates', 'only_me')
+    AND graduation_year_visibility IN ('everyone', 'club_mates', 'only_me')
+);
+ALTER TABLE users ADD CONSTRAINT valid_graduation_year CHECK (graduation_year BETWEEN 1900 AND 2200);
+
+-- Whether the two users share a club that hasn't been deleted, decides who sees club_mates fields.
+CREATE FUNCTION are_club_mates(INT, INT) RETURNS BOOLEAN AS $$
+  SELECT EXISTS (
+    SELECT 1 FROM club_members mine
+    INNER JOIN club_members theirs ON theirs.club_id = mine.club_id
+    INNER JOIN clubs shared ON shared.id = mine.club_id AND shared.deleted_at IS NULL
+    WHERE mine.user_id = $1 AND theirs.user_id = $2
+  );
+$$ LANGUAGE SQL STABLE;
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER audit_log_no_truncate ON audit_log;
+DROP TRIGGER audit_log_no_changes ON audit_log;
+DROP FUNCTION audit_log_append_only();
+DROP TABLE audit_log;
@@ -0,0 +1,37 @@
+-- Who changed what, from where. Rows are never updated or deleted, so
+-- there are no foreign keys: entries outlive the users and clubs they name.
+CREATE TABLE audit_log (
+  id SERIAL PRIMARY KEY,
+  -- NULL for changes the server makes on its own.
+  actor_id INT,
+  -- What was done, e.g. club.update, see models::audit_log_md.
+  action TEXT NOT NULL,
+  target_type TEXT NOT NULL,
+  target_id INT,
+  -- The club the change belongs to, head moderators see their club's entries.
+  club_id INT,
+  -- The target as JSON before and after the change, NULL when it didn't exist.
+  before_snapshot TEXT,
+  after_snapshot TEXT,
+  ip TEXT,
+  created_at timestamp with TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX audit_log_created_at_idx ON audit_log (created_at DESC, id DESC);
+CREATE INDEX audit_log_club_id_idx ON audit_log (club_id, created_at DESC);
+CREATE INDEX audit_log_actor_id_idx ON audit_log (actor_id, created_at DESC);
+CREATE INDEX audit_log_target_idx ON audit_log (target_type, target_id);
+
+CREATE OR REPLACE FUNCTION audit_log_append_only() RETURNS trigger AS $$
+BEGIN
+  RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_log_no_changes
+  BEFORE UPDATE OR DELETE ON audit_log
+  FOR EACH ROW EXECUTE PROCEDURE audit_log_append_only();
+
+CREATE TRIGGER audit_log_no_truncate
+  BEFORE TRUNCATE ON audit_log
+  FOR EACH STATEMENT EXECUTE PROCEDURE audit_log_append_only();
@@ -0,0 +1,23 @@
+-- Users flagging a club's name or description. Open reports on the same
+-- club are one item in the admins' queue and are resolved together.
+CREATE TABLE club_reports (
+  id SERIAL PRIMARY KEY,
+  club_id INT NOT NULL,
+  reporter_id INT,
+  reason TEXT NOT NULL CHECK (reason IN ('offensive_name', 'offensive_description', 'spam', 'harassment', 'other')),
+  details TEXT NOT NULL DEFAULT '',
+  status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'dismissed', 'warned', 'hidden', 'deleted')),
+  -- What the admin told the reporters and, when warned, the moderators.
+  resolution_note TEXT,
+  resolved_by INT,
+  resolved_at timestamp with TIME ZONE,
+  created_at timestamp with TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  CONSTRAINT report_club_id_exists FOREIGN KEY(club_id) REFERENCES clubs(id) ON DELETE CASCADE,
+  CONSTRAINT report_reporter_id_exists FOREIGN KEY(reporter_id) REFERENCES users(id) ON DELETE SET NULL,
+  CONSTRAINT report_resolved_by_exists FOREIGN KEY(resolved_by) REFERENCES users(id) ON DELETE SET NULL,
+  CONSTRAINT report_resolved CHECK ((status = 'open') = (resolved_at IS NULL))
+);
+
+-- One open report per user and club, reporting again adds nothing.
+CREATE UNIQUE INDEX one_open_report_per_user ON club_reports (club_id, reporter_id) WHERE status = 'open';
+CREATE INDEX club_reports_queue_idx ON club_reports (club_id, created_at) WHERE status = 'open';
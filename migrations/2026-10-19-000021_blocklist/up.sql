@@ -0,0 +1,18 @@
+-- Terms club names and descriptions may not contain, managed by admins.
+CREATE TABLE blocklist_rules (
+  id SERIAL PRIMARY KEY,
+  pattern TEXT NOT NULL CHECK (pattern <> ''),
+  -- exact: the whole field, word: whole words anywhere, regex: a regular expression.
+  match_type TEXT NOT NULL CHECK (match_type IN ('exact', 'word', 'regex')),
+  -- reject: refuse the submission, review: let it through into the review queue.
+  severity TEXT NOT NULL CHECK (severity IN ('reject', 'review')),
+  applies_to TEXT NOT NULL DEFAULT 'both' CHECK (applies_to IN ('name', 'body', 'both')),
+  -- Rules start disabled so they can be tried against existing clubs first.
+  enabled BOOLEAN NOT NULL DEFAULT FALSE,
+  note TEXT NOT NULL DEFAULT '',
+  created_by INT,
+  created_at timestamp with TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  CONSTRAINT blocklist_rule_created_by_exists FOREIGN KEY(created_by) REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE INDEX blocklist_rules_enabled_idx ON blocklist_rules (id) WHERE enabled;
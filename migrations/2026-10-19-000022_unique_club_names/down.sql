@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+-- Renamed duplicates keep their new names.
+DROP INDEX clubs_normalized_name_idx;
+CREATE INDEX clubs_normalized_name_idx ON clubs (normalize_club_name(name));
@@ -0,0 +1,9 @@
+-- Names that only differ in case or spacing were only refused by the application, two requests could still race past it.
+-- The oldest club keeps its name, the others get their id appended so the unique index can be built.
+UPDATE clubs SET name = left(clubs.name, 64 - length(' #' || clubs.id::TEXT)) || ' #' || clubs.id::TEXT
+FROM clubs older
+WHERE normalize_club_name(older.name) = normalize_club_name(clubs.name)
+  AND older.id < clubs.id;
+
+DROP INDEX clubs_normalized_name_idx;
+CREATE UNIQUE INDEX clubs_normalized_name_idx ON clubs (normalize_club_name(name));
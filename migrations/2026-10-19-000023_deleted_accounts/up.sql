@@ -0,0 +1,6 @@
+-- Accounts their owners deleted, by the SHA-256 of the email. Google sign ins issued
+-- before deleted_at are refused so a still valid token can't bring the account back.
+CREATE TABLE deleted_accounts (
+  email_hash TEXT PRIMARY KEY,
+  deleted_at timestamp with TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -163,7 +163,7 @@ impl Component for ClubCard {
 			// Deletes the club represented by this card.
 			Msg::Delet => {
 				// opens confirm dialog
-				let result = confirm("Are you sure you want to delete?");
+				let result = confirm("Are you sure you want to delete? The head moderator or an admin can still restore it for a while.");
 				if result {
 					let req =
 						Request::delete(format!("/api/clubs/{}", self.props.details.unwrap().id))
@@ -391,7 +391,30 @@ impl Component for ClubCard {
 				}
 				<div ref={self.body_ref.clone()} class="club-card">
 					<div class="club-card-header">
-						<h1>{self.props.details.unwrap().name.clone()}</h1>
+						<h1>
+							{self.props.details.unwrap().name.clone()}
+							{
+								if self.props.details.unwrap().is_verified {
+									html! {
+										<abbr class="club-card-verified" data_title="Verified"><span class="material-icons">{"verified"}</span></abbr>
+									}
+								} else {
+									html! {
+										<>
+										</>
+									}
+								}
+							}
+						</h1>
+						{
+							// Only the club's own members get to see clubs that haven't been approved yet.
+							match self.props.details.unwrap().approval_status.as_str() {
+								"pending" => html! { <p class="club-card-review-status">{"Awaiting review"}</p> },
+								"changes_requested" => html! { <p class="club-card-review-status">{"Changes requested"}</p> },
+								"rejected" => html! { <p class="club-card-review-status">{"Not approved"}</p> },
+								_ => html! { <></> },
+							}
+						}
 					</div>
 
 					<hr/>
@@ -399,7 +422,7 @@ impl Component for ClubCard {
 
 					<div class="club-card-body">
 						<div id="left-col">
-							<img src={format!("/assets/clubs/{}.png", self.props.details.unwrap().id)}/>
+							<img src={self.props.details.unwrap().logo_src(256)}/>
 						</div>
 
 						<div id="right-col">
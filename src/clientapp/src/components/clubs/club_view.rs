@@ -1,10 +1,6 @@
-use std::cmp::Ordering;
-
 use anyhow::*;
 
 
-use wasm_bindgen::JsCast;
-use web_sys::{HtmlButtonElement};
 use yew::{
 	format::{Json, Nothing},
 	prelude::*,
@@ -37,19 +33,58 @@ pub struct ClubView {
 	auth_details_fetch_state: FetchState<AuthDetails>,
 	user_details_fetch_state: FetchState<UserDetails>,
 
-	interested_radio_button_ref: NodeRef,
-	most_popular_radio_button_ref: NodeRef,
-	moderated_radio_button_ref: NodeRef,
+	rank: Rank,
+	category: Option<i32>,
+	tags: Vec<String>,
+	facets: Option<ClubFacets>,
 	clubs: Vec<ClubDetails>,
+	next_cursor: Option<String>,
 	show_cards: bool,
 }
 
 #[derive(Properties, PartialEq, Clone)]
 pub struct Props {
-	#[prop_or(Mlk::new(None))]
-	pub search_filter_function: Mlk<Option<fn(&String, &ClubDetails) -> bool>>,
 	#[prop_or(None)]
-	pub search_filter_text: Option<String>,
+	pub search_text: Option<String>,
+}
+
+// Each rank maps onto the filter and sort query parameters of /api/clubs, the
+// server does all of the searching and ordering.
+#[derive(PartialEq, Clone, Copy)]
+pub enum Rank {
+	Relevant,
+	Interested,
+	MostPopular,
+	Moderated,
+	Newest,
+	ExpiringSoon,
+	Alphabetical,
+}
+
+impl Rank {
+	fn query(&self) -> &'static str {
+		match self {
+			Rank::Relevant => "",
+			Rank::Interested => "filter=member",
+			Rank::MostPopular => "sort=popular",
+			Rank::Moderated => "filter=moderator",
+			Rank::Newest => "sort=newest",
+			Rank::ExpiringSoon => "sort=expiring",
+			Rank::Alphabetical => "sort=alphabetical",
+		}
+	}
+
+	fn label(&self) -> &'static str {
+		match self {
+			Rank::Relevant => "Relevant",
+			Rank::Interested => "Interested",
+			Rank::MostPopular => "Most popular",
+			Rank::Moderated => "Moderated",
+			Rank::Newest => "Newest",
+			Rank::ExpiringSoon => "Expiring soon",
+			Rank::Alphabetical => "A to Z",
+		}
+	}
 }
 
 pub enum Msg {
@@ -61,7 +96,7 @@ pub enum Msg {
 	// Receives
 	ReceiveUserDetails(Option<UserDetails>),
 	ReceiveAuthDetails(Option<AuthDetails>),
-	ReceiveClubDetails(Option<Vec<ClubDetails>>),
+	ReceiveClubDetails(Option<ClubPage>),
 
 	// Other
 	RequestLogin,
@@ -70,9 +105,12 @@ pub enum Msg {
 	FakeGettingClubs,
 	Ignore,
 
-	// Updates which rank to use. The thing wrapped in the enum is the button that was just selected
-	UpdateRankState(HtmlButtonElement),
-	UpdateClubSort,
+	// Updates which rank to use and fetches the first page again
+	UpdateRankState(Rank),
+	// Filter chips, selecting the active one again clears it
+	ToggleCategory(i32),
+	ToggleTag(String),
+	LoadMoreClubs,
 }
 
 enum Redirect {
@@ -104,23 +142,6 @@ impl ClubView {
 		}
 	}
 
-	pub fn get_radio_buttons(&self) -> [HtmlButtonElement; 3] {
-		let interested_button = self
-			.interested_radio_button_ref
-			.cast::<HtmlButtonElement>()
-			.unwrap();
-		let moderated_button = self
-			.moderated_radio_button_ref
-			.cast::<HtmlButtonElement>()
-			.unwrap();
-		let popular_button = self
-			.most_popular_radio_button_ref
-			.cast::<HtmlButtonElement>()
-			.unwrap();
-
-		[interested_button, moderated_button, popular_button]
-	}
-
 	pub fn make_cards(&self) -> Html {
 		let mut i = 0.1;
 
@@ -142,49 +163,88 @@ impl ClubView {
 		}
 	}
 
-	pub fn sort_clubs(&mut self) {
-		let [interested_button, moderated_button, popular_button] = self.get_radio_buttons();
+	pub fn make_filter_chips(&self) -> Html {
+		let facets = match &self.facets {
+			Some(facets) => facets,
+			None => {
+				return html! {
+					<>
+					</>
+				}
+			}
+		};
+
+		html! {
+			<>
+				<h3><i>{"Filter by..."}</i></h3>
 
-		// Unsurprisingly, these sort functions sort in ascending order. However, this is not super useful
-		// when you want the items you consider to be "higher" to be towards the front of the list (which is
-		// basically descending order). As such these are basically sorting backwards with "greater" items
-		// at lower indices. That way, clubs that are meeting whatever qualifications set forth by
-		// the ranker are towards the top of the page when displayed.
+				<div class="ranks filter-chips">
+					{
+						for facets.categories.iter().filter_map(|facet| facet.id.map(|id| (id, facet))).map(|(id, facet)| {
+							let on_clicc = self.link.callback(move |_e: MouseEvent| Msg::ToggleCategory(id));
 
-		if interested_button.class_list().contains("active-rank") {
-			self.clubs.sort_by(|x, y| {
-				if x.is_member == y.is_member {
-					Ordering::Equal
-				} else {
-					if x.is_member && !y.is_member {
-						Ordering::Less
-					} else {
-						Ordering::Greater
+							html! {
+								<button onclick=on_clicc class=classes!("rank-button", if self.category == Some(id) { Some("active-rank") } else { None })>
+									<span class="material-icons">
+										{"category"}
+									</span>
+									{format!("{} ({})", facet.name, facet.count)}
+								</button>
+							}
+						})
 					}
-				}
-			});
-		} else if moderated_button.class_list().contains("active-rank") {
-			self.clubs.sort_by(|x, y| {
-				if x.is_moderator == y.is_moderator {
-					Ordering::Equal
-				} else {
-					if (x.is_moderator == "true" || x.is_moderator == "head")
-						&& y.is_moderator == "false"
+
 					{
-						Ordering::Less
-					} else {
-						Ordering::Greater
+						for facets.tags.iter().map(|facet| {
+							let tag = facet.name.clone();
+							let on_clicc = self.link.callback(move |_e: MouseEvent| Msg::ToggleTag(tag.clone()));
+
+							html! {
+								<button onclick=on_clicc class=classes!("rank-button", if self.tags.contains(&facet.name) { Some("active-rank") } else { None })>
+									<span class="material-icons">
+										{"tag"}
+									</span>
+									{format!("{} ({})", facet.name, facet.count)}
+								</button>
+							}
+						})
 					}
-				}
-			});
-		} else if popular_button.class_list().contains("active-rank") {
-			// This is pretty big brain if you ask me. Since it's sorting in ascending
-			// order by member count, all you need to do is make the higher member counts
-			// appear "lower" than the lower member counts. An incredibly simple way
-			// to do this is to just negate the member count.
-			self.clubs.sort_by_key(|x| -x.member_count);
+				</div>
+			</>
 		}
 	}
+
+	pub fn clubs_url(&self, cursor: Option<&String>) -> String {
+		let mut params = vec![self.rank.query().to_owned()];
+
+		if let Some(text) = &self.props.search_text {
+			params.push(format!(
+				"q={}",
+				String::from(js_sys::encode_uri_component(text))
+			));
+		}
+
+		if let Some(category) = self.category {
+			params.push(format!("category={}", category));
+		}
+
+		for tag in &self.tags {
+			params.push(format!(
+				"tag={}",
+				String::from(js_sys::encode_uri_component(tag))
+			));
+		}
+
+		if let Some(cursor) = cursor {
+			params.push(format!(
+				"cursor={}",
+				String::from(js_sys::encode_uri_component(cursor))
+			);
+		}
+
+		params.retain(|e| e.len() > 0);
+		format!("/api/clubs?{}", params.join("&"))
+	}
 }
 
 impl Component for ClubView {
@@ -194,7 +254,6 @@ impl Component for ClubView {
 	fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
 		Self {
 			link,
-			props,
 			fetch_tasks: vec![],
 			redirect: No,
 			show_dialog: false,
@@ -202,10 +261,17 @@ impl Component for ClubView {
 			clubs_fetch_state: FetchState::Waiting,
 			auth_details_fetch_state: FetchState::Waiting,
 			user_details_fetch_state: FetchState::Waiting,
-			moderated_radio_button_ref: NodeRef::default(),
-			interested_radio_button_ref: NodeRef::default(),
-			most_popular_radio_button_ref: NodeRef::default(),
+			rank: if props.search_text.is_some() {
+				Rank::Relevant
+			} else {
+				Rank::Newest
+			},
+			props,
+			category: None,
+			tags: vec![],
+			facets: None,
 			clubs: vec![],
+			next_cursor: None,
 			show_cards: true,
 		}
 	}
@@ -320,14 +386,21 @@ impl Component for ClubView {
 
 			GetClubDetails(_id) => {
 				self.clubs_fetch_state = FetchState::Waiting;
+				self.clubs.clear();
+				self.next_cursor = None;
+				self.link.send_message(LoadMoreClubs);
+			}
 
-				let req =
-					yew::services::fetch::Request::get("/api/clubs").body(yew::format::Nothing);
+			LoadMoreClubs => {
+				let req = yew::services::fetch::Request::get(
+					self.clubs_url(self.next_cursor.as_ref()),
+				)
+				.body(yew::format::Nothing);
 
 				match req {
 					Ok(req) => {
 						let callback = self.link.callback(
-							|response: Response<Json<Result<Vec<ClubDetails>, anyhow::Error>>>| {
+							|response: Response<Json<Result<ClubPage, anyhow::Error>>>| {
 								match response.status() {
 									StatusCode::OK => {
 										tell!("Got club details");
@@ -390,22 +463,14 @@ impl Component for ClubView {
 			}
 
 			ReceiveClubDetails(deets) => {
-				self.clubs_fetch_state = if let Some(deet) = deets {
-					let mut v = deet;
+				self.clubs_fetch_state = if let Some(page) = deets {
+					self.clubs.extend(page.clubs);
+					self.next_cursor = page.next_cursor;
 
-					if let Some(f) = self.props.search_filter_function.unwrap() {
-						let search_text = self.props.search_filter_text.as_ref().expect("If you provide a search_filter_function to this component you must also provide search_filter_text.");
-						v.retain(|e| f(&search_text, e))
+					if page.facets.is_some() {
+						self.facets = page.facets;
 					}
 
-					self.clubs = v;
-
-					self.interested_radio_button_ref
-						.cast::<HtmlButtonElement>()
-						.unwrap()
-						.click();
-					self.sort_clubs();
-
 					FetchState::Done(())
 				} else {
 					FetchState::Failed(Some(anyhow!(
@@ -432,22 +497,29 @@ impl Component for ClubView {
 				self.clubs_fetch_state = FetchState::Done(());
 			}
 
-			UpdateRankState(el) => {
-				self.show_cards = false;
-				for butt in self.get_radio_buttons() {
-					if butt.class_list().contains("active-rank") {
-						butt.class_list().remove_1("active-rank").unwrap();
-					}
+			UpdateRankState(rank) => {
+				if self.rank != rank {
+					self.rank = rank;
+					self.link.send_message(GetClubDetails(None));
 				}
+			}
 
-				el.class_list().add_1("active-rank").unwrap();
-
-				self.link.send_message(Msg::UpdateClubSort);
+			ToggleCategory(category) => {
+				self.category = if self.category == Some(category) {
+					None
+				} else {
+					Some(category)
+				};
+				self.link.send_message(GetClubDetails(None));
 			}
 
-			UpdateClubSort => {
-				self.sort_clubs();
-				self.show_cards = true;
+			ToggleTag(tag) => {
+				if self.tags.contains(&tag) {
+					self.tags.retain(|e| e != &tag);
+				} else {
+					self.tags.push(tag);
+				}
+				self.link.send_message(GetClubDetails(None));
 			}
 		}
 
@@ -455,16 +527,37 @@ impl Component for ClubView {
 	}
 
 	fn change(&mut self, props: Self::Properties) -> ShouldRender {
-		self.props = props;
+		if self.props != props {
+			// A new search starts out by relevance, clearing it drops the rank that needs one
+			if self.props.search_text != props.search_text {
+				if props.search_text.is_some() {
+					self.rank = Rank::Relevant;
+				} else if self.rank == Rank::Relevant {
+					self.rank = Rank::Newest;
+				}
+			}
+			self.props = props;
+			self.link.send_message(Msg::GetClubDetails(None));
+		}
+
 		true
 	}
 
 	fn view(&self) -> Html {
-		let on_clicc = self.link.callback(|e: MouseEvent| {
-			let target = e.target().unwrap().dyn_into::<HtmlButtonElement>().unwrap();
-
-			Msg::UpdateRankState(target)
-		});
+		let on_load_more = self.link.callback(|_e: MouseEvent| Msg::LoadMoreClubs);
+
+		let mut ranks = vec![
+			Rank::Interested,
+			Rank::MostPopular,
+			Rank::Moderated,
+			Rank::Newest,
+			Rank::ExpiringSoon,
+			Rank::Alphabetical,
+		];
+
+		if self.props.search_text.is_some() {
+			ranks.insert(0, Rank::Relevant);
+		}
 
 		if let Yes(route) = &self.redirect {
 			html! {
@@ -479,25 +572,25 @@ impl Component for ClubView {
 
 
 							<div class="ranks">
-								<button onclick=on_clicc.clone() class="rank-button" ref=self.interested_radio_button_ref.clone()>
-									<span class="material-icons">
-										{"done"}
-									</span>
-									{"Interested"}
-								</button>
-								<button onclick=on_clicc.clone() class="rank-button" ref=self.most_popular_radio_button_ref.clone()>
-									<span class="material-icons">
-										{"done"}
-									</span>
-									{"Most popular"}
-								</button>
-								<button onclick=on_clicc.clone() class="rank-button" ref=self.moderated_radio_button_ref.clone()>
-									<span class="material-icons">
-										{"done"}
-									</span>
-									{"Moderated"}
-								</button>
+								{
+									for ranks.into_iter().map(|rank| {
+										let on_clicc = self.link.callback(move |_e: MouseEvent| Msg::UpdateRankState(rank));
+
+										html! {
+											<button onclick=on_clicc class=classes!("rank-button", if self.rank == rank { Some("active-rank") } else { None })>
+												<span class="material-icons">
+													{"done"}
+												</span>
+												{rank.label()}
+											</button>
+										}
+									})
+								}
 							</div>
+
+							{
+								self.make_filter_chips()
+							}
 						</div>
 					</div>
 
@@ -529,7 +622,7 @@ impl Component for ClubView {
 											<span class="bad">
 												<h2>
 													{
-														if self.props.search_filter_function.unwrap().is_some() {
+														if self.props.search_text.is_some() {
 															"No matching clubs found."
 														} else {
 															"Be the first to post your own club!"
@@ -557,11 +650,29 @@ impl Component for ClubView {
 										html! {
 											if self.show_cards {
 												html! {
-													<div class="club-view">
+													<>
+														<div class="club-view">
+															{
+																self.make_cards()
+															}
+														</div>
 														{
-															self.make_cards()
+															if self.next_cursor.is_some() {
+																html! {
+																	<div class="club-view-load-more">
+																		<button class="rank-button" onclick=on_load_more.clone()>
+																			{"Load more"}
+																		</button>
+																	</div>
+																}
+															} else {
+																html! {
+																	<>
+																	</>
+																}
+															}
 														}
-													</div>
+													</>
 												}
 											} else {
 												html! {
@@ -1,11 +1,13 @@
 pub mod club_card;
 pub mod club_view;
 pub mod pg_details;
+pub mod pg_history;
 pub mod pg_new_club;
 pub mod search;
 
 pub use club_card::ClubCard;
 pub use club_view::ClubView;
 pub use pg_details::DetailsPage;
+pub use pg_history::HistoryPage;
 pub use pg_new_club::NewClubPage;
 pub use search::SearchBar;
@@ -1,4 +1,3 @@
-use comrak::{markdown_to_html, ComrakExtensionOptions, ComrakOptions};
 use serde::{Deserialize, Serialize};
 use web_sys::{
 	Blob,
@@ -235,6 +234,13 @@ impl Component for DetailsPage {
 																</button>
 															</abbr>
 														</h1>
+														<h1 class="club-edit">
+															<abbr data_title="History">
+																<AppAnchor route=AppRoute::History {id: details.id as usize}>
+																	<span class="material-icons">{"history"}</span>
+																</AppAnchor>
+															</abbr>
+														</h1>
 													}
 												} else {
 													html! {
@@ -249,11 +255,11 @@ impl Component for DetailsPage {
 										</h3>
 									</div>
 									<div class="club-image-wrapper">
-										<img class="club-image" src={format!("/assets/clubs/{}.png", details.id)}/>
+										<img class="club-image" src={details.logo_src(1024)}/>
 										<div class="club-image-panel">
 											<ul>
 												<li>
-													{"Created by "}<img src={format!("{}", details.head_moderator.picture)}/>
+													{"Created by "}<img src={format!("{}", details.head_moderator.picture)} title={details.head_moderator.name()}/>
 												</li>
 												<li>
 													{details.member_count} {" interested"}
@@ -322,26 +328,9 @@ impl Component for DetailsPage {
 
 			let el = self.markdown_body_ref.cast::<HtmlElement>().unwrap();
 
-			el.set_inner_html(
-				if let Some(md) = Some(details.body.clone()) {
-					let md = markdown_to_html(
-						md.as_str(),
-						&ComrakOptions {
-							extension: ComrakExtensionOptions {
-								tagfilter: false,
-								..ComrakExtensionOptions::default()
-							},
-							..ComrakOptions::default()
-						},
-					);
-
-					self.markdown_rendered = true;
-					ammonia::clean(md.as_str())
-				} else {
-					String::from("")
-				}
-				.as_str(),
-			);
+			// Rendered and sanitized by the server when the club was saved
+			el.set_inner_html(details.body_html.as_str());
+			self.markdown_rendered = true;
 		}
 	}
 }
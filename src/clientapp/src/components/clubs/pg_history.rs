@@ -0,0 +1,373 @@
+use gloo_dialogs::confirm;
+use yew::{
+	format::{Json, Nothing},
+	prelude::*,
+	services::fetch::{FetchService, FetchTask, Request, Response, StatusCode},
+};
+
+use crate::{components::core::router::*, tell, types::*};
+
+// Lists the revisions of a club's name and body, shows what changed between
+// any two of them and lets the head moderator go back to an older one.
+pub struct HistoryPage {
+	link: ComponentLink<Self>,
+	props: Props,
+
+	details: Option<ClubDetails>,
+	revisions: Option<Vec<ClubRevisionSummary>>,
+	diff: Option<ClubRevisionDiff>,
+
+	// The two revisions being compared, older one first.
+	from: Option<i32>,
+	to: Option<i32>,
+
+	details_task: Option<FetchTask>,
+	revisions_task: Option<FetchTask>,
+	diff_task: Option<FetchTask>,
+	revert_task: Option<FetchTask>,
+
+	redirect_to_details: bool,
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct Props {
+	pub id: usize,
+}
+
+pub enum Msg {
+	GetDetailsDone(ClubDetails),
+	GetRevisions,
+	GetRevisionsDone(Vec<ClubRevisionSummary>),
+	SelectFrom(i32),
+	SelectTo(i32),
+	GetDiff,
+	GetDiffDone(ClubRevisionDiff),
+	Revert(i32),
+	RevertDone(ClubDetails),
+	// Someone else changed the club since the page loaded, carries the club as it is now.
+	RevertConflict(ClubDetails),
+	// Sent when the page can't be shown, e.g. the user isn't a moderator.
+	Leave,
+	Ignore,
+}
+
+impl HistoryPage {
+	fn author_name(revision: &ClubRevisionSummary) -> String {
+		match &revision.author {
+			Some(author) => author.name(),
+			None => "Someone".to_owned(),
+		}
+	}
+
+	fn view_revision(&self, revision: &ClubRevisionSummary) -> Html {
+		let id = revision.id;
+		let select_from = self.link.callback(move |_: MouseEvent| Msg::SelectFrom(id));
+		let select_to = self.link.callback(move |_: MouseEvent| Msg::SelectTo(id));
+		let revert = self.link.callback(move |_: MouseEvent| Msg::Revert(id));
+		let is_head = self.details.as_ref().map(|details| details.is_moderator == "head").unwrap_or(false);
+
+		html! {
+			<li class="revision">
+				<button class=classes!(if self.from == Some(id) { "active-rank" } else { "" }) onclick=select_from>{"from"}</button>
+				<button class=classes!(if self.to == Some(id) { "active-rank" } else { "" }) onclick=select_to>{"to"}</button>
+				<span class="revision-name">{revision.name.clone()}</span>
+				<span class="revision-meta">
+					{format!("{} on {}", Self::author_name(revision), revision.created_at.format("%A, %B %e %Y %H:%M"))}
+					{
+						match revision.reverted_from {
+							Some(reverted_from) => format!(", reverted to #{}", reverted_from),
+							None => String::new(),
+						}
+					}
+				</span>
+				{
+					if is_head {
+						html! {
+							<abbr data_title="Revert to this revision">
+								<button onclick=revert><span class="material-icons">{"restore"}</span></button>
+							</abbr>
+						}
+					} else {
+						html! {
+							<>
+							</>
+						}
+					}
+				}
+			</li>
+		}
+	}
+
+	fn view_diff_lines(lines: &Vec<DiffLine>) -> Html {
+		html! {
+			<pre class="revision-diff">
+				{
+					for lines.iter().map(|line| {
+						let (class, prefix) = match line.op.as_str() {
+							"insert" => ("diff-insert", "+ "),
+							"delete" => ("diff-delete", "- "),
+							_ => ("diff-equal", "  "),
+						};
+
+						html! {
+							<div class=class>{format!("{}{}", prefix, line.text)}</div>
+						}
+					})
+				}
+			</pre>
+		}
+	}
+}
+
+impl Component for HistoryPage {
+	type Message = Msg;
+	type Properties = Props;
+
+	fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+		let details_task = Request::get(format!("/api/clubs/{}", props.id))
+			.body(Nothing)
+			.ok()
+			.and_then(|req| {
+				let callback = link.callback(
+					|response: Response<Json<Result<ClubDetails, anyhow::Error>>>| {
+						match response.into_body() {
+							Json(Ok(details)) => Msg::GetDetailsDone(details),
+							Json(Err(_)) => Msg::Leave,
+						}
+					},
+				);
+
+				FetchService::fetch(req, callback).ok()
+			});
+
+		link.send_message(Msg::GetRevisions);
+
+		Self {
+			link,
+			props,
+			details: None,
+			revisions: None,
+			diff: None,
+			from: None,
+			to: None,
+			details_task,
+			revisions_task: None,
+			diff_task: None,
+			revert_task: None,
+			redirect_to_details: false,
+		}
+	}
+
+	fn update(&mut self, msg: Self::Message) -> ShouldRender {
+		match msg {
+			Msg::GetDetailsDone(details) => {
+				self.details_task = None;
+				self.details = Some(details);
+			}
+
+			Msg::GetRevisions => {
+				let req = Request::get(format!("/api/clubs/{}/revisions", self.props.id)).body(Nothing);
+
+				match req {
+					Ok(req) => {
+						let callback = self.link.callback(
+							|response: Response<Json<Result<Vec<ClubRevisionSummary>, anyhow::Error>>>| {
+								match response.status() {
+									StatusCode::OK => match response.into_body() {
+										Json(Ok(revisions)) => Msg::GetRevisionsDone(revisions),
+										Json(Err(err)) => {
+											tell!("Failed to read club history: {:?}", err);
+											Msg::Leave
+										}
+									},
+
+									_ => {
+										tell!("Failed to get club history: status code {}", response.status());
+										Msg::Leave
+									}
+								}
+							},
+						);
+
+						match FetchService::fetch(req, callback) {
+							Ok(task) => self.revisions_task = Some(task),
+							Err(err) => tell!("Failed to fetch club history: {:?}", err),
+						}
+					}
+
+					Err(err) => tell!("Failed to build request for club history: {:?}", err),
+				}
+			}
+
+			Msg::GetRevisionsDone(revisions) => {
+				self.revisions_task = None;
+
+				// Start out comparing the latest revision to the one before it.
+				self.to = revisions.get(0).map(|revision| revision.id);
+				self.from = revisions.get(1).map(|revision| revision.id).or(self.to);
+				self.revisions = Some(revisions);
+				self.link.send_message(Msg::GetDiff);
+			}
+
+			Msg::SelectFrom(id) => {
+				self.from = Some(id);
+				self.link.send_message(Msg::GetDiff);
+			}
+
+			Msg::SelectTo(id) => {
+				self.to = Some(id);
+				self.link.send_message(Msg::GetDiff);
+			}
+
+			Msg::GetDiff => {
+				if let (Some(from), Some(to)) = (self.from, self.to) {
+					let req = Request::get(format!(
+						"/api/clubs/{}/revisions/diff?from={}&to={}",
+						self.props.id, from, to
+					))
+					.body(Nothing);
+
+					match req {
+						Ok(req) => {
+							let callback = self.link.callback(
+								|response: Response<Json<Result<ClubRevisionDiff, anyhow::Error>>>| {
+									match response.into_body() {
+										Json(Ok(diff)) => Msg::GetDiffDone(diff),
+										Json(Err(err)) => {
+											tell!("Failed to get diff: {:?}", err);
+											Msg::Ignore
+										}
+									}
+								},
+							);
+
+							match FetchService::fetch(req, callback) {
+								Ok(task) => self.diff_task = Some(task),
+								Err(err) => tell!("Failed to fetch diff: {:?}", err),
+							}
+						}
+
+						Err(err) => tell!("Failed to build request for diff: {:?}", err),
+					}
+				}
+			}
+
+			Msg::GetDiffDone(diff) => {
+				self.diff_task = None;
+				self.diff = Some(diff);
+			}
+
+			Msg::Revert(id) => {
+				if confirm(&format!("Revert the club to revision #{}?", id)) {
+					let version = self.details.as_ref().map(|details| details.version).unwrap_or_default();
+					let req = Request::put(format!("/api/clubs/{}/revisions/{}/revert", self.props.id, id))
+						.header("If-Match", format!("\"{}\"", version))
+						.body(Nothing);
+
+					match req {
+						Ok(req) => {
+							let callback = self.link.callback(
+								|response: Response<Result<String, anyhow::Error>>| {
+									let details = response.body().as_ref().ok().and_then(|body| serde_json::from_str::<ClubDetails>(body).ok());
+
+									match (response.status(), details) {
+										(StatusCode::OK, Some(details)) => Msg::RevertDone(details),
+										(StatusCode::PRECONDITION_FAILED, Some(theirs)) => Msg::RevertConflict(theirs),
+										_ => {
+											let err = ApiErrorBody::from_response(&response);
+											tell!("Failed to revert club: {} ({})", err.error, err.code);
+											Msg::Ignore
+										}
+									}
+								},
+							);
+
+							match FetchService::fetch(req, callback) {
+								Ok(task) => self.revert_task = Some(task),
+								Err(err) => tell!("Failed to revert club: {:?}", err),
+							}
+						}
+
+						Err(err) => tell!("Failed to build request to revert club: {:?}", err),
+					}
+				}
+			}
+
+			Msg::RevertDone(details) => {
+				self.revert_task = None;
+				self.details = Some(details);
+				self.link.send_message(Msg::GetRevisions);
+			}
+
+			Msg::RevertConflict(theirs) => {
+				self.revert_task = None;
+				tell!("{} was changed while you were looking at its history, check the latest revision before reverting.", theirs.name);
+				self.details = Some(theirs);
+				self.link.send_message(Msg::GetRevisions);
+			}
+
+			Msg::Leave => {
+				self.redirect_to_details = true;
+			}
+
+			Msg::Ignore => (),
+		}
+
+		true
+	}
+
+	fn change(&mut self, _props: Self::Properties) -> ShouldRender {
+		false
+	}
+
+	fn view(&self) -> Html {
+		if self.redirect_to_details {
+			return html! {
+				<AppRedirect route=AppRoute::Details {id: self.props.id}/>
+			};
+		}
+
+		html! {
+			<div class="details-page history-page">
+				<div class="club-header">
+					<div class="club-header-line">
+						<h1 class="club-name">
+							{
+								match &self.details {
+									Some(details) => format!("History of {}", details.name),
+									None => "History".to_owned(),
+								}
+							}
+						</h1>
+					</div>
+					<AppAnchor route=AppRoute::Details {id: self.props.id}>{"back to the club"}</AppAnchor>
+				</div>
+
+				<ul class="revision-list">
+					{
+						for self.revisions.iter().flatten().map(|revision| self.view_revision(revision))
+					}
+				</ul>
+
+				{
+					match &self.diff {
+						Some(diff) => html! {
+							<div class="club-body">
+								<hr/>
+								<h2>{format!("Changes from #{} to #{}", diff.from, diff.to)}</h2>
+								<h3>{"Name"}</h3>
+								{Self::view_diff_lines(&diff.name)}
+								<h3>{"About this Club"}</h3>
+								{Self::view_diff_lines(&diff.body)}
+							</div>
+						},
+						None => html! {
+							<>
+							</>
+						},
+					}
+				}
+			</div>
+		}
+	}
+}
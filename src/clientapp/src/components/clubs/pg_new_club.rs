@@ -1,7 +1,6 @@
 
 
 use anyhow::anyhow;
-use comrak::{markdown_to_html, ComrakExtensionOptions, ComrakOptions};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use wasm_bindgen::{prelude::Closure, JsCast};
@@ -29,7 +28,7 @@ use crate::{
 	components::{Spinner},
 	event::{Amogus, EventBus},
 	tell,
-	types::{BinaryBlob, ClubDetails, FetchState},
+	types::{ApiErrorBody, BinaryBlob, ClubDetails, FetchState, MarkdownPreview},
 };
 
 pub struct NewClubPage {
@@ -41,6 +40,8 @@ pub struct NewClubPage {
 	props: Props,
 	post_task: Option<FetchTask>,
 	post_logo_task: Option<FetchTask>,
+	// Replaced on every keystroke, which cancels the render still in flight.
+	preview_task: Option<FetchTask>,
 
 	post_task_state: FetchState<()>,
 	post_logo_task_state: FetchState<()>,
@@ -61,6 +62,8 @@ pub struct NewClubPage {
 	msg_acceptor: Box<dyn yew::Bridge<crate::event::Amogus>>,
 
 	is_edit_mode: bool,
+	// The version of the club being edited, sent as If-Match.
+	edit_version: Option<i32>,
 }
 
 #[derive(Properties, Debug, Clone)]
@@ -82,9 +85,14 @@ pub enum Msg {
 	PostClub,
 	PostClubDone(i32),
 	Reset,
-	PostClubFailedDuplicateName,
+	// Sent when the server refused the club, carries its reasons.
+	PostClubFailed(ApiErrorBody),
 	ActivateEditMode(ClubDetails),
+	// Sent when someone else saved the club while it was being edited, carries their version.
+	EditConflict(ClubDetails),
 	SendCloseMessage,
+	// The server's rendering of the description, see /api/markdown/preview.
+	RenderPreviewDone(String),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -270,6 +278,7 @@ impl Component for NewClubPage {
 			markdown_preview_ref: NodeRef::default(),
 			club_name_input_ref: NodeRef::default(),
 			post_logo_task: None,
+			preview_task: None,
 			post_logo_task_state: FetchState::Waiting,
 			form_errors: None,
 			markdown_textarea_ref: NodeRef::default(),
@@ -286,6 +295,7 @@ impl Component for NewClubPage {
 			link,
 
 			is_edit_mode: false,
+			edit_version: None,
 		}
 	}
 
@@ -305,27 +315,37 @@ impl Component for NewClubPage {
 				WhichTextField::TheLongDescriptionOne => {
 					self.long_club_description_contents =
 						if value.len() > 0 { Some(value) } else { None };
-					let el = self.markdown_preview_ref.cast::<HtmlElement>().unwrap();
-
-					el.set_inner_html(
-						if let Some(md) = &self.long_club_description_contents {
-							let md = markdown_to_html(
-								md.as_str(),
-								&ComrakOptions {
-									extension: ComrakExtensionOptions {
-										tagfilter: false,
-										..ComrakExtensionOptions::default()
-									},
-									..ComrakOptions::default()
+
+					match &self.long_club_description_contents {
+						Some(md) => {
+							// Rendered by the server so the preview matches the saved club exactly
+							let request = Request::post("/api/markdown/preview")
+								.body(Json(&json!({ "body": md })))
+								.unwrap();
+
+							let callback = self.link.callback(
+								|response: Response<Json<Result<MarkdownPreview, anyhow::Error>>>| {
+									match response.into_body() {
+										Json(Ok(preview)) => Msg::RenderPreviewDone(preview.html),
+										Json(Err(err)) => {
+											tell!("Failed to render preview: {:?}", err);
+											Msg::Ignore
+										}
+									}
 								},
 							);
 
-							ammonia::clean(md.as_str())
-						} else {
-							String::from("")
+							match FetchService::fetch(request, callback) {
+								Ok(task) => self.preview_task = Some(task),
+								Err(err) => tell!("Failed to fetch preview: {:?}", err),
+							}
+						}
+
+						None => {
+							self.preview_task = None;
+							self.link.send_message(Msg::RenderPreviewDone(String::new()));
 						}
-						.as_str(),
-					);
+					}
 				}
 			},
 
@@ -350,12 +370,13 @@ impl Component for NewClubPage {
 				if let Some(el) = self.img_preview_ref.cast::<HtmlImageElement>() {
 					let src = el.src();
 
-					if src.is_empty() {
-						v.push(FormError::ClubLogo("Club logo cannot be empty.".to_owned()));
-					} else if src.bytes().len() > 1000000 {
+					// No logo is fine, the server makes up an avatar. Base64 takes 4 characters for every 3 bytes.
+					let size = src.bytes().len() * 3 / 4;
+
+					if src.starts_with("data:") && size > 4 * 1024 * 1024 {
 						v.push(FormError::ClubLogo(format!(
-							"Club logo is too large. ({} MB > 1MB)",
-							src.bytes().len() as f32 / 1000000.0
+							"Club logo is too large. ({:.1} MB > 4MB)",
+							size as f32 / (1024.0 * 1024.0)
 						)));
 					}
 				}
@@ -375,8 +396,8 @@ impl Component for NewClubPage {
 					self.long_club_description_contents.clone(),
 				) {
 					//FIXME back end often returns 422 on markdown with newlines and probably other stuff
-					// Clean your body with ammonia
-					let json = json!({"name": name, "body": ammonia::clean(&body)});
+					// The server validates and sanitizes both, its field errors end up next to the inputs
+					let json = json!({"name": name, "body": body});
 
 					let _request = if !self.is_edit_mode {
 						Request::post("/api/clubs/create")
@@ -397,32 +418,22 @@ impl Component for NewClubPage {
 							.unwrap();
 
 						let response_callback = self.link.callback(
-							|response: Response<Json<Result<Vec<ClubDetails>, anyhow::Error>>>| {
+							|response: Response<Result<String, anyhow::Error>>| {
 								match response.status() {
 									StatusCode::OK | StatusCode::ACCEPTED => {
 										tell!("Successfully post`ed club");
-										tell!("{:?}", response);
-										if let Json(thing) = response.body() {
-											Msg::PostClubDone(thing.as_ref().unwrap()[0].id)
-										} else {
-											Msg::Ignore
+										match response.body().as_ref().ok().and_then(|body| serde_json::from_str::<Vec<ClubDetails>>(body).ok()) {
+											Some(created) => Msg::PostClubDone(created[0].id),
+											None => Msg::Ignore,
 										}
 									}
 
-									StatusCode::INTERNAL_SERVER_ERROR => {
-										Msg::PostClubFailedDuplicateName
-									}
-
 									StatusCode::FORBIDDEN => {
 										// TODO make this redirect
 										Msg::Ignore
 									}
 
-									_ => {
-										tell!("Bad status receieved: {:?}", response.status());
-										//Error stuff
-										Msg::Ignore
-									}
+									_ => Msg::PostClubFailed(ApiErrorBody::from_response(&response)),
 								}
 							},
 						);
@@ -440,33 +451,26 @@ impl Component for NewClubPage {
 							"/api/clubs/{}",
 							self.props.starting_vals.as_ref().unwrap().id
 						))
+						.header("If-Match", format!("\"{}\"", self.edit_version.unwrap_or_default()))
 						.body(Json(&json))
 						.unwrap();
 
 						let response_callback = self.link.callback(
-							|response: Response<Json<Result<ClubDetails, anyhow::Error>>>| {
-								match response.status() {
-									StatusCode::OK | StatusCode::ACCEPTED => {
-										tell!("Successfully updated club");
+							|response: Response<Result<String, anyhow::Error>>| {
+								let details = response.body().as_ref().ok().and_then(|body| serde_json::from_str::<ClubDetails>(body).ok());
 
-										if let Json(thing) = response.body() {
-											Msg::PostClubDone(thing.as_ref().unwrap().id)
-										} else {
-											Msg::Ignore
-										}
+								match (response.status(), details) {
+									(StatusCode::OK | StatusCode::ACCEPTED, Some(updated)) => {
+										tell!("Successfully updated club");
+										Msg::PostClubDone(updated.id)
 									}
 
-									StatusCode::INTERNAL_SERVER_ERROR => {
-										Msg::PostClubFailedDuplicateName
-									}
+									// The body is the club as someone else saved it.
+									(StatusCode::PRECONDITION_FAILED, Some(theirs)) => Msg::EditConflict(theirs),
 
-									StatusCode::FORBIDDEN => Msg::Ignore,
+									(StatusCode::FORBIDDEN, _) => Msg::Ignore,
 
-									_ => {
-										tell!("Bad status receieved: {:?}", response.status());
-										//Error stuff
-										Msg::Ignore
-									}
+									_ => Msg::PostClubFailed(ApiErrorBody::from_response(&response)),
 								}
 							},
 						);
@@ -483,11 +487,23 @@ impl Component for NewClubPage {
 				}
 			}
 
-			Msg::PostClubFailedDuplicateName => {
+			Msg::PostClubFailed(err) => {
 				self.post_task.take();
-				self.add_form_error(FormError::ClubName(
-					"A club with this name already exists.".to_owned(),
-				));
+
+				if err.fields.is_empty() {
+					match err.code.as_str() {
+						"conflict" => self.add_form_error(FormError::ClubName(err.error)),
+						_ => tell!("Failed to save club: {} ({})", err.error, err.code),
+					}
+				} else {
+					for field in err.fields {
+						match field.field.as_str() {
+							"name" => self.add_form_error(FormError::ClubName(field.message)),
+							"body" => self.add_form_error(FormError::ClubDescription(field.message)),
+							_ => self.add_form_error(FormError::ClubLogo(field.message)),
+						}
+					}
+				}
 			}
 
 			Msg::PostClubDone(id) => {
@@ -511,16 +527,25 @@ impl Component for NewClubPage {
 					.unwrap()
 					.src();
 
-				if !img_code.starts_with("data:image/png;base64") {
-					tell!("Image was not changed. Skipping");
-					return true;
-				}
+				// Only a freshly picked file is a data URL, the server checks the bytes themselves
+				let (content_type, data) = match img_code
+					.strip_prefix("data:")
+					.and_then(|rest| rest.split_once(";base64,"))
+				{
+					Some((content_type @ ("image/png" | "image/jpeg" | "image/webp"), data)) => {
+						(content_type.to_owned(), data.to_owned())
+					}
+					_ => {
+						tell!("Image was not changed. Skipping");
+						return true;
+					}
+				};
 
-				let thing = base64::decode(img_code[22..].to_owned()).unwrap();
+				let thing = base64::decode(data).unwrap();
 				let binchilling = BinaryBlob(&thing);
 				let request = Request::put(format!("/api/clubs/{}/logo", id))
 					.header("Content-Length", thing.len())
-					.header("Content-Type", "image/png")
+					.header("Content-Type", content_type)
 					.body(binchilling)
 					.unwrap();
 
@@ -528,7 +553,7 @@ impl Component for NewClubPage {
 					self.link
 						.callback(|response: Response<yew::format::Binary>| {
 							match response.status() {
-								StatusCode::ACCEPTED => {
+								StatusCode::OK | StatusCode::ACCEPTED => {
 									tell!("Successfully put`ed logo");
 									Msg::PostClubLogoDone
 								}
@@ -572,12 +597,29 @@ impl Component for NewClubPage {
 				}
 			}
 
+			Msg::EditConflict(theirs) => {
+				self.post_task.take();
+
+				let keep_mine = gloo_dialogs::confirm(&format!(
+					"Someone else saved changes to {} while you were editing it. Press OK to overwrite their changes with yours, or Cancel to load their version.",
+					theirs.name
+				));
+
+				if keep_mine {
+					self.edit_version = Some(theirs.version);
+					self.link.send_message(Msg::PostClub);
+				} else {
+					self.link.send_message(Msg::ActivateEditMode(theirs));
+				}
+			}
+
 			Msg::ActivateEditMode(deets) => {
 				self.is_edit_mode = true;
+				self.edit_version = Some(deets.version);
 				self.img_preview_ref
 					.cast::<HtmlImageElement>()
 					.unwrap()
-					.set_src(format!("/assets/clubs/{}.png", deets.id).as_str());
+					.set_src(deets.logo_src(256).as_str());
 				self.markdown_textarea_ref
 					.cast::<HtmlInputElement>()
 					.unwrap()
@@ -597,6 +639,14 @@ impl Component for NewClubPage {
 			Msg::SendCloseMessage => {
 				self.close_editor();
 			}
+
+			Msg::RenderPreviewDone(html) => {
+				self.preview_task = None;
+
+				if let Some(el) = self.markdown_preview_ref.cast::<HtmlElement>() {
+					el.set_inner_html(html.as_str());
+				}
+			}
 		}
 		true
 	}
@@ -641,7 +691,7 @@ impl Component for NewClubPage {
 					}
 					<div class="image-input">
 						<img ref=self.img_preview_ref.clone() class="club-logo"/>
-						<input ref=self.img_selector_ref.clone() oninput=image_input_callback type="file" name="file" id="file" class="inputfile" accept="image/png"/>
+						<input ref=self.img_selector_ref.clone() oninput=image_input_callback type="file" name="file" id="file" class="inputfile" accept="image/png,image/jpeg,image/webp"/>
 						<label for="file">{"Select a club logo"}</label>
 						<small>{"(png files only. <= 1MB in size)"}</small>
 					</div>
@@ -1,4 +1,3 @@
-use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
 use gloo_timers::callback::Timeout;
 
 
@@ -8,7 +7,6 @@ use yew::{agent::Dispatcher, prelude::*, Properties};
 use crate::{
 	components::clubs::ClubView,
 	event::{Amogus, EventBus},
-	types::ClubDetails,
 };
 
 // TODO Future improvements
@@ -38,15 +36,9 @@ impl ClubViewEmitter {
 	}
 
 	fn get(&self, search_text: Option<String>) -> Html {
-		let f: fn(&String, &ClubDetails) -> bool = |filter, club| {
-			let matcher = SkimMatcherV2::default();
-			return matcher.fuzzy_match(&club.name, filter).is_some()
-				|| matcher.fuzzy_match(&club.body, filter).is_some();
-		};
-
 		if self.show && search_text.is_some() {
 			html! {
-				<ClubView search_filter_function=crate::types::Mlk::new(Some(f)) search_filter_text=search_text/>
+				<ClubView search_text=search_text/>
 			}
 		} else {
 			html! {
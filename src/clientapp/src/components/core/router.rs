@@ -24,6 +24,10 @@ pub enum AppRoute {
 	Search,
 	#[to = "/new_club"]
 	ClubForm,
+	#[to = "/settings/profile"]
+	ProfileSettings,
+	#[to = "/details/{id}/history"]
+	History { id: usize },
 	#[to = "/details/{id}"]
 	Details { id: usize },
 }
@@ -62,6 +62,9 @@ pub enum Msg {
 pub struct Props {
 	pub username: String,
 	pub pfp_url: String,
+	// Guests get a sign in link instead of the profile dropdown
+	#[prop_or(false)]
+	pub is_guest: bool,
 }
 
 impl Component for ToolbarComponent {
@@ -242,17 +245,36 @@ impl Component for ToolbarComponent {
 								</div>
 
 								<div class="toolbar-inner-component-right-side">
-									<button class="dropdown-btn" onclick=on_dropdown_button_clicked>
-										<img class="toolbar-pfp" src=self.props.pfp_url.clone()/>
-										<h1>
-										{
-											self.props.username.clone()
+									{
+										if self.props.is_guest {
+											html! {
+												<div class="toolbar-text-link">
+													<AppAnchor route=AppRoute::Login>{ "sign in" }</AppAnchor>
+												</div>
+											}
+										} else {
+											html! {
+												<button class="dropdown-btn" onclick=on_dropdown_button_clicked>
+													<img class="toolbar-pfp" src=self.props.pfp_url.clone()/>
+													<h1>
+													{
+														self.props.username.clone()
+													}
+													</h1>
+												</button>
+											}
 										}
-										</h1>
-									</button>
+									}
 								</div>
 
 								<div class="pfp-button-dropdown" ref=self.dropdown_content_ref.clone()>
+										<AppAnchor route=AppRoute::ProfileSettings>
+											<span class="material-icons">
+												{"manage_accounts"}
+											</span>
+
+											{"Profile"}
+										</AppAnchor>
 										<button onclick=sign_out_cb>
 											<span class="material-icons">
 												{"exit_to_app"}
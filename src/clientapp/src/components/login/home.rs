@@ -10,7 +10,9 @@ use crate::{
 		core::{router::*, *},
 		ClubView,
 		DetailsPage,
+		HistoryPage,
 		NewClubPage,
+		ProfileSettingsPage,
 		SearchBar,
 	},
 	tell,
@@ -65,13 +67,9 @@ impl Component for Home {
 										tell!("OK response");
 										let Json(body) = response.into_body();
 
+										// Guests are let through too, they can browse public clubs
 										match body {
-											Ok(deets) => match deets.auth_level {
-												AuthLevel::Guest => Msg::FailToReceiveUserInfo(
-													Some(anyhow!("Guests must log in")),
-												),
-												_ => Msg::ReceieveUserInfo(deets),
-											},
+											Ok(deets) => Msg::ReceieveUserInfo(deets),
 											Err(err) => Msg::FailToReceiveUserInfo(Some(err)),
 										}
 									}
@@ -139,10 +137,22 @@ impl Home {
 			},
 
 			FetchState::Done(details) => {
+				let is_guest = matches!(details.auth_level, AuthLevel::Guest);
+
 				html! {
 					<div>
 						<div id="root">
-							<Toolbar pfp_url=details.picture.as_ref().unwrap().clone() username=format!("{} {}",details.first_name.clone().unwrap(), details.last_name.clone().unwrap())/>
+							{
+								if is_guest {
+									html! {
+										<Toolbar pfp_url=String::new() username=String::new() is_guest=true/>
+									}
+								} else {
+									html! {
+										<Toolbar pfp_url=details.picture.as_ref().unwrap().clone() username=format!("{} {}",details.first_name.clone().unwrap(), details.last_name.clone().unwrap())/>
+									}
+								}
+							}
 
 							{
 								match &self.props.route {
@@ -165,8 +175,14 @@ impl Home {
 									},
 
 									AppRoute::ClubForm => {
-										html! {
-											<NewClubPage/>
+										if is_guest {
+											html! {
+												<AppRedirect route=AppRoute::Login/>
+											}
+										} else {
+											html! {
+												<NewClubPage/>
+											}
 										}
 									},
 
@@ -176,6 +192,30 @@ impl Home {
 										}
 									}
 
+									AppRoute::ProfileSettings => {
+										if is_guest {
+											html! {
+												<AppRedirect route=AppRoute::Login/>
+											}
+										} else {
+											html! {
+												<ProfileSettingsPage/>
+											}
+										}
+									},
+
+									AppRoute::History{id} => {
+										if is_guest {
+											html! {
+												<AppRedirect route=AppRoute::Login/>
+											}
+										} else {
+											html! {
+												<HistoryPage id=*id/>
+											}
+										}
+									}
+
 									_ => unreachable!()
 								}
 							}
@@ -2,11 +2,13 @@ pub mod clubs;
 pub mod coolshit;
 pub mod core;
 pub mod login;
+pub mod users;
 
 pub use ::core::*;
 pub use clubs::*;
 pub use coolshit::*;
 pub use login::*;
+pub use users::*;
 pub use search::*;
 
 #[macro_use]
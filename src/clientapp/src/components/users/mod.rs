@@ -0,0 +1,3 @@
+pub mod pg_settings;
+
+pub use pg_settings::ProfileSettingsPage;
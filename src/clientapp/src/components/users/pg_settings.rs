@@ -0,0 +1,377 @@
+use gloo_dialogs::confirm;
+use yew::{
+	format::{Json, Nothing},
+	prelude::*,
+	services::fetch::{FetchService, FetchTask, Request, Response, StatusCode},
+};
+
+use crate::{components::core::router::*, tell, types::*};
+
+// Lets the signed in user edit their profile and choose who sees each field,
+// download what Saturn stores about them or delete their account.
+pub struct ProfileSettingsPage {
+	link: ComponentLink<Self>,
+
+	settings: Option<ProfileSettings>,
+	// Kept as typed so a half written year doesn't get thrown away.
+	graduation_year: String,
+	errors: Option<ApiErrorBody>,
+	saved: bool,
+
+	get_task: Option<FetchTask>,
+	save_task: Option<FetchTask>,
+	delete_task: Option<FetchTask>,
+
+	deleted: bool,
+}
+
+#[derive(Clone, Copy)]
+pub enum Field {
+	Email,
+	DisplayName,
+	Pronouns,
+	Bio,
+	Major,
+	GraduationYear,
+}
+
+pub enum Msg {
+	GetSettingsDone(ProfileSettings),
+	Edit(Field, String),
+	SetVisibility(Field, String),
+	Save,
+	SaveDone(ProfileSettings),
+	SaveFailed(ApiErrorBody),
+	DeleteAccount,
+	DeleteDone,
+	Ignore,
+}
+
+const VISIBILITIES: [(&str, &str); 3] = [
+	("everyone", "Everyone"),
+	("club_mates", "People in my clubs"),
+	("only_me", "Only me"),
+];
+
+fn none_if_blank(value: String) -> Option<String> {
+	if value.trim().is_empty() {
+		None
+	} else {
+		Some(value)
+	}
+}
+
+impl ProfileSettingsPage {
+	fn visibility(settings: &ProfileSettings, field: Field) -> &String {
+		match field {
+			Field::Email => &settings.email_visibility,
+			Field::DisplayName => &settings.display_name_visibility,
+			Field::Pronouns => &settings.pronouns_visibility,
+			Field::Bio => &settings.bio_visibility,
+			Field::Major => &settings.major_visibility,
+			Field::GraduationYear => &settings.graduation_year_visibility,
+		}
+	}
+
+	fn field_name(field: Field) -> &'static str {
+		match field {
+			Field::Email => "email",
+			Field::DisplayName => "display_name",
+			Field::Pronouns => "pronouns",
+			Field::Bio => "bio",
+			Field::Major => "major",
+			Field::GraduationYear => "graduation_year",
+		}
+	}
+
+	fn view_errors(&self, field: Field) -> Html {
+		let name = Self::field_name(field);
+
+		html! {
+			{
+				for self.errors.iter().flat_map(|errors| errors.fields.iter()).filter(|error| error.field == name).map(|error| html! {
+					<p class="form-error">{error.message.clone()}</p>
+				})
+			}
+		}
+	}
+
+	fn view_visibility(&self, settings: &ProfileSettings, field: Field) -> Html {
+		let current = Self::visibility(settings, field).clone();
+		let onchange = self.link.callback(move |data: ChangeData| match data {
+			ChangeData::Select(select) => Msg::SetVisibility(field, select.value()),
+			_ => Msg::Ignore,
+		});
+
+		html! {
+			<select class="visibility-select" onchange=onchange>
+				{
+					for VISIBILITIES.iter().map(|(value, label)| html! {
+						<option value={*value} selected={current == *value}>{*label}</option>
+					})
+				}
+			</select>
+		}
+	}
+
+	fn view_text_field(&self, settings: &ProfileSettings, field: Field, label: &str, value: String) -> Html {
+		let oninput = self.link.callback(move |data: InputData| Msg::Edit(field, data.value));
+
+		html! {
+			<div class="settings-field">
+				<label>{label}</label>
+				<input type="text" value=value oninput=oninput/>
+				{self.view_visibility(settings, field)}
+				{self.view_errors(field)}
+			</div>
+		}
+	}
+}
+
+impl Component for ProfileSettingsPage {
+	type Message = Msg;
+	type Properties = ();
+
+	fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
+		let get_task = Request::get("/api/users/me/profile")
+			.body(Nothing)
+			.ok()
+			.and_then(|req| {
+				let callback = link.callback(
+					|response: Response<Json<Result<ProfileSettings, anyhow::Error>>>| {
+						match response.into_body() {
+							Json(Ok(settings)) => Msg::GetSettingsDone(settings),
+							Json(Err(err)) => {
+								tell!("Failed to get profile settings: {:?}", err);
+								Msg::Ignore
+							}
+						}
+					},
+				);
+
+				FetchService::fetch(req, callback).ok()
+			});
+
+		Self {
+			link,
+			settings: None,
+			graduation_year: String::new(),
+			errors: None,
+			saved: false,
+			get_task,
+			save_task: None,
+			delete_task: None,
+			deleted: false,
+		}
+	}
+
+	fn update(&mut self, msg: Self::Message) -> ShouldRender {
+		match msg {
+			Msg::GetSettingsDone(settings) => {
+				self.get_task = None;
+				self.graduation_year = settings.graduation_year.map(|year| year.to_string()).unwrap_or_default();
+				self.settings = Some(settings);
+			}
+
+			Msg::Edit(field, value) => {
+				self.saved = false;
+				if let Some(settings) = self.settings.as_mut() {
+					match field {
+						Field::Email => (),
+						Field::DisplayName => settings.display_name = none_if_blank(value),
+						Field::Pronouns => settings.pronouns = none_if_blank(value),
+						Field::Bio => settings.bio = value,
+						Field::Major => settings.major = none_if_blank(value),
+						Field::GraduationYear => self.graduation_year = value,
+					}
+				}
+			}
+
+			Msg::SetVisibility(field, value) => {
+				self.saved = false;
+				if let Some(settings) = self.settings.as_mut() {
+					match field {
+						Field::Email => settings.email_visibility = value,
+						Field::DisplayName => settings.display_name_visibility = value,
+						Field::Pronouns => settings.pronouns_visibility = value,
+						Field::Bio => settings.bio_visibility = value,
+						Field::Major => settings.major_visibility = value,
+						Field::GraduationYear => settings.graduation_year_visibility = value,
+					}
+				}
+			}
+
+			Msg::Save => {
+				if let Some(settings) = self.settings.as_mut() {
+					settings.graduation_year = match self.graduation_year.trim() {
+						"" => None,
+						year => match year.parse::<i32>() {
+							Ok(year) => Some(year),
+							Err(_) => {
+								self.errors = Some(ApiErrorBody {
+									code: "validation_failed".to_owned(),
+									error: "Some fields are invalid.".to_owned(),
+									fields: vec![ApiFieldError {
+										field: "graduation_year".to_owned(),
+										code: "invalid".to_owned(),
+										message: "The graduation year has to be a year, like 2027.".to_owned(),
+									}],
+								});
+								return true;
+							}
+						},
+					};
+
+					let request = Request::put("/api/users/me/profile").body(Json(&*settings));
+
+					match request {
+						Ok(request) => {
+							let callback = self.link.callback(
+								|response: Response<Result<String, anyhow::Error>>| match response.status() {
+									StatusCode::OK => match response.body().as_ref().ok().and_then(|body| serde_json::from_str::<ProfileSettings>(body).ok()) {
+										Some(settings) => Msg::SaveDone(settings),
+										None => Msg::Ignore,
+									},
+									_ => Msg::SaveFailed(ApiErrorBody::from_response(&response)),
+								},
+							);
+
+							match FetchService::fetch(request, callback) {
+								Ok(task) => self.save_task = Some(task),
+								Err(err) => tell!("Failed to save profile: {:?}", err),
+							}
+						}
+
+						Err(err) => tell!("Failed to build request to save profile: {:?}", err),
+					}
+				}
+			}
+
+			Msg::SaveDone(settings) => {
+				self.save_task = None;
+				self.errors = None;
+				self.saved = true;
+				self.graduation_year = settings.graduation_year.map(|year| year.to_string()).unwrap_or_default();
+				self.settings = Some(settings);
+			}
+
+			Msg::SaveFailed(errors) => {
+				self.save_task = None;
+				self.delete_task = None;
+				self.errors = Some(errors);
+			}
+
+			Msg::DeleteAccount => {
+				if confirm("Delete your account? This can't be undone.") {
+					match Request::delete("/api/me").body(Nothing) {
+						Ok(request) => {
+							let callback = self.link.callback(
+								|response: Response<Result<String, anyhow::Error>>| match response.status() {
+									StatusCode::OK | StatusCode::ACCEPTED => Msg::DeleteDone,
+									// Heads get one error per club they still have to hand over
+									_ => Msg::SaveFailed(ApiErrorBody::from_response(&response)),
+								},
+							);
+
+							match FetchService::fetch(request, callback) {
+								Ok(task) => self.delete_task = Some(task),
+								Err(err) => tell!("Failed to delete account: {:?}", err),
+							}
+						}
+
+						Err(err) => tell!("Failed to build request to delete account: {:?}", err),
+					}
+				}
+			}
+
+			Msg::DeleteDone => {
+				self.delete_task = None;
+				self.deleted = true;
+			}
+
+			Msg::Ignore => (),
+		}
+
+		true
+	}
+
+	fn change(&mut self, _props: Self::Properties) -> ShouldRender {
+		false
+	}
+
+	fn view(&self) -> Html {
+		if self.deleted {
+			return html! {
+				<AppRedirect route=AppRoute::Login/>
+			};
+		}
+
+		let settings = match &self.settings {
+			Some(settings) => settings,
+			None => {
+				return html! {
+					<div class="details-page settings-page">
+						<h1>{"Profile"}</h1>
+					</div>
+				}
+			}
+		};
+
+		let bio_cb = self.link.callback(|data: InputData| Msg::Edit(Field::Bio, data.value));
+		let save_cb = self.link.callback(|_: MouseEvent| Msg::Save);
+		let delete_cb = self.link.callback(|_: MouseEvent| Msg::DeleteAccount);
+
+		html! {
+			<div class="details-page settings-page">
+				<h1>{"Profile"}</h1>
+				<p>{format!("Signed in as {} {}", settings.first_name, settings.last_name)}</p>
+
+				<div class="settings-field">
+					<label>{"Email"}</label>
+					<span>{settings.email.clone()}</span>
+					{self.view_visibility(settings, Field::Email)}
+				</div>
+
+				{self.view_text_field(settings, Field::DisplayName, "Display name", settings.display_name.clone().unwrap_or_default())}
+				{self.view_text_field(settings, Field::Pronouns, "Pronouns", settings.pronouns.clone().unwrap_or_default())}
+				{self.view_text_field(settings, Field::Major, "Major", settings.major.clone().unwrap_or_default())}
+				{self.view_text_field(settings, Field::GraduationYear, "Graduation year", self.graduation_year.clone())}
+
+				<div class="settings-field">
+					<label>{"Bio"}</label>
+					<textarea value=settings.bio.clone() oninput=bio_cb/>
+					{self.view_visibility(settings, Field::Bio)}
+					{self.view_errors(Field::Bio)}
+				</div>
+
+				{
+					match &self.errors {
+						Some(errors) if errors.fields.is_empty() => html! {
+							<p class="form-error">{errors.error.clone()}</p>
+						},
+						Some(errors) if errors.fields.iter().any(|error| error.code == "head_of_club") => html! {
+							<>
+								{
+									for errors.fields.iter().map(|error| html! {
+										<p class="form-error">{format!("{} Appoint a new head on the club's page.", error.message)}</p>
+									})
+								}
+							</>
+						},
+						_ => html! {
+							<>
+							</>
+						},
+					}
+				}
+
+				<button onclick=save_cb disabled=self.save_task.is_some()>{ if self.saved { "Saved" } else { "Save" } }</button>
+
+				<hr/>
+				<h2>{"Your data"}</h2>
+				<a href="/api/me/export" download="saturn-account.zip">{"Download everything Saturn stores about you"}</a>
+				<button class="danger" onclick=delete_cb disabled=self.delete_task.is_some()>{"Delete account"}</button>
+			</div>
+		}
+	}
+}
@@ -5,10 +5,59 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
 pub struct UserDetails {
-	pub email: String,
+	#[serde(default)]
+	pub id: i32,
+	// Left out for guests and whenever the user's settings hide it
+	#[serde(default)]
+	pub email: Option<String>,
 	pub picture: String,
 	pub first_name: String,
 	pub last_name: String,
+	#[serde(default)]
+	pub display_name: Option<String>,
+	#[serde(default)]
+	pub pronouns: Option<String>,
+}
+
+impl UserDetails {
+	// The display name when there is one, otherwise first and last name.
+	pub fn name(&self) -> String {
+		match &self.display_name {
+			Some(display_name) => display_name.clone(),
+			None => format!("{} {}", self.first_name, self.last_name),
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UserProfile {
+	#[serde(flatten)]
+	pub details: UserDetails,
+	#[serde(default)]
+	pub bio: Option<String>,
+	#[serde(default)]
+	pub major: Option<String>,
+	#[serde(default)]
+	pub graduation_year: Option<i32>,
+}
+
+// The signed in user's own profile. Each *_visibility is "everyone", "club_mates" or "only_me".
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ProfileSettings {
+	pub email: String,
+	pub first_name: String,
+	pub last_name: String,
+	pub display_name: Option<String>,
+	pub pronouns: Option<String>,
+	pub bio: String,
+	pub major: Option<String>,
+	pub graduation_year: Option<i32>,
+	pub email_visibility: String,
+	pub display_name_visibility: String,
+	pub pronouns_visibility: String,
+	pub bio_visibility: String,
+	pub major_visibility: String,
+	pub graduation_year_visibility: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -16,14 +65,147 @@ pub struct ClubDetails {
 	pub id: i32,
 	pub name: String,
 	pub body: String,
+	pub body_html: String,
 	pub member_count: i64,
 	pub publish_date: DateTime<Utc>,
 	pub expiry_date: DateTime<Utc>,
 	pub is_member: bool,
 	pub is_moderator: String,
+	pub is_public: bool,
+	pub category: Option<String>,
+	pub tags: Vec<String>,
+	pub archived_at: Option<DateTime<Utc>>,
+	#[serde(default)]
+	pub term_status: Option<String>,
+	#[serde(default)]
+	pub approval_status: String,
+	#[serde(default)]
+	pub review_note: Option<String>,
+	#[serde(default)]
+	pub is_verified: bool,
+	// Sent back as If-Match when editing the club
+	#[serde(default)]
+	pub version: i32,
+	// The club's generated avatar until a logo is uploaded
+	#[serde(default)]
+	pub logo: Option<ClubLogo>,
 	pub head_moderator: UserDetails,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ClubLogo {
+	pub url: String,
+	// Smallest first
+	pub thumbnails: Vec<LogoThumbnail>,
+	#[serde(default)]
+	pub is_generated: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LogoThumbnail {
+	pub size: u32,
+	pub url: String,
+}
+
+impl ClubDetails {
+	// The smallest version of the logo at least `size` pixels wide, the full logo if none is.
+	pub fn logo_src(&self, size: u32) -> String {
+		match &self.logo {
+			Some(logo) => logo
+				.thumbnails
+				.iter()
+				.find(|thumbnail| thumbnail.size >= size)
+				.map(|thumbnail| thumbnail.url.clone())
+				.unwrap_or_else(|| logo.url.clone()),
+			None => String::new(),
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ClubPage {
+	pub clubs: Vec<ClubDetails>,
+	pub next_cursor: Option<String>,
+	pub facets: Option<ClubFacets>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ClubFacets {
+	pub categories: Vec<FacetCount>,
+	pub tags: Vec<FacetCount>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FacetCount {
+	pub id: Option<i32>,
+	pub name: String,
+	pub count: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ClubRevisionSummary {
+	pub id: i32,
+	pub name: String,
+	// None once the author's account is gone
+	pub author: Option<UserDetails>,
+	pub reverted_from: Option<i32>,
+	pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DiffLine {
+	// One of "equal", "insert" or "delete"
+	pub op: String,
+	pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ClubRevisionDiff {
+	pub from: i32,
+	pub to: i32,
+	pub name: Vec<DiffLine>,
+	pub body: Vec<DiffLine>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MarkdownPreview {
+	pub html: String,
+}
+
+// The body of every error the API sends back.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ApiErrorBody {
+	// Stable, e.g. "conflict" or "validation_failed"
+	pub code: String,
+	// Meant for people
+	pub error: String,
+	#[serde(default)]
+	pub fields: Vec<ApiFieldError>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ApiFieldError {
+	pub field: String,
+	pub code: String,
+	pub message: String,
+}
+
+impl ApiErrorBody {
+	// Reads the error out of a failed response, making one up when the body isn't one.
+	pub fn from_response(response: &yew::services::fetch::Response<Result<String, anyhow::Error>>) -> Self {
+		response
+			.body()
+			.as_ref()
+			.ok()
+			.and_then(|body| serde_json::from_str::<ApiErrorBody>(body).ok())
+			.unwrap_or_else(|| ApiErrorBody {
+				code: "unknown".to_owned(),
+				error: format!("Unexpected status {}", response.status()),
+				fields: Vec::new(),
+			})
+	}
+}
+
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct AuthDetails {
 	pub auth_level: AuthLevel,
@@ -0,0 +1,33 @@
+use crate::prelude::*;
+use crate::services::accounts;
+use std::collections::HashMap;
+
+#[derive(Deserialize, Default)]
+pub struct DeleteAccountDTO {
+    //Club id to the member who takes over as head, for every club the caller heads.
+    #[serde(default)]
+    pub successors: HashMap<i32, i32>,
+}
+
+/*
+Deletes the caller's account and signs them out. Heads of clubs have
+to name a successor for each club, otherwise nothing is deleted and the
+clubs come back as field errors on successors.<club id>. Google sign ins
+issued before the deletion are refused from then on, signing in with
+Google again creates a new, empty account.
+*/
+#[delete("/me", data = "<request>")]
+pub async fn delete(user: User, db: Db, storage: &State<Storage>, cookies: &CookieJar<'_>, request: Option<Json<DeleteAccountDTO>>) -> Result<status::Accepted<()>> {
+    let request = request.map(|request| request.into_inner()).unwrap_or_default();
+
+    let unused_avatar = db.run(move |conn| accounts::delete(conn, &user, &request.successors)).await?;
+
+    if let Some(key) = unused_avatar {
+        if let Err(e) = storage.delete(&key).await {
+            eprintln!("Failed to delete stored object {}, {:?}", key, e);
+        }
+    }
+
+    cookies.remove_private(Cookie::named("user_jwt"));
+    Ok(status::Accepted(None))
+}
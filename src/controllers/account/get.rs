@@ -0,0 +1,48 @@
+use crate::prelude::*;
+use crate::services::accounts;
+use rocket::http::Header;
+
+#[derive(Responder)]
+pub struct AccountArchive {
+    inner: (ContentType, Vec<u8>),
+    content_disposition: Header<'static>,
+    cache_control: Header<'static>,
+}
+
+/*
+Everything we store about the caller as a zip download, see
+services::accounts for what is in it.
+*/
+#[get("/me/export")]
+pub async fn export(user: User, db: Db, storage: &State<Storage>) -> Result<AccountArchive> {
+    let user_id = user.id;
+    let collected = db.run(move |conn| accounts::collect(conn, &user)).await?;
+
+    let mut files = Vec::new();
+    for (path, key) in collected.files {
+        match storage.get(&key).await {
+            Ok(Some(bytes)) => files.push((path, bytes)),
+            //Listed in account.json either way, a missing file shouldn't cost the user the rest.
+            Ok(None) => eprintln!("Stored object {} is missing from the export of user {}", key, user_id),
+            Err(e) => {
+                eprintln!("Failed to fetch {} for the export of user {}, {:?}", key, user_id, e);
+                return Err(ApiError::Internal)
+            }
+        }
+    }
+
+    let export = collected.export;
+    let bytes = rocket::tokio::task::spawn_blocking(move || accounts::archive(&export, files))
+        .await
+        .map_err(|_| ApiError::Internal)?
+        .map_err(|e| {
+            eprintln!("Failed to build the export of user {}, {:?}", user_id, e);
+            ApiError::Internal
+        })?;
+
+    Ok(AccountArchive {
+        inner: (ContentType::ZIP, bytes),
+        content_disposition: Header::new("Content-Disposition", "attachment; filename=\"saturn-account.zip\""),
+        cache_control: Header::new("Cache-Control", "no-store"),
+    })
+}
@@ -0,0 +1,91 @@
+use crate::prelude::*;
+use crate::services::{attachments, avatars};
+use crate::services::storage::is_valid_key;
+use rocket::http::Header;
+use std::path::PathBuf;
+
+#[derive(Responder)]
+pub struct StoredAsset {
+    inner: (ContentType, Vec<u8>),
+    cache_control: Header<'static>,
+}
+
+#[derive(Responder)]
+pub enum AssetResponse {
+    Redirect(Redirect),
+    Stored(StoredAsset),
+}
+
+fn immutable() -> Header<'static> {
+    Header::new("Cache-Control", "public, max-age=31536000, immutable")
+}
+
+/*
+Serves uploaded assets out of storage, or redirects to the storage
+backend when it can hand out URLs itself. Keys are content addressed so
+whatever is served here never changes. Attachments are left out, they
+go through the attachment routes. Generated club avatars are
+addressed the same way but made on request, and so are the avatars of
+users whose Google picture hasn't been copied into storage.
+*/
+#[get("/assets/<key..>")]
+pub async fn get(key: PathBuf, db: Db, storage: &State<Storage>) -> Result<AssetResponse> {
+    let key = match key.to_str() {
+        Some(key) if is_valid_key(key) && !attachments::is_attachment_key(key) => key.to_owned(),
+        _ => return Err(ApiError::NotFound("There is no such asset.".to_owned())),
+    };
+
+    if let Some(club_id) = avatars::club_id_from_key(&key) {
+        let club = Club::get_by_id_async(&db, &club_id).await
+            .ok_or(ApiError::NotFound("There is no such asset.".to_owned()))?;
+        let avatar = avatars::club_avatar(club.id, &club.name);
+
+        //The club was renamed since the URL was handed out.
+        if avatar.key != key {
+            return Ok(AssetResponse::Redirect(Redirect::temporary(format!("/assets/{}", avatar.key))))
+        }
+
+        return Ok(AssetResponse::Stored(StoredAsset {
+            inner: (ContentType::SVG, avatar.svg.into_bytes()),
+            cache_control: immutable(),
+        }))
+    }
+
+    if let Some(user_id) = avatars::user_id_from_key(&key) {
+        let user = User::get_by_id_async(&db, &user_id).await
+            .ok_or(ApiError::NotFound("There is no such asset.".to_owned()))?;
+        let avatar = avatars::user_avatar(user.id, &user.first_name, &user.last_name);
+
+        if avatar.key != key {
+            return Ok(AssetResponse::Redirect(Redirect::temporary(format!("/assets/{}", avatar.key))))
+        }
+
+        return Ok(AssetResponse::Stored(StoredAsset {
+            inner: (ContentType::SVG, avatar.svg.into_bytes()),
+            cache_control: immutable(),
+        }))
+    }
+
+    let failed = |e: std::io::Error| {
+        eprintln!("Failed to fetch asset {}, {:?}", key, e);
+        ApiError::Internal
+    };
+
+    if let Some(url) = storage.presigned_url(&key).await.map_err(failed)? {
+        return Ok(AssetResponse::Redirect(Redirect::temporary(url)))
+    }
+
+    match storage.get(&key).await.map_err(failed)? {
+        Some(bytes) => {
+            let content_type = key.rsplit('.').next()
+                .and_then(ContentType::from_extension)
+                .unwrap_or(ContentType::Binary);
+
+            Ok(AssetResponse::Stored(StoredAsset {
+                inner: (content_type, bytes),
+                cache_control: immutable(),
+            }))
+        },
+        None => Err(ApiError::NotFound("There is no such asset.".to_owned())),
+    }
+}
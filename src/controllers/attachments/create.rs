@@ -0,0 +1,120 @@
+use crate::prelude::*;
+use crate::models::club_attachments_md::{VISIBILITY_MEMBERS, VISIBILITY_PUBLIC};
+use crate::services::attachments;
+
+#[derive(FromForm)]
+pub struct AttachmentUploadForm<'r> {
+    //photo or document
+    pub kind: String,
+    pub caption: Option<String>,
+    //public (the default) or members
+    pub visibility: Option<String>,
+    pub file: TempFile<'r>,
+}
+
+/*
+Adds a photo or document to the club, sent as multipart form data. Who
+may upload each kind is up to the club's head, see update::settings.
+Uploads that would take the club over its storage quota are refused
+with 413, checked again when the upload is saved since others may have
+finished in the meantime.
+*/
+#[post("/clubs/<id>/attachments", data = "<upload>")]
+pub async fn create(user: User, db: Db, storage: &State<Storage>, id: i32, mut upload: Form<AttachmentUploadForm<'_>>) -> Result<Json<AttachmentDetails>> {
+    let user_id = user.id.clone();
+    let status = user.get_membership_status_async(&db, &id).await?;
+
+    let kind = upload.kind.clone();
+    let caption = upload.caption.clone().unwrap_or_default().trim().to_owned();
+    let visibility = upload.visibility.clone().unwrap_or(VISIBILITY_PUBLIC.to_owned());
+    if caption.chars().count() > 500 {
+        return Err(ApiError::Validation(vec![FieldError::new("caption", "length", "Captions can be at most 500 characters long.")]))
+    }
+    if visibility != VISIBILITY_PUBLIC && visibility != VISIBILITY_MEMBERS {
+        return Err(ApiError::Validation(vec![FieldError::new("visibility", "invalid", "Attachments are either public or for members only.")]))
+    }
+
+    let declared_size = upload.file.len() as i64;
+    let (allowed, remaining) = db.run(move |conn| {
+        let club = Club::get_active(conn, &id)
+            .ok_or(ApiError::NotFound("The club you are trying to access does not exist.".to_owned()))?;
+        let remaining = club.storage_quota() - ClubAttachment::bytes_used(conn, &id)?;
+        Ok::<_, ApiError>((club.may_upload(&kind, &status), remaining))
+    }).await?;
+
+    if !allowed {
+        return Err(ApiError::Forbidden("You are not allowed to upload that here.".to_owned()))
+    }
+    if declared_size > remaining {
+        return Err(ApiError::TooLarge("The club has run out of storage space.".to_owned()))
+    }
+
+    let bytes = attachments::read_temp_file(&mut upload.file).await.map_err(|e| {
+        eprintln!("Failed to read an attachment upload, {:?}", e);
+        ApiError::BadRequest("The upload could not be read.".to_owned())
+    })?;
+    let declared = upload.file.content_type().cloned();
+    let original_name = upload.file.name().unwrap_or(upload.kind.as_str()).to_owned();
+
+    let kind = upload.kind.clone();
+    let processed = rocket::tokio::task::spawn_blocking(move || {
+        attachments::process(&kind, bytes, declared.as_ref())
+    }).await.map_err(|_| ApiError::Internal)??;
+
+    let size = processed.file.bytes.len() as i64;
+    if size > remaining {
+        return Err(ApiError::TooLarge("The club has run out of storage space.".to_owned()))
+    }
+
+    let file_name = format!("{}.{}", original_name, processed.extension);
+    let file_key = processed.file.key.clone();
+    let content_type = processed.file.content_type.clone();
+    let thumbnail_key = processed.thumbnail.as_ref().map(|thumbnail| thumbnail.key.clone());
+    let persist_failed = |e: std::io::Error| {
+        eprintln!("Error encountered while trying to persist an attachment, {:?}", e);
+        ApiError::Internal
+    };
+
+    storage.put(&processed.file.key, processed.file.bytes, &processed.file.content_type).await.map_err(persist_failed)?;
+    if let Some(thumbnail) = processed.thumbnail {
+        storage.put(&thumbnail.key, thumbnail.bytes, &thumbnail.content_type).await.map_err(persist_failed)?;
+    }
+
+    let kind = upload.kind.clone();
+    db.run(move |conn| {
+        use crate::schema::club_attachments::dsl::club_attachments;
+        use crate::schema::clubs::dsl::{clubs, deleted_at};
+
+        conn.transaction::<_, ApiError, _>(|| {
+            //Locking the club makes concurrent uploads wait here, so each sees what the others used.
+            let club = clubs.find(id)
+                .filter(deleted_at.is_null())
+                .for_update()
+                .get_result::<Club>(conn)
+                .optional()?
+                .ok_or(ApiError::NotFound("The club you are trying to access does not exist.".to_owned()))?;
+            if size > club.storage_quota() - ClubAttachment::bytes_used(conn, &id)? {
+                return Err(ApiError::TooLarge("The club has run out of storage space.".to_owned()))
+            }
+
+            let position = ClubAttachment::next_position(conn, &id, &kind)?;
+
+            let attachment = insert_into(club_attachments)
+                .values(&NewClubAttachment {
+                    club_id: &id,
+                    kind: &kind,
+                    storage_key: &file_key,
+                    thumbnail_key: thumbnail_key.as_deref(),
+                    file_name: &file_name,
+                    content_type: &content_type,
+                    size_bytes: &size,
+                    caption: &caption,
+                    position: &position,
+                    visibility: &visibility,
+                    uploaded_by: Some(&user_id),
+                })
+                .get_result::<ClubAttachment>(conn)?;
+            Ok(Json(attachment.to_details()))
+        })
+    }).await
+}
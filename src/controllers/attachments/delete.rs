@@ -0,0 +1,39 @@
+use crate::prelude::*;
+
+/*
+Removes an attachment, for whoever uploaded it, the club's head and
+admins. The stored file goes too unless another attachment has the
+same content.
+*/
+#[delete("/clubs/<id>/attachments/<attachment_id>")]
+pub async fn delete(user: User, db: Db, storage: &State<Storage>, id: i32, attachment_id: i32) -> Result<status::Accepted<()>> {
+    use crate::schema::club_attachments::dsl::club_attachments;
+
+    let user_id = user.id.clone();
+    let is_admin = user.is_admin;
+    let is_head = user.get_membership_status_async(&db, &id).await? == MembershipStatus::Moderator(true);
+
+    let (attachment, shared) = db.run(move |conn| {
+        let attachment = ClubAttachment::get_for_club(conn, &id, &attachment_id)
+            .ok_or(ApiError::NotFound("That attachment does not exist.".to_owned()))?;
+        if !is_admin && !is_head && attachment.uploaded_by != Some(user_id) {
+            return Err(ApiError::Forbidden("You are not allowed to do that.".to_owned()))
+        }
+
+        diesel::delete(club_attachments.find(attachment.id)).execute(conn)?;
+        let shared = attachment.is_key_shared(conn)?;
+        Ok((attachment, shared))
+    }).await?;
+
+    if !shared {
+        //The row is gone either way, a leftover object only costs space.
+        let keys = std::iter::once(&attachment.storage_key).chain(attachment.thumbnail_key.iter());
+        for key in keys {
+            if let Err(e) = storage.delete(key).await {
+                eprintln!("Failed to delete stored object {}, {:?}", key, e);
+            }
+        }
+    }
+
+    Ok(status::Accepted(None))
+}
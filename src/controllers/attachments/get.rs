@@ -0,0 +1,112 @@
+use crate::prelude::*;
+use crate::models::club_attachments_md::{KIND_DOCUMENT, KIND_PHOTO, VISIBILITY_PUBLIC};
+use rocket::http::Header;
+
+#[derive(Responder)]
+pub struct AttachmentFile {
+    inner: (ContentType, Vec<u8>),
+    cache_control: Header<'static>,
+    content_disposition: Header<'static>,
+}
+
+#[derive(Responder)]
+pub enum AttachmentContent {
+    Redirect(Redirect),
+    File(AttachmentFile),
+}
+
+/*
+A club's photos (kind=photo, the default) or documents (kind=document)
+in order. Members see everything, everyone else only what is public.
+*/
+#[get("/clubs/<id>/attachments?<kind>")]
+pub async fn get_all(user: Option<User>, db: Db, id: i32, kind: Option<String>) -> Result<Json<Vec<AttachmentDetails>>> {
+    let user_id = user.map(|user| user.id);
+    let kind = kind.unwrap_or(KIND_PHOTO.to_owned());
+    if kind != KIND_PHOTO && kind != KIND_DOCUMENT {
+        return Err(ApiError::BadRequest("Attachments are either photos or documents.".to_owned()))
+    }
+
+    let attachments = db.run(move |conn| {
+        let club = ClubDetails::get_for_user(conn, &id, user_id)?
+            .ok_or(ApiError::NotFound("The club you are trying to access does not exist.".to_owned()))?;
+
+        let attachments = ClubAttachment::list(conn, &id, &kind, club.is_member)?;
+        Ok::<_, ApiError>(attachments.into_iter().map(ClubAttachment::to_details).collect::<Vec<_>>())
+    }).await?;
+
+    Ok(Json(attachments))
+}
+
+//How much of its storage quota the club has used, for its moderators.
+#[get("/clubs/<id>/attachments/usage")]
+pub async fn get_usage(user: User, db: Db, id: i32) -> Result<Json<AttachmentUsage>> {
+    match user.get_membership_status_async(&db, &id).await? {
+        MembershipStatus::Moderator(_) => (),
+        _ => return Err(ApiError::Forbidden("Only moderators can see the club's storage use.".to_owned()))
+    }
+
+    db.run(move |conn| {
+        let club = Club::get_by_id(conn, &id)
+            .ok_or(ApiError::NotFound("The club you are trying to access does not exist.".to_owned()))?;
+
+        Ok(Json(AttachmentUsage {
+            used_bytes: ClubAttachment::bytes_used(conn, &id)?,
+            quota_bytes: club.storage_quota(),
+        }))
+    }).await
+}
+
+/*
+The file behind an attachment, or its thumbnail. Goes through here
+rather than /assets so members only files stay with members. Public
+files are cached like other assets, documents are always downloaded
+rather than opened in the page.
+*/
+#[get("/clubs/<id>/attachments/<attachment_id>/content?<thumbnail>")]
+pub async fn get_content(user: Option<User>, db: Db, storage: &State<Storage>, id: i32, attachment_id: i32, thumbnail: Option<bool>) -> Result<AttachmentContent> {
+    let user_id = user.map(|user| user.id);
+    let attachment = db.run(move |conn| {
+        let club = ClubDetails::get_for_user(conn, &id, user_id)?;
+        let attachment = ClubAttachment::get_for_club(conn, &id, &attachment_id);
+
+        match (club, attachment) {
+            (Some(club), Some(attachment)) if club.is_member || attachment.visibility == VISIBILITY_PUBLIC => Ok(attachment),
+            _ => Err(ApiError::NotFound("That attachment does not exist.".to_owned())),
+        }
+    }).await?;
+
+    let key = match (thumbnail.unwrap_or(false), &attachment.thumbnail_key) {
+        (true, Some(thumbnail_key)) => thumbnail_key.clone(),
+        _ => attachment.storage_key.clone(),
+    };
+
+    let failed = |e: std::io::Error| {
+        eprintln!("Failed to fetch attachment {}, {:?}", attachment.id, e);
+        ApiError::Internal
+    };
+
+    if let Some(url) = storage.presigned_url(&key).await.map_err(failed)? {
+        return Ok(AttachmentContent::Redirect(Redirect::temporary(url)))
+    }
+
+    let bytes = storage.get(&key).await.map_err(failed)?
+        .ok_or(ApiError::NotFound("That attachment does not exist.".to_owned()))?;
+    let content_type = ContentType::parse_flexible(&attachment.content_type).unwrap_or(ContentType::Binary);
+    let cache_control = if attachment.visibility == VISIBILITY_PUBLIC {
+        "public, max-age=31536000, immutable"
+    } else {
+        "private, max-age=3600"
+    };
+    let content_disposition = if attachment.kind == KIND_DOCUMENT {
+        format!("attachment; filename=\"{}\"", attachment.file_name.replace('"', ""))
+    } else {
+        "inline".to_owned()
+    };
+
+    Ok(AttachmentContent::File(AttachmentFile {
+        inner: (content_type, bytes),
+        cache_control: Header::new("Cache-Control", cache_control),
+        content_disposition: Header::new("Content-Disposition", content_disposition),
+    }))
+}
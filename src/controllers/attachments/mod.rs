@@ -0,0 +1,4 @@
+pub mod get;
+pub mod create;
+pub mod update;
+pub mod delete;
\ No newline at end of file
@@ -0,0 +1,165 @@
+use crate::prelude::*;
+use crate::models::club_attachments_md::{KIND_DOCUMENT, KIND_PHOTO, VISIBILITY_MEMBERS, VISIBILITY_PUBLIC};
+use crate::models::audit_log_md::ACTION_CLUB_ATTACHMENT_QUOTA;
+
+#[derive(Deserialize)]
+pub struct AttachmentUpdateDTO {
+    //Both left alone when missing.
+    pub caption: Option<String>,
+    pub visibility: Option<String>,
+}
+
+//Changes an attachment's caption or visibility, for whoever uploaded it and the club's moderators.
+#[put("/clubs/<id>/attachments/<attachment_id>", data = "<request>", rank = 2)]
+pub async fn update(user: User, db: Db, id: i32, attachment_id: i32, request: Json<AttachmentUpdateDTO>) -> Result<Json<AttachmentDetails>> {
+    use crate::schema::club_attachments::dsl::{club_attachments, caption, visibility};
+
+    let user_id = user.id.clone();
+    let is_moderator = match user.get_membership_status_async(&db, &id).await? {
+        MembershipStatus::Moderator(_) => true,
+        _ => false,
+    };
+
+    let request = request.into_inner();
+    let new_caption = request.caption.as_ref().map(|new_caption| new_caption.trim().to_owned());
+    if new_caption.as_ref().map(|new_caption| new_caption.chars().count() > 500).unwrap_or(false) {
+        return Err(ApiError::Validation(vec![FieldError::new("caption", "length", "Captions can be at most 500 characters long.")]))
+    }
+    match request.visibility.as_deref() {
+        None | Some(VISIBILITY_PUBLIC) | Some(VISIBILITY_MEMBERS) => (),
+        Some(_) => return Err(ApiError::Validation(vec![FieldError::new("visibility", "invalid", "Attachments are either public or for members only.")]))
+    }
+
+    db.run(move |conn| {
+        if Club::get_active(conn, &id).is_none() {
+            return Err(ApiError::NotFound("The club you are trying to access does not exist.".to_owned()))
+        }
+        let attachment = ClubAttachment::get_for_club(conn, &id, &attachment_id)
+            .ok_or(ApiError::NotFound("That attachment does not exist.".to_owned()))?;
+        if !is_moderator && attachment.uploaded_by != Some(user_id) {
+            return Err(ApiError::Forbidden("You are not allowed to do that.".to_owned()))
+        }
+
+        let updated = diesel::update(club_attachments.find(attachment.id))
+            .set((
+                caption.eq(new_caption.unwrap_or(attachment.caption)),
+                visibility.eq(request.visibility.unwrap_or(attachment.visibility)),
+            ))
+            .get_result::<ClubAttachment>(conn)?;
+
+        Ok(Json(updated.to_details()))
+    }).await
+}
+
+#[derive(Deserialize)]
+pub struct AttachmentOrderDTO {
+    pub kind: String,
+    //Every photo or document of the club, first one first.
+    pub ids: Vec<i32>,
+}
+
+//Rearranges the club's photos or documents, moderators only.
+#[put("/clubs/<id>/attachments/order", data = "<request>")]
+pub async fn reorder(user: User, db: Db, id: i32, request: Json<AttachmentOrderDTO>) -> Result<Json<Vec<AttachmentDetails>>> {
+    match user.get_membership_status_async(&db, &id).await? {
+        MembershipStatus::Moderator(_) => (),
+        _ => return Err(ApiError::Forbidden("You are not allowed to do that.".to_owned()))
+    }
+
+    let request = request.into_inner();
+    if request.kind != KIND_PHOTO && request.kind != KIND_DOCUMENT {
+        return Err(ApiError::BadRequest("Attachments are either photos or documents.".to_owned()))
+    }
+
+    db.run(move |conn| {
+        if Club::get_active(conn, &id).is_none() {
+            return Err(ApiError::NotFound("The club you are trying to access does not exist.".to_owned()))
+        }
+
+        if !ClubAttachment::reorder(conn, &id, &request.kind, &request.ids)? {
+            return Err(ApiError::Conflict("The order has to list every attachment of that kind exactly once.".to_owned()))
+        }
+
+        let attachments = ClubAttachment::list(conn, &id, &request.kind, true)?;
+        Ok(Json(attachments.into_iter().map(ClubAttachment::to_details).collect()))
+    }).await
+}
+
+#[derive(Deserialize)]
+pub struct AttachmentSettingsDTO {
+    //member, moderator or head. Left alone when missing.
+    pub photo_upload_role: Option<String>,
+    pub document_upload_role: Option<String>,
+}
+
+//Who may upload photos and documents to the club, up to its head.
+#[put("/clubs/<id>/attachments/settings", data = "<request>")]
+pub async fn settings(user: User, db: Db, id: i32, request: Json<AttachmentSettingsDTO>) -> Result<Json<ClubDetails>> {
+    use crate::schema::clubs::dsl::{clubs, photo_upload_role, document_upload_role};
+
+    let user_id = user.id.clone();
+    match user.get_membership_status_async(&db, &id).await? {
+        MembershipStatus::Moderator(true) => (),
+        _ => return Err(ApiError::Forbidden("Only the club's head can change who may upload.".to_owned()))
+    }
+
+    let request = request.into_inner();
+    let mut errors = Vec::new();
+    for (field, role) in [("photo_upload_role", &request.photo_upload_role), ("document_upload_role", &request.document_upload_role)] {
+        match role.as_deref() {
+            None | Some("member") | Some("moderator") | Some("head") => (),
+            Some(_) => errors.push(FieldError::new(field, "invalid", "Uploads are open to members, moderators or only the head.")),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(ApiError::Validation(errors))
+    }
+
+    db.run(move |conn| {
+        let club = Club::get_active(conn, &id)
+            .ok_or(ApiError::NotFound("The club you are trying to access does not exist.".to_owned()))?;
+
+        let club = diesel::update(clubs.find(id))
+            .set((
+                photo_upload_role.eq(request.photo_upload_role.unwrap_or(club.photo_upload_role)),
+                document_upload_role.eq(request.document_upload_role.unwrap_or(club.document_upload_role)),
+            ))
+            .get_result::<Club>(conn)?;
+
+        Ok(Json(club.to_club_details(&conn, &user_id)?))
+    }).await
+}
+
+#[derive(Deserialize)]
+pub struct AttachmentQuotaDTO {
+    //None goes back to the default, CLUB_STORAGE_QUOTA_MB.
+    pub quota_mb: Option<i64>,
+}
+
+//Admin tool, gives a club more or less storage than the default.
+#[put("/clubs/<id>/attachments/quota", data = "<request>")]
+pub async fn set_quota(admin: Admin, db: Db, id: i32, ip: ClientIp, request: Json<AttachmentQuotaDTO>) -> Result<Json<AttachmentUsage>> {
+    use crate::schema::clubs::dsl::{clubs, storage_quota_bytes, deleted_at};
+
+    let quota_bytes = match request.quota_mb {
+        Some(quota_mb) if quota_mb < 0 => return Err(ApiError::Validation(vec![FieldError::new("quota_mb", "invalid", "Quotas can't be negative.")])),
+        Some(quota_mb) => Some(quota_mb * 1024 * 1024),
+        None => None,
+    };
+
+    let user_id = admin.0.id.clone();
+    db.run(move |conn| conn.transaction(|| {
+        let before = Club::get_active(conn, &id)
+            .ok_or(ApiError::NotFound("The club you are trying to access does not exist.".to_owned()))?;
+        let club = diesel::update(clubs.find(id))
+            .filter(deleted_at.is_null())
+            .set(storage_quota_bytes.eq(quota_bytes))
+            .get_result::<Club>(conn)?;
+        AuditEntry::record_club(conn, user_id, &ip, ACTION_CLUB_ATTACHMENT_QUOTA, id, Some(&before), Some(&club))?;
+
+        Ok(Json(AttachmentUsage {
+            used_bytes: ClubAttachment::bytes_used(conn, &id)?,
+            quota_bytes: club.storage_quota(),
+        }))
+    })).await
+}
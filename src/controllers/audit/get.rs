@@ -0,0 +1,26 @@
+use crate::prelude::*;
+
+/*
+Searches the whole audit log, newest first. Filters combine, e.g.
+/audit?actor=4&action=club.update&since=2026-10-01T00:00:00Z, and q
+matches anywhere in the action or the snapshots.
+*/
+#[get("/audit?<query..>")]
+pub async fn get_all(_admin: Admin, db: Db, query: AuditQuery) -> Result<Json<AuditPage>> {
+    db.run(move |conn| {
+        AuditEntry::search(conn, &query, None).map(Json)
+    }).await
+}
+
+//The same search for a head moderator, limited to their club and without addresses.
+#[get("/clubs/<id>/audit?<query..>")]
+pub async fn get_for_club(user: User, db: Db, id: i32, query: AuditQuery) -> Result<Json<AuditPage>> {
+    match user.get_membership_status_async(&db, &id).await? {
+        MembershipStatus::Moderator(true) => {
+            db.run(move |conn| {
+                AuditEntry::search(conn, &query, Some(id)).map(|page| Json(page.without_ips()))
+            }).await
+        },
+        _ => Err(ApiError::Forbidden("Only the head moderator can see the club's audit log.".to_owned()))
+    }
+}
@@ -45,9 +45,9 @@ pub async fn details_admin(admin: Admin, key_state: &State<GoogleKeysState>, coo
     Ok(Json(AuthDetails{
         auth_level: AuthLevel::User,
         exp: exp,
+        picture: Some(admin.0.avatar_url()),
         id: Some(admin.0.id),
         email: Some(admin.0.email),
-        picture: Some(admin.0.picture),
         first_name: Some(admin.0.first_name),
         last_name: Some(admin.0.last_name)
     }))
@@ -76,9 +76,9 @@ pub async fn details_user(user: User, key_state: &State<GoogleKeysState>, cookie
     Ok(Json(AuthDetails{
         auth_level: AuthLevel::User,
         exp: exp,
+        picture: Some(user.avatar_url()),
         id: Some(user.id),
         email: Some(user.email),
-        picture: Some(user.picture),
         first_name: Some(user.first_name),
         last_name: Some(user.last_name)
     }))
@@ -93,7 +93,7 @@ not authorized.
 */
 
 #[catch(403)]
-pub async fn forbidden_or_details_guest(req: &Request<'_>) -> std::result::Result<status::Custom<Json<AuthDetails>>, status::Forbidden<Json<JsonError>>> {
+pub async fn forbidden_or_details_guest(req: &Request<'_>) -> Result<status::Custom<Json<AuthDetails>>> {
     if req.uri().path() == "/api/auth/details" {
         //details_guest
         Ok(status::Custom(Status::Ok, Json(AuthDetails{
@@ -101,6 +101,6 @@ pub async fn forbidden_or_details_guest(req: &Request<'_>) -> std::result::Resul
             ..Default::default()
         })))
     } else{
-        Err(status::Forbidden(Some(Json(JsonError{error: "Client not authorized to perform that action.".to_owned()}))))
+        Err(ApiError::from_status(Status::Forbidden))
     }
 }
\ No newline at end of file
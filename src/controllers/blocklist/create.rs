@@ -0,0 +1,63 @@
+use crate::prelude::*;
+use crate::models::audit_log_md::{ACTION_BLOCKLIST_CREATE, TARGET_BLOCKLIST_RULE};
+use crate::models::blocklist_md::APPLIES_TO_BOTH;
+use crate::services::blocklist;
+
+#[derive(Deserialize)]
+pub struct BlocklistRuleDTO {
+    pub pattern: String,
+    //exact, word or regex
+    pub match_type: String,
+    //reject or review
+    pub severity: String,
+    //name, body or both, the default
+    pub applies_to: Option<String>,
+    //Rules are created disabled unless asked otherwise.
+    pub enabled: Option<bool>,
+    pub note: Option<String>,
+}
+
+impl BlocklistRuleDTO {
+    pub fn applies_to(&self) -> &str {
+        self.applies_to.as_deref().unwrap_or(APPLIES_TO_BOTH)
+    }
+
+    pub fn validate_rule(&self) -> Result<blocklist::CompiledRule> {
+        blocklist::validate_rule(&self.pattern, &self.match_type, &self.severity, self.applies_to())
+    }
+}
+
+#[post("/blocklist", data = "<request>")]
+pub async fn create(admin: Admin, db: Db, ip: ClientIp, request: Json<BlocklistRuleDTO>) -> Result<Json<BlocklistRule>> {
+    use crate::schema::blocklist_rules::dsl::{blocklist_rules};
+
+    request.validate_rule()?;
+
+    let admin_id = admin.0.id.clone();
+    db.run(move |conn| conn.transaction(|| {
+        let created = insert_into(blocklist_rules)
+            .values(&NewBlocklistRule {
+                pattern: &request.pattern,
+                match_type: &request.match_type,
+                severity: &request.severity,
+                applies_to: request.applies_to(),
+                enabled: &request.enabled.unwrap_or(false),
+                note: request.note.as_deref().unwrap_or(""),
+                created_by: Some(&admin_id),
+            })
+            .get_result::<BlocklistRule>(conn)?;
+
+        AuditEntry::record(conn, &NewAuditEntry {
+            actor_id: Some(admin_id),
+            action: ACTION_BLOCKLIST_CREATE,
+            target_type: TARGET_BLOCKLIST_RULE,
+            target_id: Some(created.id),
+            club_id: None,
+            before_snapshot: None,
+            after_snapshot: AuditEntry::snapshot(&created),
+            ip: ip.0.clone(),
+        })?;
+
+        Ok(Json(created))
+    })).await
+}
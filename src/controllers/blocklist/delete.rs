@@ -0,0 +1,30 @@
+use crate::prelude::*;
+use crate::models::audit_log_md::{ACTION_BLOCKLIST_DELETE, TARGET_BLOCKLIST_RULE};
+
+//Clubs the rule sent for review stay in the queue.
+#[delete("/blocklist/<id>")]
+pub async fn delete(admin: Admin, db: Db, id: i32, ip: ClientIp) -> Result<status::Accepted<()>> {
+    use crate::schema::blocklist_rules::dsl::{blocklist_rules};
+
+    let admin_id = admin.0.id.clone();
+    db.run(move |conn| conn.transaction(|| {
+        let current = BlocklistRule::get_by_id(conn, &id)
+            .ok_or(ApiError::NotFound("The blocklist rule you are trying to delete does not exist.".to_owned()))?;
+
+        diesel::delete(blocklist_rules.find(id))
+            .execute(conn)?;
+
+        AuditEntry::record(conn, &NewAuditEntry {
+            actor_id: Some(admin_id),
+            action: ACTION_BLOCKLIST_DELETE,
+            target_type: TARGET_BLOCKLIST_RULE,
+            target_id: Some(id),
+            club_id: None,
+            before_snapshot: AuditEntry::snapshot(&current),
+            after_snapshot: None,
+            ip: ip.0.clone(),
+        })?;
+
+        Ok(status::Accepted(None))
+    })).await
+}
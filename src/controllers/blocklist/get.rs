@@ -0,0 +1,36 @@
+use crate::prelude::*;
+use crate::services::blocklist::{ClubMatch, CompiledRule};
+use super::create::BlocklistRuleDTO;
+
+#[get("/blocklist")]
+pub async fn get_all(_admin: Admin, db: Db) -> Result<Json<Vec<BlocklistRule>>> {
+    db.run(move |conn| {
+        Ok(Json(BlocklistRule::get_all(conn)?))
+    }).await
+}
+
+/*
+Tries a rule against every club without saving it, so admins can see
+what it would catch before enabling it. Takes the same body as
+POST /blocklist, severity included even though it doesn't matter here.
+*/
+#[post("/blocklist/test", data = "<request>")]
+pub async fn test_rule(_admin: Admin, db: Db, request: Json<BlocklistRuleDTO>) -> Result<Json<Vec<ClubMatch>>> {
+    let rule = request.validate_rule()?;
+
+    db.run(move |conn| {
+        Ok(Json(rule.test(conn)?))
+    }).await
+}
+
+//The same for a saved rule, enabled or not.
+#[get("/blocklist/<id>/matches")]
+pub async fn get_matches(_admin: Admin, db: Db, id: i32) -> Result<Json<Vec<ClubMatch>>> {
+    db.run(move |conn| {
+        let rule = BlocklistRule::get_by_id(conn, &id)
+            .ok_or(ApiError::NotFound("That blocklist rule does not exist.".to_owned()))?;
+        let compiled = CompiledRule::from_rule(&rule).map_err(|error| ApiError::Validation(vec![error]))?;
+
+        Ok(Json(compiled.test(conn)?))
+    }).await
+}
@@ -0,0 +1,41 @@
+use crate::prelude::*;
+use crate::models::audit_log_md::{ACTION_BLOCKLIST_UPDATE, TARGET_BLOCKLIST_RULE};
+use super::create::BlocklistRuleDTO;
+
+//Replaces the rule, including whether it is enabled. Leaving enabled out keeps it as it is.
+#[put("/blocklist/<id>", data = "<request>")]
+pub async fn update(admin: Admin, db: Db, id: i32, ip: ClientIp, request: Json<BlocklistRuleDTO>) -> Result<Json<BlocklistRule>> {
+    use crate::schema::blocklist_rules::dsl::{blocklist_rules, pattern, match_type, severity, applies_to, enabled, note};
+
+    request.validate_rule()?;
+
+    let admin_id = admin.0.id.clone();
+    db.run(move |conn| conn.transaction(|| {
+        let current = BlocklistRule::get_by_id(conn, &id)
+            .ok_or(ApiError::NotFound("That blocklist rule does not exist.".to_owned()))?;
+
+        let updated = diesel::update(blocklist_rules.find(id))
+            .set((
+                pattern.eq(&request.pattern),
+                match_type.eq(&request.match_type),
+                severity.eq(&request.severity),
+                applies_to.eq(request.applies_to()),
+                enabled.eq(request.enabled.unwrap_or(current.enabled)),
+                note.eq(request.note.as_deref().unwrap_or("")),
+            ))
+            .get_result::<BlocklistRule>(conn)?;
+
+        AuditEntry::record(conn, &NewAuditEntry {
+            actor_id: Some(admin_id),
+            action: ACTION_BLOCKLIST_UPDATE,
+            target_type: TARGET_BLOCKLIST_RULE,
+            target_id: Some(id),
+            club_id: None,
+            before_snapshot: AuditEntry::snapshot(&current),
+            after_snapshot: AuditEntry::snapshot(&updated),
+            ip: ip.0.clone(),
+        })?;
+
+        Ok(Json(updated))
+    })).await
+}
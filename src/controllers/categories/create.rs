@@ -0,0 +1,25 @@
+use crate::prelude::*;
+
+#[derive(Deserialize)]
+pub struct NewCategoryDTO<'r> {
+    pub name: Cow<'r, str>,
+}
+
+#[post("/categories", data = "<category>")]
+pub async fn create(_admin: Admin, db: Db, category: Json<NewCategoryDTO<'_>>) -> Result<Json<Category>> {
+    use crate::schema::categories::dsl::{categories};
+
+    let category_name = category.name.trim().to_owned();
+    if category_name.is_empty() {
+        return Err(ApiError::BadRequest("Categories need a name.".to_owned()))
+    }
+
+    //Taken names come back as 409 through the unique constraint.
+    let created = db.run(move |conn| {
+        insert_into(categories)
+            .values(&NewCategory { name: &category_name })
+            .get_result::<Category>(conn)
+    }).await?;
+
+    Ok(Json(created))
+}
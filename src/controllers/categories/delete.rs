@@ -0,0 +1,18 @@
+use crate::prelude::*;
+
+//Clubs in the category are left without one.
+#[delete("/categories/<id>")]
+pub async fn delete(_admin: Admin, db: Db, id: i32) -> Result<status::Accepted<()>> {
+    use crate::schema::categories::dsl::{categories};
+
+    let deleted = db.run(move |conn| {
+        diesel::delete(categories.find(id))
+            .execute(conn)
+    }).await?;
+
+    if deleted > 0 {
+        Ok(status::Accepted(None))
+    } else {
+        Err(ApiError::NotFound("The category you are trying to delete does not exist.".to_owned()))
+    }
+}
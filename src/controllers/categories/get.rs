@@ -0,0 +1,10 @@
+use crate::prelude::*;
+
+#[get("/categories")]
+pub async fn get_all(db: Db) -> Result<Json<Vec<Category>>> {
+    let loaded_categories = db.run(move |conn| {
+        Category::get_all(conn)
+    }).await?;
+
+    Ok(Json(loaded_categories))
+}
@@ -0,0 +1,3 @@
+pub mod get;
+pub mod create;
+pub mod delete;
\ No newline at end of file
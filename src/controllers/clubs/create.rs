@@ -1,32 +1,64 @@
 use crate::prelude::*;
+use crate::validation::{normalize_name, validate_club_name};
+use crate::services::{blocklist, markdown};
+use crate::models::audit_log_md::ACTION_CLUB_CREATE;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Validate)]
 pub struct NewClubDTO<'r> {
+    #[validate(length(min = 3, max = 64), custom = "validate_club_name")]
     pub name: Cow<'r, str>,
+    #[validate(length(min = 1, max = 20000))]
     pub body: Cow<'r, str>
 }
 
 #[post("/clubs/create", data = "<club>")]
-pub async fn create(user: User, db: Db, club: Json<NewClubDTO<'_>>) -> Result<Json<Vec<ClubDetails>>, ()> {
-    use crate::schema::clubs::dsl::{clubs};
+pub async fn create(user: User, db: Db, club: Json<NewClubDTO<'_>>, lifecycle: &State<LifecycleConfig>, ip: ClientIp) -> Result<Json<Vec<ClubDetails>>> {
+    use crate::schema::clubs::dsl::{clubs, approval_status, review_note};
     use crate::schema::club_members::dsl::{club_members};
 
-    let name = club.name.to_string().clone();
-    let body = club.body.to_string().clone();
+    let mut club = club.into_inner();
+    club.name = Cow::Owned(normalize_name(&club.name));
+    club.validate()?;
+
+    let name = club.name.to_string();
+    let body = club.body.to_string();
+    let body_html = markdown::render(&body);
     let user_id = user.id.clone();
+    let expiry = lifecycle.next_expiry(None);
+
+    let taken_name = name.clone();
+    if db.run(move |conn| Club::name_taken(conn, &taken_name, None)).await? {
+        return Err(ApiError::Validation(vec![FieldError::new("name", "taken", "A club with that name already exists.")]))
+    }
 
-    let (created_club, created_club_member): (Club, ClubMember) = db.run(move |conn| {
+    let (screened_name, screened_body) = (name.clone(), body.clone());
+    let screening = db.run(move |conn| blocklist::screen(conn, &screened_name, &screened_body)).await?;
+    screening.check()?;
+
+    //Racing another request for the name, in any case or spacing, fails the insert with a unique violation, which goes out as 409.
+    //The club, its first revision and its head go in together or not at all.
+    let (created_club, created_club_member): (Club, ClubMember) = db.run(move |conn| conn.transaction(|| {
         let new_club = NewClub {
             name: &name.clone(),
             body: &body.clone(),
+            body_html: &body_html,
             publish_date: &chrono::offset::Utc::now(),
-            expiry_date: &(chrono::offset::Utc::now() + chrono::Duration::days(3)),
+            expiry_date: &expiry,
         };
 
-        let club = insert_into(clubs)
+        let mut club = insert_into(clubs)
             .values(&new_club)
-            .get_result::<Club>(conn).unwrap();
+            .get_result::<Club>(conn)?;
+
+        //New clubs wait for review anyway, this tells the admin why to look closely.
+        if screening.needs_review() {
+            club = diesel::update(clubs.find(club.id))
+                .set((approval_status.eq("pending"), review_note.eq(screening.review_note())))
+                .get_result::<Club>(conn)?;
+        }
 
+        ClubRevision::record(conn, &club, Some(&user.id), None)?;
+        AuditEntry::record_club(conn, user.id, &ip, ACTION_CLUB_CREATE, club.id, None, Some(&club))?;
 
         let new_club_member = NewClubMember{
             user_id: &user.id,
@@ -36,10 +68,10 @@ pub async fn create(user: User, db: Db, club: Json<NewClubDTO<'_>>) -> Result<Js
 
         let club_member = insert_into(club_members)
             .values(&new_club_member)
-            .get_result::<ClubMember>(conn).expect("Failed to add owner member to club.");
+            .get_result::<ClubMember>(conn)?;
         
-        (club, club_member)
-    }).await;
+        Ok::<_, diesel::result::Error>((club, club_member))
+    })).await?;
 
     match ClubDetails::from_join_async((created_club_member, created_club), user_id, db).await {
         Some(value) => {
@@ -48,9 +80,8 @@ pub async fn create(user: User, db: Db, club: Json<NewClubDTO<'_>>) -> Result<Js
             Ok(Json(vec))
         },
         None => {
-            eprintln!("Uh this wasn't supposed to happen.");
-            //eprintln!("member {:?}, created_club {:?}, user_id {:?}, db {:?}",member,created_club,user_id,db);
-            Err(())
+            eprintln!("Created a club without a head moderator.");
+            Err(ApiError::Internal)
         }
     }
 }
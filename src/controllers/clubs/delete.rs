@@ -1,48 +1,118 @@
 use crate::prelude::*;
+use crate::models::audit_log_md::{ACTION_CLUB_DELETE, ACTION_CLUB_RESTORE};
+
+/*
+Deleting a club only hides it. The head moderator or an admin can
+restore it until the restore window runs out, after that the purge job
+removes it along with its members. Also used when an admin deletes a
+club over reports.
+*/
+pub fn soft_delete(conn: &PgConnection, id: i32, deleted_by_user: i32, ip: &ClientIp, lifecycle: &LifecycleConfig) -> Result<status::Accepted<()>> {
+    use crate::schema::clubs::dsl::{clubs, deleted_at, deleted_by};
+
+    conn.transaction::<_, ApiError, _>(|| {
+        match Club::get_by_id(conn, &id) {
+            Some(club) if club.deleted_at.is_none() => {
+                let now = chrono::offset::Utc::now();
+                //Only the request that actually flips deleted_at gets to record and announce the deletion.
+                let deleted = diesel::update(clubs.find(id).filter(deleted_at.is_null()))
+                    .set((
+                        deleted_at.eq(now),
+                        deleted_by.eq(deleted_by_user),
+                    ))
+                    .get_result::<Club>(conn)
+                    .optional()?
+                    .ok_or(ApiError::NotFound("The club you are trying to delete does not exist.".to_owned()))?;
+                AuditEntry::record_club(conn, deleted_by_user, ip, ACTION_CLUB_DELETE, id, Some(&club), Some(&deleted))?;
+
+                //A failed insert aborts the transaction, so it has to fail the deletion rather than be ignored.
+                Notification::notify_moderators(conn, &id, "club_deleted", &format!(
+                    "{} was deleted. It can be restored until {}.", club.name, (now + lifecycle.restore_window()).format("%A, %B %e %Y")
+                ))?;
+                Ok(status::Accepted(None))
+            },
+            _ => Err(ApiError::NotFound("The club you are trying to delete does not exist.".to_owned()))
+        }
+    })
+}
+
+fn restore(conn: &PgConnection, id: i32, user_id: i32, ip: &ClientIp, lifecycle: &LifecycleConfig) -> Result<Json<ClubDetails>> {
+    use crate::schema::clubs::dsl::{clubs, deleted_at, deleted_by};
+
+    conn.transaction::<_, ApiError, _>(|| {
+        match Club::get_by_id(conn, &id) {
+            Some(club) if club.deleted_at.is_some() => {
+                if club.is_purgeable(lifecycle) {
+                    return Err(ApiError::Gone("The restore window for this club has passed.".to_owned()))
+                }
+
+                let restored = diesel::update(clubs.find(id).filter(deleted_at.is_not_null()))
+                    .set((
+                        deleted_at.eq(None::<DateTime<Utc>>),
+                        deleted_by.eq(None::<i32>),
+                    ))
+                    .get_result::<Club>(conn)
+                    .optional()?
+                    .ok_or(ApiError::BadRequest("The club you are trying to restore has not been deleted.".to_owned()))?;
+                AuditEntry::record_club(conn, user_id, ip, ACTION_CLUB_RESTORE, id, Some(&club), Some(&restored))?;
+                Ok(Json(restored.to_club_details(&conn, &user_id)?))
+            },
+            Some(_) => Err(ApiError::BadRequest("The club you are trying to restore has not been deleted.".to_owned())),
+            None => Err(ApiError::NotFound("The club you are trying to restore does not exist.".to_owned()))
+        }
+    })
+}
 
 #[delete("/clubs/<id>", rank=1)]
-pub async fn delete_admin(_admin: Admin, db: Db, id: i32) -> std::result::Result<status::Accepted<()>, status::Custom<Option<Json<JsonError>>>> {
-    use crate::schema::clubs::dsl::{clubs};
-    use crate::schema::club_members::dsl::{club_members, club_id};
-    let club = Club::get_by_id_async(&db, &id).await;
-    if club.is_some(){
-        db.run(move |conn| {
-            diesel::delete(club_members.filter(club_id.eq(id)))
-                    .execute(conn)
-                    .expect("Couldn't delete clubs_members prior to club deletion from database.");
-            diesel::delete(clubs.find(id))
-                .execute(conn)
-                .expect("Couldn't delete clubs from database.");
-        }).await;
-        Ok(status::Accepted(None))
-    }else {
-        Err(status::Custom(Status::BadRequest, Some(Json(JsonError {error: "The club you are trying to delete does not exist.".to_owned()}))))
-    }
+pub async fn delete_admin(admin: Admin, db: Db, id: i32, ip: ClientIp, lifecycle: &State<LifecycleConfig>) -> Result<status::Accepted<()>> {
+    let user_id = admin.0.id.clone();
+    let lifecycle = *lifecycle.inner();
+    db.run(move |conn| {
+        soft_delete(conn, id, user_id, &ip, &lifecycle)
+    }).await
 }
 
 #[delete("/clubs/<id>", rank=2)]
-pub async fn delete_user(user: User, db: Db, id: i32) -> std::result::Result<status::Accepted<()>, status::Custom<Option<Json<JsonError>>>> {
-    use crate::schema::clubs::dsl::{clubs};
-    use crate::schema::club_members::dsl::{club_members, club_id};
-
-    match user.get_membership_status_async(&db, &id).await {
+pub async fn delete_user(user: User, db: Db, id: i32, ip: ClientIp, lifecycle: &State<LifecycleConfig>) -> Result<status::Accepted<()>> {
+    let user_id = user.id.clone();
+    let lifecycle = *lifecycle.inner();
+    match user.get_membership_status_async(&db, &id).await? {
         MembershipStatus::Moderator(is_head) => {
             if is_head {
-                let _result = db.run(move |conn| {
-                    diesel::delete(club_members.filter(club_id.eq(id)))
-                        .execute(conn)
-                        .expect("Couldn't delete clubs_members prior to club deletion from database.");
-                    diesel::delete(clubs.find(id))
-                        .execute(conn)
-                        .expect("Couldn't delete clubs from database.");
-                }).await;
-                Ok(status::Accepted(None))
+                db.run(move |conn| {
+                    soft_delete(conn, id, user_id, &ip, &lifecycle)
+                }).await
             } else {
-                Err(status::Custom(Status::Forbidden, Some(Json(JsonError {error: "User is not a head moderator.".to_owned()}))))
+                Err(ApiError::Forbidden("User is not a head moderator.".to_owned()))
             }
         },
         _ => {
-            Err(status::Custom(Status::BadRequest, Some(Json(JsonError {error: "User isn't even a moderator.".to_owned()}))))
+            Err(ApiError::BadRequest("User isn't even a moderator.".to_owned()))
         }
     }
-}
\ No newline at end of file
+}
+
+#[put("/clubs/<id>/restore", rank=1)]
+pub async fn restore_admin(admin: Admin, db: Db, id: i32, ip: ClientIp, lifecycle: &State<LifecycleConfig>) -> Result<Json<ClubDetails>> {
+    let user_id = admin.0.id.clone();
+    let lifecycle = *lifecycle.inner();
+    db.run(move |conn| {
+        restore(conn, id, user_id, &ip, &lifecycle)
+    }).await
+}
+
+#[put("/clubs/<id>/restore", rank=2)]
+pub async fn restore_user(user: User, db: Db, id: i32, ip: ClientIp, lifecycle: &State<LifecycleConfig>) -> Result<Json<ClubDetails>> {
+    let user_id = user.id.clone();
+    let lifecycle = *lifecycle.inner();
+    match user.get_membership_status_async(&db, &id).await? {
+        MembershipStatus::Moderator(true) => {
+            db.run(move |conn| {
+                restore(conn, id, user_id, &ip, &lifecycle)
+            }).await
+        },
+        _ => {
+            Err(ApiError::Forbidden("Only the head moderator or an admin can restore a club.".to_owned()))
+        }
+    }
+}
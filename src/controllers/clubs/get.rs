@@ -1,122 +1,122 @@
 use crate::prelude::*;
 
-#[get("/clubs")]
-pub async fn get_all(user: User, db: Db) -> Result<Json<Vec<ClubDetails>>> {
-    use crate::schema::clubs::dsl::{clubs};
-    use crate::schema::club_members::dsl::{club_members, club_id, user_id};
-
-    let loaded_clubs: Vec<ClubDetails> = db.run(move |conn| {
-        let club_load = clubs
-            //.filter(user_id.eq(user.id).or(user_id.nullable().is_null()))
-            .load::<Club>(conn)
-            .expect("Couldn't perform left outer join with clubs from database.");
-        
-        let mut results = Vec::new();
-        for  club  in club_load {
-            let member = club_members.filter(user_id.eq(user.id)).filter(club_id.eq(club.id)).first::<ClubMember>(conn);
-
-            let member_unwrapped = member.unwrap_or(ClubMember{
-                id: -1,
-                user_id: -1,
-                club_id: -1,
-                is_moderator: "false".to_owned()
-            });
-            if let Some(result)=ClubDetails::from_join((member_unwrapped, club), user.id, &conn){
-                results.push(result)
-            }
-        }
-
-        results
-    }).await;
+/*
+Lists clubs one page at a time. Supports full text search (q),
+membership filters, several sort orders and cursor pagination, pass
+the returned next_cursor back as cursor to get the following page.
+Guests can browse too but only see public clubs.
+*/
+#[get("/clubs?<query..>")]
+pub async fn get_all(user: Option<User>, db: Db, query: ClubQuery) -> Result<Json<ClubPage>> {
+    let user_id = user.map(|user| user.id);
+    let page = db.run(move |conn| {
+        Club::search(conn, user_id, &query)
+    }).await?;
+
+    Ok(Json(page))
+}
+
+/*
+The admin review queue. Lists pending clubs by default, pass a status
+(changes_requested, rejected or approved) to look at the others.
+*/
+#[get("/clubs/review?<status>")]
+pub async fn get_review_queue(admin: Admin, db: Db, status: Option<String>) -> Result<Json<Vec<ClubDetails>>> {
+    let status = status.unwrap_or("pending".to_owned());
+    let loaded_clubs = db.run(move |conn| {
+        ClubDetails::load_for_review(conn, &admin.0.id, &status)
+    }).await?;
 
     Ok(Json(loaded_clubs))
 }
 
+//Admin tool, lists clubs that lost their head moderator.
+#[get("/clubs/headless")]
+pub async fn get_headless(_admin: Admin, db: Db) -> Result<Json<Vec<HeadlessClub>>> {
+    let headless = db.run(move |conn| {
+        ClubMember::find_headless(conn)
+    }).await?;
+
+    Ok(Json(headless))
+}
+
 #[get("/clubs/<id>")]
-pub async fn get_club_details(user: User, db: Db, id: i32) -> std::result::Result<status::Custom<Json<ClubDetails>>, status::Custom<Option<Json<JsonError>>>> {
-    use crate::schema::clubs::dsl::{clubs};
-    use crate::schema::club_members::dsl::{club_members, club_id, user_id};
-
-    let loaded_clubs: Vec<ClubDetails> = db.run(move |conn| {
-        let club_load = clubs
-            .load::<Club>(conn)
-            .expect("Couldn't perform left outer join with clubs from database.");
-        
-            let mut results = Vec::new();
-
-            for  club  in club_load {
-                if club.id == id {
-                    let member = club_members.filter(user_id.eq(user.id)).filter(club_id.eq(club.id)).first::<ClubMember>(conn);
-    
-                    let member_unwrapped = member.unwrap_or(ClubMember{
-                        id: -1,
-                        user_id: -1,
-                        club_id: -1,
-                        is_moderator: "false".to_owned()
-                    });
-                    if let Some(result)=ClubDetails::from_join((member_unwrapped, club), user.id, &conn){
-                        results.push(result)
-                    }
-                }
-            }
-    
-            results
-    }).await;
-
-    if loaded_clubs.len() > 0 {
-        Ok(status::Custom(Status::Ok, Json(loaded_clubs[0].to_owned())))
-    } else {
-        Err(status::Custom(Status::NotFound, Some(Json(JsonError {error: "The club you are trying to get the details of does not exist.".to_owned()}))))
+pub async fn get_club_details(user: Option<User>, db: Db, id: i32) -> Result<Versioned<Json<ClubDetails>>> {
+    let user_id = user.map(|user| user.id);
+    let loaded_club = db.run(move |conn| {
+        ClubDetails::get_for_user(conn, &id, user_id)
+    }).await?;
+
+    match loaded_club {
+        Some(club) => {
+            let version = club.version;
+            Ok(Versioned(Json(club), version))
+        },
+        None => Err(ApiError::NotFound("The club you are trying to get the details of does not exist.".to_owned())),
     }
 }
 
 #[get("/clubs/by/membership")]
 pub async fn get_clubs_by_membership(user: User, db: Db) -> Result<Json<Vec<ClubDetails>>> {
-    use crate::schema::clubs::dsl::{clubs};
-    use crate::schema::club_members::dsl::{club_members, user_id};
-
-    let loaded_clubs: Vec<ClubDetails> = db.run(move |conn| {
-        let join = club_members
-            .inner_join(clubs)
-            .filter(user_id.eq(user.id))
-            .load::<(ClubMember, Club)>(conn)
-            .expect("Couldn't perform inner join with clubs from database.");
-        
-        let mut results = Vec::new();
-        for (member, club) in join {
-            if let Some(result)=ClubDetails::from_join((member, club), user.id, &conn){
-                results.push(result);
-            }
-        }
-
-        results
-    }).await;
+    let loaded_clubs = db.run(move |conn| {
+        ClubDetails::load_by_membership(conn, &user.id, MembershipFilter::Member)
+    }).await?;
 
     Ok(Json(loaded_clubs))
 }
 
 #[get("/clubs/by/moderatorship")]
 pub async fn get_clubs_by_moderatorship(user: User, db: Db) -> Result<Json<Vec<ClubDetails>>> {
-    use crate::schema::clubs::dsl::{clubs};
-    use crate::schema::club_members::dsl::{club_members, user_id, is_moderator};
-
-    let loaded_clubs: Vec<ClubDetails> = db.run(move |conn| {
-        let join = club_members
-            .inner_join(clubs)
-            .filter(user_id.eq(user.id))
-            .filter(is_moderator.eq("true").or(is_moderator.eq("head")))
-            .load::<(ClubMember, Club)>(conn)
-            .expect("Couldn't perform inner join with clubs from database.");
-        
-        let mut results = Vec::new();
-        for (member, club) in join {
-            if let Some(result)=ClubDetails::from_join((member, club), user.id, &conn){
-                results.push(result);
-            }
-        }
-        
-        results
-    }).await;
+    let loaded_clubs = db.run(move |conn| {
+        ClubDetails::load_by_membership(conn, &user.id, MembershipFilter::Moderator)
+    }).await?;
 
     Ok(Json(loaded_clubs))
-}
\ No newline at end of file
+}
+
+//Every term the club has registered for, newest first. Moderators only.
+#[get("/clubs/<id>/registrations")]
+pub async fn get_registrations(user: User, db: Db, id: i32) -> Result<Json<Vec<ClubRegistration>>> {
+    match user.get_membership_status_async(&db, &id).await? {
+        MembershipStatus::Moderator(_) => {
+            let loaded_registrations = db.run(move |conn| {
+                ClubRegistration::get_for_club(conn, &id)
+            }).await?;
+
+            Ok(Json(loaded_registrations))
+        },
+        _ => Err(ApiError::Forbidden("You are not allowed to do that.".to_owned()))
+    }
+}
+
+//The club's edit history, newest first. Moderators only.
+#[get("/clubs/<id>/revisions")]
+pub async fn get_revisions(user: User, db: Db, id: i32) -> Result<Json<Vec<ClubRevisionSummary>>> {
+    let user_id = user.id.clone();
+    match user.get_membership_status_async(&db, &id).await? {
+        MembershipStatus::Moderator(_) => {
+            let loaded_revisions = db.run(move |conn| {
+                ClubRevision::list(conn, &id, Some(user_id))
+            }).await?;
+
+            Ok(Json(loaded_revisions))
+        },
+        _ => Err(ApiError::Forbidden("You are not allowed to do that.".to_owned()))
+    }
+}
+
+//What changed between two revisions of the club, in either direction.
+#[get("/clubs/<id>/revisions/diff?<from>&<to>")]
+pub async fn get_revision_diff(user: User, db: Db, id: i32, from: i32, to: i32) -> Result<Json<ClubRevisionDiff>> {
+    match user.get_membership_status_async(&db, &id).await? {
+        MembershipStatus::Moderator(_) => {
+            db.run(move |conn| {
+                match (ClubRevision::get_for_club(conn, &id, &from), ClubRevision::get_for_club(conn, &id, &to)) {
+                    (Some(from), Some(to)) => Ok(Json(from.diff(&to))),
+                    _ => Err(ApiError::NotFound("That revision does not exist.".to_owned()))
+                }
+            }).await
+        },
+        _ => Err(ApiError::Forbidden("You are not allowed to do that.".to_owned()))
+    }
+}
@@ -1,95 +1,348 @@
 use crate::prelude::*;
+use crate::validation::{normalize_name, validate_club_name};
+use crate::services::{blocklist, logos, markdown};
+use crate::models::audit_log_md::{ACTION_CLUB_UPDATE, ACTION_CLUB_RENEW, ACTION_CLUB_APPOINT, ACTION_CLUB_LOGO, ACTION_CLUB_SET_HEAD, ACTION_CLUB_REVERT, ACTION_CLUB_REVIEW, ACTION_CLUB_REACTIVATE, ACTION_CLUB_LIFECYCLE, ACTION_CLUB_VERIFY, ACTION_CLUB_REPAIR_HEAD};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Validate)]
 pub struct UpdateClubDTO<'r> {
+    #[validate(length(min = 3, max = 64), custom = "validate_club_name")]
     pub name: Cow<'r, str>,
-    pub body: Cow<'r, str>
+    #[validate(length(min = 1, max = 20000))]
+    pub body: Cow<'r, str>,
+    //Left alone when missing.
+    pub is_public: Option<bool>,
+}
+
+#[derive(Responder)]
+pub enum ClubEditError {
+    //Someone else changed the club first, carries the current details and their ETag.
+    #[response(status = 412)]
+    Stale(Versioned<Json<ClubDetails>>),
+    Failed(ApiError),
+}
+
+impl From<ApiError> for ClubEditError {
+    fn from(error: ApiError) -> Self {
+        ClubEditError::Failed(error)
+    }
 }
 
+impl From<diesel::result::Error> for ClubEditError {
+    fn from(error: diesel::result::Error) -> Self {
+        ClubEditError::Failed(error.into())
+    }
+}
+
+/*
+What every new name and description goes through, returns the name as
+it is stored and what the blocklist made of it. Reject rules fail the
+edit, review rules are up to the caller, see hold_for_review.
+*/
+fn check_details(conn: &PgConnection, id: i32, club_name: &str, club_body: &str) -> Result<(String, blocklist::Screening)> {
+    let details = UpdateClubDTO {
+        name: Cow::Owned(normalize_name(club_name)),
+        body: Cow::Borrowed(club_body),
+        is_public: None,
+    };
+    details.validate()?;
+
+    if Club::name_taken(conn, &details.name, Some(id))? {
+        return Err(ApiError::Validation(vec![
+            FieldError::new("name", "taken", "A club with that name already exists.")
+        ]))
+    }
+
+    let screening = blocklist::screen(conn, &details.name, club_body)?;
+    screening.check()?;
+
+    Ok((details.name.into_owned(), screening))
+}
+
+//Matching a review rule of the blocklist sends the club back to the review queue, listed or not.
+fn hold_for_review(conn: &PgConnection, id: i32, screening: &blocklist::Screening) -> QueryResult<Club> {
+    use crate::schema::clubs::dsl::{clubs, approval_status, review_note};
+
+    diesel::update(clubs.find(id))
+        .set((approval_status.eq("pending"), review_note.eq(screening.review_note())))
+        .get_result::<Club>(conn)
+}
+
+/*
+Edits need the club's ETag in If-Match. When it no longer matches the
+edit is refused with 412 and the club as it is now, so the editor can
+decide whether to keep their changes or the other ones.
+*/
 #[put("/clubs/<id>", data = "<club>")]
-pub async fn update(user: User, db: Db, id: i32, club: Json<UpdateClubDTO<'_>>) -> std::result::Result<Json<ClubDetails>, status::Custom<Option<Json<JsonError>>>> {
+pub async fn update(user: User, db: Db, id: i32, if_match: IfMatch, ip: ClientIp, club: Json<UpdateClubDTO<'_>>) -> std::result::Result<Versioned<Json<ClubDetails>>, ClubEditError> {
     let user_id=user.id.clone();
-    use crate::schema::clubs::dsl::{clubs, name,body};
+    use crate::schema::clubs::dsl::{clubs, name, body, body_html, is_public, approval_status, version};
 
-    let club_name = club.name.to_string().clone();
-    let club_body = club.body.to_string().clone();
-    match user.get_membership_status_async(&db, &id).await {
+    if let IfMatch::Missing = if_match {
+        return Err(ClubEditError::Failed(ApiError::PreconditionRequired("Send the version you are editing in If-Match.".to_owned())))
+    }
+
+    let mut club = club.into_inner();
+    club.name = Cow::Owned(normalize_name(&club.name));
+    if let Err(errors) = club.validate() {
+        return Err(ClubEditError::Failed(errors.into()))
+    }
+
+    let club_name = club.name.to_string();
+    let club_body = club.body.to_string();
+    let club_body_html = markdown::render(&club_body);
+    let club_is_public = club.is_public;
+    match user.get_membership_status_async(&db, &id).await? {
         MembershipStatus::Moderator(is_head) => {
             if is_head {
-                let result = db.run(move |conn| {
+                //The club, its revision and the audit entry change together or not at all.
+                let result = db.run(move |conn| conn.transaction::<_, ClubEditError, _>(|| {
+                    let current = match Club::get_active(conn, &id) {
+                        Some(current) => current,
+                        None => return Err(ClubEditError::Failed(ApiError::NotFound("The club you are trying to access does not exist.".to_owned())))
+                    };
+
+                    if !if_match.matches(current.version) {
+                        let current_version = current.version;
+                        return Err(ClubEditError::Stale(Versioned(Json(current.to_club_details(&conn, &user_id)?), current_version)))
+                    }
+
+                    let (club_name, screening) = check_details(conn, id, &club_name, &club_body)?;
+
+                    //Checking the version again in the update closes the gap since we read it.
                     let update = diesel::update(clubs.find(id))
+                        .filter(version.eq(current.version))
                         .set((
                             name.eq(club_name),
                             body.eq(club_body),
+                            body_html.eq(club_body_html),
+                            is_public.eq(club_is_public.unwrap_or(current.is_public)),
                         ))
-                        .get_result::<Club>(conn);
-                    
-                    if let Ok(update) = update{
-                        let member = ClubMember{
-                            id: -1,
-                            user_id: user_id,
-                            club_id: update.id,
-                            is_moderator: if is_head {"head".to_owned()} else {"true".to_owned()}
-                        };
-                        Ok(Json(ClubDetails::from_join((member, update), user_id, &conn).unwrap()))
-                    }else{
-                        Err(status::Custom(Status::BadRequest, Some(Json(JsonError {error: "The club you are trying to access does not exist.".to_owned()}))))
+                        .get_result::<Club>(conn)
+                        .optional();
+
+                    match update {
+                        Ok(Some(update)) => {
+                            //Editing a club admins asked changes for sends it back to the review queue.
+                            diesel::update(clubs.find(id))
+                                .filter(approval_status.eq("changes_requested"))
+                                .set(approval_status.eq("pending"))
+                                .execute(conn)?;
+
+                            //So does matching a review rule of the blocklist.
+                            let update = if screening.needs_review() {
+                                hold_for_review(conn, id, &screening)?
+                            } else {
+                                update
+                            };
+
+                            ClubRevision::record(conn, &update, Some(&user_id), None)?;
+                            AuditEntry::record_club(conn, user_id, &ip, ACTION_CLUB_UPDATE, id, Some(&current), Some(&update))
+                                .map_err(|e| ClubEditError::Failed(e.into()))?;
+                            let updated_version = update.version;
+                            let member = ClubMember{
+                                id: -1,
+                                user_id: user_id,
+                                club_id: update.id,
+                                is_moderator: if is_head {"head".to_owned()} else {"true".to_owned()}
+                            };
+                            let details = ClubDetails::from_join((member, update), user_id, &conn).ok_or(ClubEditError::Failed(ApiError::Internal))?;
+                            Ok(Versioned(Json(details), updated_version))
+                        },
+                        Ok(None) => {
+                            let latest = Club::get_active(conn, &id).ok_or(ClubEditError::Failed(ApiError::NotFound("The club you are trying to access does not exist.".to_owned())))?;
+                            let latest_version = latest.version;
+                            Err(ClubEditError::Stale(Versioned(Json(latest.to_club_details(&conn, &user_id)?), latest_version)))
+                        },
+                        Err(e) => Err(ClubEditError::Failed(e.into()))
                     }
-                }).await;
+                })).await;
                 result
             }else{
-                Err(status::Custom(Status::Forbidden, None))
+                Err(ClubEditError::Failed(ApiError::Forbidden("You are not allowed to do that.".to_owned())))
             }
         },
         _ => {
-            Err(status::Custom(Status::Forbidden, None))
+            Err(ClubEditError::Failed(ApiError::Forbidden("You are not allowed to do that.".to_owned())))
         }
     }
 }
 
 
 #[put("/clubs/<id>/renew")]
-pub async fn renew(user: User, db: Db, id: i32) -> std::result::Result<Json<ClubDetails>, status::Custom<Option<Json<JsonError>>>> {
-    use crate::schema::clubs::dsl::{clubs, expiry_date};
+pub async fn renew(user: User, db: Db, id: i32, ip: ClientIp, lifecycle: &State<LifecycleConfig>) -> Result<Json<ClubDetails>> {
+    use crate::schema::clubs::dsl::{clubs, expiry_date, expiry_warned_at};
 
     let user_id=user.id.clone();
-    match user.get_membership_status_async(&db, &id).await {
+    let lifecycle = *lifecycle.inner();
+    match user.get_membership_status_async(&db, &id).await? {
         MembershipStatus::Moderator(is_head) => {
             let result = db.run(move |conn| {
+                let club = match Club::get_active(conn, &id) {
+                    Some(club) => club,
+                    None => return Err(ApiError::NotFound("The club you are trying to access does not exist.".to_owned()))
+                };
+
+                if club.archived_at.is_some() {
+                    return Err(ApiError::BadRequest("This club has been archived, it has to be reactivated instead.".to_owned()))
+                }
+
+                if Term::terms_in_use(conn).unwrap_or(false) {
+                    return Err(ApiError::BadRequest("Clubs are registered per term now, re-register the club for the upcoming term instead.".to_owned()))
+                }
+
                 let update = diesel::update(clubs.find(id))
-                    .set(expiry_date.eq(&(chrono::offset::Utc::now() + chrono::Duration::days(3))))
+                    .set((
+                        expiry_date.eq(lifecycle.next_expiry(club.renewal_days)),
+                        expiry_warned_at.eq(None::<DateTime<Utc>>),
+                    ))
                     .get_result::<Club>(conn);
                 
                 if let Ok(update) = update{
+                    AuditEntry::record_club(conn, user_id, &ip, ACTION_CLUB_RENEW, id, Some(&club), Some(&update))?;
                     let member = ClubMember{
                         id: -1,
                         user_id: user_id,
                         club_id: update.id,
                         is_moderator: if is_head {"head".to_owned()} else {"true".to_owned()}
                     };
-                    Ok(Json(ClubDetails::from_join((member, update), user_id, &conn).unwrap()))
+                    ClubDetails::from_join((member, update), user_id, &conn).map(Json).ok_or(ApiError::Internal)
                 }else{
-                    Err(status::Custom(Status::BadRequest, Some(Json(JsonError {error: "The club you are trying to access does not exist.".to_owned()}))))
+                    Err(ApiError::NotFound("The club you are trying to access does not exist.".to_owned()))
                 }
             }).await;
             result
         },
         _ => {
-            Err(status::Custom(Status::Forbidden, None))
+            Err(ApiError::Forbidden("You are not allowed to do that.".to_owned()))
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ClubRegistrationDTO<'r> {
+    //Defaults to the upcoming term, or the current one when none is scheduled.
+    pub term_id: Option<i32>,
+    //Members who should be the club's moderators for the term, everyone else loses moderator rights.
+    pub officer_ids: Vec<i32>,
+    //Updated details for the term, left alone when missing.
+    pub name: Option<Cow<'r, str>>,
+    pub body: Option<Cow<'r, str>>,
+}
+
+/*
+Re-registers a club for a term. Only the head moderator can confirm
+the officers and details, the registration then waits for an admin.
+Changing the details is an edit like any other, it needs If-Match.
+*/
+#[put("/clubs/<id>/register", data = "<request>")]
+pub async fn register(user: User, db: Db, id: i32, if_match: IfMatch, request: Json<ClubRegistrationDTO<'_>>) -> std::result::Result<Json<ClubRegistration>, ClubEditError> {
+    use crate::schema::clubs::dsl::{clubs, name, body, body_html, version};
+    use crate::schema::club_members::dsl::{club_members, club_id, user_id};
+
+    let user_id_copy = user.id.clone();
+    let term_id = request.term_id;
+    let officers = request.officer_ids.clone();
+    let club_name = request.name.as_ref().map(|club_name| club_name.to_string());
+    let club_body = request.body.as_ref().map(|club_body| club_body.to_string());
+    let changes_details = club_name.is_some() || club_body.is_some();
+
+    if let (true, IfMatch::Missing) = (changes_details, &if_match) {
+        return Err(ClubEditError::Failed(ApiError::PreconditionRequired("Send the version you are editing in If-Match.".to_owned())))
+    }
+
+    match user.get_membership_status_async(&db, &id).await? {
+        MembershipStatus::Moderator(true) => {
+            db.run(move |conn| conn.transaction::<_, ClubEditError, _>(|| {
+                let term = match term_id {
+                    Some(term_id) => Term::get_by_id(conn, &term_id),
+                    None => match Term::upcoming(conn)? {
+                        Some(term) => Some(term),
+                        None => Term::current(conn)?,
+                    },
+                };
+                let term = match term {
+                    Some(term) if term.end_date > chrono::offset::Utc::now() => term,
+                    Some(_) => return Err(ApiError::BadRequest("That term is already over.".to_owned()).into()),
+                    None => return Err(ApiError::BadRequest("There is no term to register for.".to_owned()).into())
+                };
+
+                let current = match Club::get_active(conn, &id) {
+                    Some(current) => current,
+                    None => return Err(ApiError::NotFound("The club you are trying to access does not exist.".to_owned()).into())
+                };
+
+                let members = club_members
+                    .filter(club_id.eq(id))
+                    .filter(user_id.eq_any(&officers))
+                    .count()
+                    .get_result::<i64>(conn)?;
+                let mut distinct_officers = officers.clone();
+                distinct_officers.sort();
+                distinct_officers.dedup();
+                if members != distinct_officers.len() as i64 {
+                    return Err(ApiError::BadRequest("Every officer has to be a member of the club.".to_owned()).into())
+                }
+                if !distinct_officers.contains(&user_id_copy) {
+                    distinct_officers.push(user_id_copy);
+                }
+
+                if changes_details {
+                    if !if_match.matches(current.version) {
+                        let current_version = current.version;
+                        return Err(ClubEditError::Stale(Versioned(Json(current.to_club_details(&conn, &user_id_copy)?), current_version)))
+                    }
+
+                    let club_body = club_body.unwrap_or_else(|| current.body.clone());
+                    let (club_name, screening) = check_details(conn, id, club_name.as_deref().unwrap_or(&current.name), &club_body)?;
+                    let rendered = markdown::render(&club_body);
+
+                    let update = diesel::update(clubs.find(id))
+                        .filter(version.eq(current.version))
+                        .set((name.eq(club_name), body.eq(club_body), body_html.eq(rendered)))
+                        .get_result::<Club>(conn)
+                        .optional()?;
+                    match update {
+                        Some(update) => {
+                            let update = if screening.needs_review() {
+                                hold_for_review(conn, id, &screening)?
+                            } else {
+                                update
+                            };
+                            ClubRevision::record(conn, &update, Some(&user_id_copy), None)?;
+                        },
+                        None => {
+                            let latest = Club::get_active(conn, &id).ok_or(ApiError::NotFound("The club you are trying to access does not exist.".to_owned()))?;
+                            let latest_version = latest.version;
+                            return Err(ClubEditError::Stale(Versioned(Json(latest.to_club_details(&conn, &user_id_copy)?), latest_version)))
+                        }
+                    }
+                }
+
+                let registration = ClubRegistration::submit(conn, &id, &term, &user_id_copy, &distinct_officers)?;
+                Ok(Json(registration))
+            })).await
+        },
+        _ => {
+            Err(ClubEditError::Failed(ApiError::Forbidden("Only the head moderator can register the club for a term.".to_owned())))
         }
     }
 }
 
 #[put("/clubs/<id>/join")]
-pub async fn join(user: User, db: Db, id: i32) -> std::result::Result<Json<ClubDetails>, status::Custom<Option<Json<JsonError>>>> {
-    use crate::schema::clubs::dsl::{clubs};
+pub async fn join(user: User, db: Db, id: i32) -> Result<Json<ClubDetails>> {
+    use crate::schema::clubs::dsl::{clubs, deleted_at, archived_at, approval_status};
     use crate::schema::club_members::dsl::{club_members};
 
     let user_id=user.id.clone();
-    match user.get_membership_status_async(&db, &id).await {
+    match user.get_membership_status_async(&db, &id).await? {
         MembershipStatus::Unassociated => {
             let result = db.run(move |conn| {
-                let club_exists = clubs.find(id).get_result::<Club>(conn);
+                //Only listed clubs can be joined, pending, archived and deleted ones can't take new members.
+                let club_exists = clubs.find(id)
+                    .filter(deleted_at.is_null())
+                    .filter(archived_at.is_null())
+                    .filter(approval_status.eq("approved"))
+                    .get_result::<Club>(conn);
                 
                 if club_exists.is_ok() {
                     let member = NewClubMember{
@@ -98,40 +351,40 @@ pub async fn join(user: User, db: Db, id: i32) -> std::result::Result<Json<ClubD
                         is_moderator: &"false",
                     };
 
-                    let result = insert_into(club_members).values(member).get_result(conn);
-                    Ok(Json(ClubDetails::from_join((result.unwrap(), club_exists.unwrap()), user_id, &conn).unwrap()))
+                    let result = insert_into(club_members).values(member).get_result(conn)?;
+                    ClubDetails::from_join((result, club_exists?), user_id, &conn).map(Json).ok_or(ApiError::Internal)
                 }else{
-                    Err(status::Custom(Status::BadRequest, Some(Json(JsonError {error: "The club you are trying to join does not exist.".to_owned()}))))
+                    Err(ApiError::NotFound("The club you are trying to join does not exist.".to_owned()))
                 }
             }).await;
             result
         },
         _ => {
-            Err(status::Custom(Status::BadRequest, Some(Json(JsonError {error: "User is already a member or moderator.".to_owned()}))))
+            Err(ApiError::BadRequest("User is already a member or moderator.".to_owned()))
         }
     }
 }
 
 #[put("/clubs/<id>/leave")]
-pub async fn leave(user: User, db: Db, id: i32) -> std::result::Result<status::Accepted<()>, status::Custom<Option<Json<JsonError>>>> {
+pub async fn leave(user: User, db: Db, id: i32) -> Result<status::Accepted<()>> {
     use crate::schema::clubs::dsl::{clubs};
     use crate::schema::club_members::dsl::{club_members, club_id, user_id};
 
     let user_id_copy = user.id.clone();
-    match user.get_membership_status_async(&db, &id).await {
+    match user.get_membership_status_async(&db, &id).await? {
         MembershipStatus::Moderator(is_head) => {
             let result = db.run(move |conn| {
                 let club_exists = clubs.find(id).get_result::<Club>(conn);
                 
                 if club_exists.is_ok() {
                     if !is_head{
-                        let _result = diesel::delete(club_members).filter(club_id.eq(id)).filter(user_id.eq(&user_id_copy)).execute(conn).unwrap();
+                        let _result = diesel::delete(club_members).filter(club_id.eq(id)).filter(user_id.eq(&user_id_copy)).execute(conn)?;
                         Ok(status::Accepted(None))
                     }else{
-                        Err(status::Custom(Status::BadRequest, Some(Json(JsonError {error: "You are the appointed head of the club appoint a new one or delete the club".to_owned()}))))
+                        Err(ApiError::BadRequest("You are the appointed head of the club appoint a new one or delete the club".to_owned()))
                     }
                 }else{
-                    Err(status::Custom(Status::BadRequest, Some(Json(JsonError {error: "The club you are trying to leave does not exist.".to_owned()}))))
+                    Err(ApiError::NotFound("The club you are trying to leave does not exist.".to_owned()))
                 }
             }).await;
             result
@@ -141,16 +394,16 @@ pub async fn leave(user: User, db: Db, id: i32) -> std::result::Result<status::A
                 let club_exists = clubs.find(id).get_result::<Club>(conn);
                 
                 if club_exists.is_ok() {
-                    let _result = diesel::delete(club_members).filter(club_id.eq(id)).filter(user_id.eq(&user_id_copy)).execute(conn).unwrap();
+                    let _result = diesel::delete(club_members).filter(club_id.eq(id)).filter(user_id.eq(&user_id_copy)).execute(conn)?;
                     Ok(status::Accepted(None))
                 }else{
-                    Err(status::Custom(Status::BadRequest, Some(Json(JsonError {error: "The club you are trying to leave does not exist.".to_owned()}))))
+                    Err(ApiError::NotFound("The club you are trying to leave does not exist.".to_owned()))
                 }
             }).await;
             result
         },
         _ => {
-            Err(status::Custom(Status::BadRequest, Some(Json(JsonError {error: "User is already unassociated with the club.".to_owned()}))))
+            Err(ApiError::BadRequest("User is already unassociated with the club.".to_owned()))
         }
     }
 }
@@ -161,105 +414,461 @@ pub struct AppointModeratorRequestDTO {
     pub appoint_to_head: bool,
 }
 
+/*
+Appointing someone else head makes the current head a moderator. Both
+changes happen in one transaction so the club is never left without a
+head or with two.
+*/
 #[put("/clubs/<id>/appoint", data = "<request>")]
-pub async fn appoint(user: User, db: Db, id: i32, request: Json<AppointModeratorRequestDTO>) -> std::result::Result<status::Accepted<Json<ClubDetails>>, status::Custom<Option<Json<JsonError>>>> {
-    use crate::schema::clubs::dsl::{clubs};
+pub async fn appoint(user: User, db: Db, id: i32, ip: ClientIp, request: Json<AppointModeratorRequestDTO>) -> Result<status::Accepted<Json<ClubDetails>>> {
     use crate::schema::club_members::dsl::{club_members, club_id, user_id, is_moderator};
 
     let user_id_copy = user.id.clone();
-    match user.get_membership_status_async(&db, &id).await {
+    match user.get_membership_status_async(&db, &id).await? {
         MembershipStatus::Moderator(is_head) => {
             let result = db.run(move |conn| {
-                let club_exists = clubs.find(id).get_result::<Club>(conn);        
-                if club_exists.is_ok() {
+                let club_exists = Club::get_active(conn, &id);
+                if club_exists.is_some() {
                     if is_head{
                         if let Some(fetched_user) = User::get_by_id(conn, &request.user_id){
-                            match fetched_user.get_membership_status(conn, &request.user_id){
-                                MembershipStatus::Moderator(fetched_user_is_head) => {
-                                    if request.appoint_to_head == true && fetched_user_is_head == false{
-                                        //Make current user just a moderator.
-                                        let _res = diesel::update(club_members)
-                                            .filter(club_id.eq(id))
-                                            .filter(user_id.eq(&user_id_copy))
-                                            .set(is_moderator.eq("true"))
-                                            .execute(conn);
-                                        //Appoint new user to head moderator.
-                                        let _res = diesel::update(club_members)
-                                            .filter(club_id.eq(id))
-                                            .filter(user_id.eq(&request.user_id))
-                                            .set(is_moderator.eq("head"))
-                                            .execute(conn);
-                                    } else {
-                                        return Err(status::Custom(Status::BadRequest, Some(Json(JsonError {error: "You already are a head moderator.".to_owned()}))))
-                                    }
-                                },
-                                MembershipStatus::Member => {
-                                    if request.appoint_to_head == true {
-                                        //Make current user just a moderator.
-                                        let _res = diesel::update(club_members)
-                                            .filter(club_id.eq(id))
-                                            .filter(user_id.eq(&user_id_copy))
-                                            .set(is_moderator.eq("true"))
-                                            .execute(conn);
-                                        //Appoint new user to head moderator.
-                                        let _res = diesel::update(club_members)
-                                            .filter(club_id.eq(id))
-                                            .filter(user_id.eq(&request.user_id))
-                                            .set(is_moderator.eq("head"))
-                                            .execute(conn);
-                                    }else{
+                            let moderators_before = ClubMember::moderators(conn, &id)?;
+                            if request.appoint_to_head {
+                                ClubMember::hand_over(conn, &id, fetched_user)?;
+                            } else {
+                                match fetched_user.get_membership_status(conn, &id)? {
+                                    MembershipStatus::Moderator(true) => {
+                                        return Err(ApiError::BadRequest("You already are a head moderator.".to_owned()))
+                                    },
+                                    MembershipStatus::Moderator(false) => {
+                                        return Err(ApiError::BadRequest("User already is a moderator.".to_owned()))
+                                    },
+                                    MembershipStatus::Member => {
                                         //Appoint new user to moderator.
-                                        let _res = diesel::update(club_members)
+                                        diesel::update(club_members)
                                             .filter(club_id.eq(id))
                                             .filter(user_id.eq(&request.user_id))
                                             .set(is_moderator.eq("true"))
-                                            .execute(conn);
+                                            .execute(conn)?;
+                                    },
+                                    MembershipStatus::Unassociated => {
+                                        return Err(ApiError::BadRequest("User is not a member.".to_owned()))
                                     }
-                                },
-                                _ => {
-                                    return Err(status::Custom(Status::BadRequest, Some(Json(JsonError {error: "User is not a member.".to_owned()}))))
                                 }
                             }
-                            let club = clubs.find(id).get_result::<Club>(conn).unwrap();
-                            Ok(status::Accepted(Some(Json(club.to_club_details(&conn, &user_id_copy)))))
+                            let moderators_after = ClubMember::moderators(conn, &id)?;
+                            AuditEntry::record_club(conn, user_id_copy, &ip, ACTION_CLUB_APPOINT, id, Some(&moderators_before), Some(&moderators_after))?;
+                            let club = Club::get_active(conn, &id).ok_or(ApiError::NotFound("The club you are trying to appoint moderators for does not exist.".to_owned()))?;
+                            Ok(status::Accepted(Some(Json(club.to_club_details(&conn, &user_id_copy)?))))
                         }else{
-                            Err(status::Custom(Status::BadRequest, Some(Json(JsonError {error: "User does not exist.".to_owned()}))))
+                            Err(ApiError::NotFound("User does not exist.".to_owned()))
                         }
                     }else{
-                        Err(status::Custom(Status::BadRequest, Some(Json(JsonError {error: "Only the head moderator can appoint new moderators.".to_owned()}))))
+                        Err(ApiError::BadRequest("Only the head moderator can appoint new moderators.".to_owned()))
                     }
                 }else{
-                    Err(status::Custom(Status::BadRequest, Some(Json(JsonError {error: "The club you are trying to leave does not exist.".to_owned()}))))
+                    Err(ApiError::NotFound("The club you are trying to appoint moderators for does not exist.".to_owned()))
                 }
             }).await;
             result
         },
         _ => {
-            Err(status::Custom(Status::Forbidden, Some(Json(JsonError {error: "User is not a head moderator for this club.".to_owned()}))))
+            Err(ApiError::Forbidden("User is not a head moderator for this club.".to_owned()))
         }
     }
 }
 
-#[put("/clubs/<id>/logo", format = "image/png", data = "<file>")]
-pub async fn upload(user: User, db: Db, id: i32, mut file: Capped<TempFile<'_>>) -> std::result::Result<status::Accepted<()>, status::Custom<Option<Json<JsonError>>>> {
-    println!("MAde it this far");
-    match user.get_membership_status_async(&db, &id).await {
-        MembershipStatus::Moderator(is_head) => {
-            if is_head{
-                if file.is_complete() {
-                    match file.move_copy_to(format!("uploads/{}.png",id)).await {
-                        Ok(_) => {return Ok(status::Accepted(None))},
-                        Err(e) => {println!("Error encountered while trying to persist a file, {:?}", e); return Err(status::Custom(Status::BadRequest, None))}
+/*
+Replaces the club's logo. PNG, JPEG and WebP are accepted, see
+services::logos for what happens to the upload.
+*/
+#[put("/clubs/<id>/logo", data = "<file>")]
+pub async fn upload(user: User, db: Db, id: i32, ip: ClientIp, content_type: Option<&ContentType>, limits: &Limits, storage: &State<Storage>, file: Data<'_>) -> Result<Json<ClubDetails>> {
+    let user_id = user.id.clone();
+    match user.get_membership_status_async(&db, &id).await? {
+        MembershipStatus::Moderator(true) => (),
+        _ => return Err(ApiError::Forbidden("You are not allowed to do that.".to_owned()))
+    }
+    //Nothing is decoded or stored for a club that is gone, the final update checks again.
+    if db.run(move |conn| Club::get_active(conn, &id)).await.is_none() {
+        return Err(ApiError::NotFound("The club you are trying to access does not exist.".to_owned()))
+    }
+
+    let limit = limits.get("logo").unwrap_or(logos::MAX_UPLOAD_BYTES.bytes());
+    let bytes = file.open(limit).into_bytes().await.map_err(|e| {
+        eprintln!("Failed to read a logo upload, {:?}", e);
+        ApiError::BadRequest("The upload could not be read.".to_owned())
+    })?;
+    if !bytes.is_complete() {
+        return Err(ApiError::TooLarge(format!("Logos can be at most {}.", limit)))
+    }
+
+    let declared = content_type.cloned();
+    let image = rocket::tokio::task::spawn_blocking(move || {
+        logos::decode(&bytes, declared.as_ref())
+    }).await.map_err(|_| ApiError::Internal)??;
+
+    let key = logos::store(storage, image).await.map_err(|e| {
+        eprintln!("Error encountered while trying to persist a logo, {:?}", e);
+        ApiError::Internal
+    })?;
+
+    db.run(move |conn| {
+        let before = Club::get_active(conn, &id)
+            .ok_or(ApiError::NotFound("The club you are trying to access does not exist.".to_owned()))?;
+        let club = logos::set_logo(conn, &id, &key)?;
+        AuditEntry::record_club(conn, user_id, &ip, ACTION_CLUB_LOGO, id, Some(&before), Some(&club))?;
+        Ok(Json(club.to_club_details(&conn, &user_id)?))
+    }).await
+}
+
+#[derive(Deserialize)]
+pub struct ClubCategoryDTO {
+    pub category_id: Option<i32>,
+}
+
+#[put("/clubs/<id>/category", data = "<request>")]
+pub async fn set_category(user: User, db: Db, id: i32, request: Json<ClubCategoryDTO>) -> Result<Json<ClubDetails>> {
+    use crate::schema::clubs::dsl::{clubs, category_id, deleted_at};
+
+    let user_id = user.id.clone();
+    match user.get_membership_status_async(&db, &id).await? {
+        MembershipStatus::Moderator(_) => {
+            db.run(move |conn| {
+                if let Some(requested) = request.category_id {
+                    if Category::get_by_id(conn, &requested).is_none() {
+                        return Err(ApiError::NotFound("That category does not exist.".to_owned()))
                     }
+                }
+
+                let update = diesel::update(clubs.find(id))
+                    .filter(deleted_at.is_null())
+                    .set(category_id.eq(request.category_id))
+                    .get_result::<Club>(conn);
+
+                if let Ok(update) = update {
+                    Ok(Json(update.to_club_details(&conn, &user_id)?))
                 } else {
-                    return Err(status::Custom(Status::BadRequest, None))
+                    Err(ApiError::NotFound("The club you are trying to access does not exist.".to_owned()))
                 }
-            }else {
-                return Err(status::Custom(Status::Forbidden, None))
-            }
+            }).await
+        },
+        _ => {
+            Err(ApiError::Forbidden("Only moderators can change the category of a club.".to_owned()))
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ClubTagsDTO {
+    pub tags: Vec<String>,
+}
+
+//Replaces all of the club's tags.
+#[put("/clubs/<id>/tags", data = "<request>")]
+pub async fn set_tags(user: User, db: Db, id: i32, request: Json<ClubTagsDTO>) -> Result<Json<ClubDetails>> {
+    use crate::models::tags_md::MAX_TAGS_PER_CLUB;
+
+    let mut normalized = Vec::new();
+    for tag in request.tags.iter() {
+        match Tag::normalize(tag) {
+            Ok(tag) => if !normalized.contains(&tag) {normalized.push(tag)},
+            Err(e) => return Err(ApiError::BadRequest(e))
+        }
+    }
+
+    if normalized.len() > MAX_TAGS_PER_CLUB {
+        return Err(ApiError::BadRequest(format!("Clubs can have at most {} tags.", MAX_TAGS_PER_CLUB)))
+    }
+
+    let user_id = user.id.clone();
+    match user.get_membership_status_async(&db, &id).await? {
+        MembershipStatus::Moderator(_) => {
+            db.run(move |conn| {
+                match Club::get_active(conn, &id) {
+                    Some(club) => {
+                        Tag::set_for_club(conn, &id, &normalized)?;
+                        Ok(Json(club.to_club_details(&conn, &user_id)?))
+                    },
+                    None => Err(ApiError::NotFound("The club you are trying to access does not exist.".to_owned()))
+                }
+            }).await
+        },
+        _ => {
+            Err(ApiError::Forbidden("Only moderators can change the tags of a club.".to_owned()))
+        }
+    }
+}
+
+//Shared by the admin and head moderator routes below.
+fn reactivate_club(conn: &PgConnection, id: i32, user_id: i32, ip: &ClientIp, lifecycle: &LifecycleConfig) -> Result<Json<ClubDetails>> {
+    conn.transaction(|| {
+        let before = Club::get_active(conn, &id)
+            .ok_or(ApiError::NotFound("The club you are trying to reactivate does not exist.".to_owned()))?;
+        let club = Club::reactivate(conn, &id, lifecycle)?;
+        AuditEntry::record_club(conn, user_id, ip, ACTION_CLUB_REACTIVATE, id, Some(&before), Some(&club))?;
+        Ok(Json(club.to_club_details(&conn, &user_id)?))
+    })
+}
+
+#[put("/clubs/<id>/reactivate", rank=1)]
+pub async fn reactivate_admin(admin: Admin, db: Db, id: i32, ip: ClientIp, lifecycle: &State<LifecycleConfig>) -> Result<Json<ClubDetails>> {
+    let user_id = admin.0.id.clone();
+    let lifecycle = *lifecycle.inner();
+    db.run(move |conn| {
+        reactivate_club(conn, id, user_id, &ip, &lifecycle)
+    }).await
+}
+
+#[put("/clubs/<id>/reactivate", rank=2)]
+pub async fn reactivate_user(user: User, db: Db, id: i32, ip: ClientIp, lifecycle: &State<LifecycleConfig>) -> Result<Json<ClubDetails>> {
+    let user_id = user.id.clone();
+    let lifecycle = *lifecycle.inner();
+    match user.get_membership_status_async(&db, &id).await? {
+        MembershipStatus::Moderator(true) => {
+            db.run(move |conn| {
+                reactivate_club(conn, id, user_id, &ip, &lifecycle)
+            }).await
+        },
+        _ => {
+            Err(ApiError::Forbidden("Only the head moderator or an admin can reactivate a club.".to_owned()))
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ClubLifecycleDTO {
+    //None goes back to the global renewal period.
+    pub renewal_days: Option<i32>,
+}
+
+#[put("/clubs/<id>/lifecycle", data = "<request>")]
+pub async fn set_lifecycle(admin: Admin, db: Db, id: i32, ip: ClientIp, request: Json<ClubLifecycleDTO>) -> Result<Json<ClubDetails>> {
+    use crate::schema::clubs::dsl::{clubs, renewal_days, deleted_at};
+
+    if let Some(days) = request.renewal_days {
+        if days < 1 || days > 366 {
+            return Err(ApiError::BadRequest("The renewal period has to be between 1 and 366 days.".to_owned()))
+        }
+    }
+
+    let user_id = admin.0.id.clone();
+    db.run(move |conn| conn.transaction(|| {
+        let before = Club::get_active(conn, &id)
+            .ok_or(ApiError::NotFound("The club you are trying to access does not exist.".to_owned()))?;
+        let club = diesel::update(clubs.find(id))
+            .filter(deleted_at.is_null())
+            .set(renewal_days.eq(request.renewal_days))
+            .get_result::<Club>(conn)?;
+        AuditEntry::record_club(conn, user_id, &ip, ACTION_CLUB_LIFECYCLE, id, Some(&before), Some(&club))?;
+        Ok(Json(club.to_club_details(&conn, &user_id)?))
+    })).await
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewDecision {
+    Approve,
+    Reject,
+    RequestChanges,
+}
+
+#[derive(Deserialize)]
+pub struct ClubReviewDTO {
+    pub decision: ReviewDecision,
+    //Required unless the club is approved, shown to the head moderator.
+    pub reason: Option<String>,
+}
+
+/*
+An admin's decision on a club in the review queue. Only approved clubs
+are listed, clubs with changes requested go back into the queue once
+the head moderator edits them.
+*/
+#[put("/clubs/<id>/review", data = "<request>")]
+pub async fn review(admin: Admin, db: Db, id: i32, ip: ClientIp, request: Json<ClubReviewDTO>) -> Result<Json<ClubDetails>> {
+    use crate::schema::clubs::dsl::{clubs, approval_status, review_note, reviewed_at, deleted_at};
+
+    let reason = request.reason.as_ref().map(|reason| reason.trim().to_owned()).filter(|reason| !reason.is_empty());
+    let (new_status, kind) = match request.decision {
+        ReviewDecision::Approve => ("approved", "club_approved"),
+        ReviewDecision::Reject => ("rejected", "club_rejected"),
+        ReviewDecision::RequestChanges => ("changes_requested", "club_changes_requested"),
+    };
+
+    if new_status != "approved" && reason.is_none() {
+        return Err(ApiError::BadRequest("Give the club a reason when rejecting it or asking for changes.".to_owned()))
+    }
+
+    let user_id = admin.0.id.clone();
+    db.run(move |conn| conn.transaction(|| {
+        let before = Club::get_active(conn, &id)
+            .ok_or(ApiError::NotFound("The club you are trying to review does not exist.".to_owned()))?;
+        let club = diesel::update(clubs.find(id))
+            .filter(deleted_at.is_null())
+            .set((
+                approval_status.eq(new_status),
+                review_note.eq(&reason),
+                reviewed_at.eq(chrono::offset::Utc::now()),
+            ))
+            .get_result::<Club>(conn)?;
+        AuditEntry::record_club(conn, user_id, &ip, ACTION_CLUB_REVIEW, id, Some(&before), Some(&club))?;
+
+        let message = match (new_status, &reason) {
+            ("approved", _) => format!("{} has been approved and is now listed.", club.name),
+            ("rejected", Some(reason)) => format!("{} was not approved: {}", club.name, reason),
+            (_, Some(reason)) => format!("{} needs changes before it can be approved: {}", club.name, reason),
+            _ => unreachable!("Only approvals come without a reason."),
+        };
+        Notification::notify_head(conn, &id, kind, &message)?;
+        Ok(Json(club.to_club_details(&conn, &user_id)?))
+    })).await
+}
+
+#[derive(Deserialize)]
+pub struct ClubVerifiedDTO {
+    pub is_verified: bool,
+}
+
+//Marks a club as officially recognized.
+#[put("/clubs/<id>/verified", data = "<request>")]
+pub async fn set_verified(admin: Admin, db: Db, id: i32, ip: ClientIp, request: Json<ClubVerifiedDTO>) -> Result<Json<ClubDetails>> {
+    use crate::schema::clubs::dsl::{clubs, is_verified, deleted_at};
+
+    let user_id = admin.0.id.clone();
+    db.run(move |conn| conn.transaction(|| {
+        let before = Club::get_active(conn, &id)
+            .ok_or(ApiError::NotFound("The club you are trying to access does not exist.".to_owned()))?;
+        let club = diesel::update(clubs.find(id))
+            .filter(deleted_at.is_null())
+            .set(is_verified.eq(request.is_verified))
+            .get_result::<Club>(conn)?;
+        AuditEntry::record_club(conn, user_id, &ip, ACTION_CLUB_VERIFY, id, Some(&before), Some(&club))?;
+        Ok(Json(club.to_club_details(&conn, &user_id)?))
+    })).await
+}
+
+/*
+Admin tool, promotes the candidate of every club without a head
+moderator. Responds with the clubs that have no members left to promote,
+those need a head named through /clubs/<id>/head.
+*/
+#[put("/clubs/headless/repair")]
+pub async fn repair_headless(admin: Admin, db: Db, ip: ClientIp) -> Result<Json<Vec<HeadlessClub>>> {
+    let user_id = admin.0.id.clone();
+    let unrepaired = db.run(move |conn| conn.transaction::<_, ApiError, _>(|| {
+        let (new_heads, unrepaired) = ClubMember::repair_headless(conn)?;
+        for head in new_heads.iter() {
+            AuditEntry::record_club(conn, user_id, &ip, ACTION_CLUB_REPAIR_HEAD, head.club_id, None, Some(head))?;
+        }
+        Ok(unrepaired)
+    })).await?;
+
+    Ok(Json(unrepaired))
+}
+
+#[derive(Deserialize)]
+pub struct ClubHeadDTO {
+    //Falls back to the club's candidate, see ClubMember::find_headless.
+    pub user_id: Option<i32>,
+}
+
+//Admin tool, makes the given user the club's head, adding them as a member if needed.
+#[put("/clubs/<id>/head", data = "<request>")]
+pub async fn set_head(admin: Admin, db: Db, id: i32, ip: ClientIp, request: Json<ClubHeadDTO>) -> Result<Json<ClubDetails>> {
+    let user_id = admin.0.id.clone();
+    db.run(move |conn| {
+        let club = Club::get_active(conn, &id)
+            .ok_or(ApiError::NotFound("The club you are trying to access does not exist.".to_owned()))?;
+        let candidate = ClubMember::find_headless(conn)?
+            .into_iter()
+            .find(|headless| headless.club_id == id)
+            .and_then(|headless| headless.candidate_id);
+
+        match request.user_id.or(candidate) {
+            Some(head_id) => {
+                if User::get_by_id(conn, &head_id).is_none() {
+                    return Err(ApiError::NotFound("User does not exist.".to_owned()))
+                }
+                let moderators_before = ClubMember::moderators(conn, &id)?;
+                ClubMember::make_head(conn, &id, &head_id)?;
+                let moderators_after = ClubMember::moderators(conn, &id)?;
+                AuditEntry::record_club(conn, user_id, &ip, ACTION_CLUB_SET_HEAD, id, Some(&moderators_before), Some(&moderators_after))?;
+                let _res = Notification::notify(conn, &head_id, Some(&id), "club_head_repaired", &format!(
+                    "An admin has made you head of {}.", club.name
+                ));
+                Ok(Json(club.to_club_details(&conn, &user_id)?))
+            },
+            None => Err(ApiError::BadRequest("The club has no members, name a user to make head.".to_owned()))
+        }
+    }).await
+}
+
+/*
+Puts an old revision's name and body back. This is recorded as a new
+revision so the history only ever grows. It is an edit like any other,
+so it needs If-Match and the old name has to still be free.
+*/
+#[put("/clubs/<id>/revisions/<revision_id>/revert")]
+pub async fn revert(user: User, db: Db, id: i32, revision_id: i32, if_match: IfMatch, ip: ClientIp) -> std::result::Result<Versioned<Json<ClubDetails>>, ClubEditError> {
+    use crate::schema::clubs::dsl::{clubs, name, body, body_html, version};
+
+    if let IfMatch::Missing = if_match {
+        return Err(ClubEditError::Failed(ApiError::PreconditionRequired("Send the version you are editing in If-Match.".to_owned())))
+    }
+
+    let user_id = user.id.clone();
+    match user.get_membership_status_async(&db, &id).await? {
+        MembershipStatus::Moderator(true) => {
+            db.run(move |conn| conn.transaction::<_, ClubEditError, _>(|| {
+                let current = match Club::get_active(conn, &id) {
+                    Some(current) => current,
+                    None => return Err(ApiError::NotFound("The club you are trying to access does not exist.".to_owned()).into())
+                };
+
+                if !if_match.matches(current.version) {
+                    let current_version = current.version;
+                    return Err(ClubEditError::Stale(Versioned(Json(current.to_club_details(&conn, &user_id)?), current_version)))
+                }
+
+                let revision = match ClubRevision::get_for_club(conn, &id, &revision_id) {
+                    Some(revision) => revision,
+                    None => return Err(ApiError::NotFound("That revision does not exist.".to_owned()).into())
+                };
+                let (club_name, screening) = check_details(conn, id, &revision.name, &revision.body)?;
+
+                let update = diesel::update(clubs.find(id))
+                    .filter(version.eq(current.version))
+                    .set((
+                        name.eq(club_name),
+                        body.eq(&revision.body),
+                        body_html.eq(markdown::render(&revision.body)),
+                    ))
+                    .get_result::<Club>(conn)
+                    .optional()?;
+
+                match update {
+                    Some(club) => {
+                        //Rules added since the revision was written apply to it too.
+                        let club = if screening.needs_review() {
+                            hold_for_review(conn, id, &screening)?
+                        } else {
+                            club
+                        };
+                        ClubRevision::record(conn, &club, Some(&user_id), Some(&revision.id))?;
+                        AuditEntry::record_club(conn, user_id, &ip, ACTION_CLUB_REVERT, id, Some(&current), Some(&club))?;
+                        let club_version = club.version;
+                        Ok(Versioned(Json(club.to_club_details(&conn, &user_id)?), club_version))
+                    },
+                    None => {
+                        let latest = Club::get_active(conn, &id).ok_or(ApiError::NotFound("The club you are trying to access does not exist.".to_owned()))?;
+                        let latest_version = latest.version;
+                        Err(ClubEditError::Stale(Versioned(Json(latest.to_club_details(&conn, &user_id)?), latest_version)))
+                    }
+                }
+            })).await
         },
         _ => {
-            return Err(status::Custom(Status::Forbidden, None))
+            Err(ClubEditError::Failed(ApiError::Forbidden("Only the head moderator can revert the club.".to_owned())))
         }
     }
-}
\ No newline at end of file
+}
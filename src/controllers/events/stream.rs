@@ -0,0 +1,36 @@
+use crate::prelude::*;
+use rocket::response::stream::{Event, EventStream};
+use rocket::tokio::select;
+use rocket::tokio::sync::broadcast::error::RecvError;
+use rocket::Shutdown;
+
+/*
+Tells the browser when club related data changed on any instance, as
+server sent "refresh" events carrying only the table that changed. Ids
+are left out on purpose: pending, private and deleted clubs change too
+and subscribers mustn't learn about clubs they can't see, so the client
+reloads whatever it shows through the regular, permission checked
+endpoints. User rows are left out entirely since the client has no
+business knowing who signed in.
+*/
+#[get("/events")]
+pub async fn stream(_user: User, bus: &State<EventBus>, mut end: Shutdown) -> EventStream![] {
+    let mut receiver = bus.subscribe();
+
+    EventStream! {
+        loop {
+            let event = select! {
+                message = receiver.recv() => match message {
+                    Ok(event) => event,
+                    Err(RecvError::Closed) => break,
+                    Err(RecvError::Lagged(_)) => continue,
+                },
+                _ = &mut end => break,
+            };
+
+            if event.club().is_some() {
+                yield Event::data(event.table).event("refresh");
+            }
+        }
+    }
+}
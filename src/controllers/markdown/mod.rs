@@ -0,0 +1 @@
+pub mod preview;
\ No newline at end of file
@@ -0,0 +1,23 @@
+use crate::prelude::*;
+use crate::services::markdown;
+
+#[derive(Deserialize, Validate)]
+pub struct MarkdownPreviewDTO<'r> {
+    #[validate(length(max = 20000))]
+    pub body: Cow<'r, str>,
+}
+
+#[derive(Serialize)]
+pub struct MarkdownPreview {
+    pub html: String,
+}
+
+//Renders a draft club body exactly like saving it would.
+#[post("/markdown/preview", data = "<request>")]
+pub async fn preview(_user: User, request: Json<MarkdownPreviewDTO<'_>>) -> Result<Json<MarkdownPreview>> {
+    request.validate()?;
+
+    Ok(Json(MarkdownPreview {
+        html: markdown::render(&request.body),
+    }))
+}
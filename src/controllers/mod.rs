@@ -1,2 +1,15 @@
 pub mod clubs;
-pub mod auth;
\ No newline at end of file
+pub mod auth;
+pub mod events;
+pub mod categories;
+pub mod tags;
+pub mod notifications;
+pub mod terms;
+pub mod markdown;
+pub mod assets;
+pub mod attachments;
+pub mod users;
+pub mod account;
+pub mod audit;
+pub mod reports;
+pub mod blocklist;
\ No newline at end of file
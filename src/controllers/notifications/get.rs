@@ -0,0 +1,11 @@
+use crate::prelude::*;
+
+//The user's latest notifications, newest first.
+#[get("/notifications?<unread>")]
+pub async fn get_all(user: User, db: Db, unread: Option<bool>) -> Result<Json<Vec<Notification>>> {
+    let loaded_notifications = db.run(move |conn| {
+        Notification::get_for_user(conn, &user.id, unread.unwrap_or(false))
+    }).await?;
+
+    Ok(Json(loaded_notifications))
+}
@@ -0,0 +1,14 @@
+use crate::prelude::*;
+
+#[put("/notifications/<id>/read")]
+pub async fn read(user: User, db: Db, id: i32) -> Result<status::Accepted<()>> {
+    let updated = db.run(move |conn| {
+        Notification::mark_read(conn, &id, &user.id)
+    }).await?;
+
+    if updated {
+        Ok(status::Accepted(None))
+    } else {
+        Err(ApiError::NotFound("The notification you are trying to read does not exist.".to_owned()))
+    }
+}
@@ -0,0 +1,58 @@
+use crate::prelude::*;
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportReason {
+    OffensiveName,
+    OffensiveDescription,
+    Spam,
+    Harassment,
+    Other,
+}
+
+impl ReportReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportReason::OffensiveName => "offensive_name",
+            ReportReason::OffensiveDescription => "offensive_description",
+            ReportReason::Spam => "spam",
+            ReportReason::Harassment => "harassment",
+            ReportReason::Other => "other",
+        }
+    }
+}
+
+#[derive(Deserialize, Validate)]
+pub struct ClubReportDTO {
+    pub reason: ReportReason,
+    //Required when the reason is other.
+    #[validate(length(max = 2000))]
+    pub details: Option<String>,
+}
+
+/*
+Flags a club for the admins. Reports on the same club are grouped in
+the queue, and a user can only have one open report per club. The
+reporter is notified when an admin acts on it.
+*/
+#[post("/clubs/<id>/report", data = "<request>")]
+pub async fn create(user: User, db: Db, id: i32, request: Json<ClubReportDTO>) -> Result<Json<ClubReport>> {
+    let mut request = request.into_inner();
+    request.details = request.details.map(|details| details.trim().to_owned()).filter(|details| !details.is_empty());
+    request.validate()?;
+
+    if let (ReportReason::Other, None) = (request.reason, &request.details) {
+        return Err(ApiError::Validation(vec![FieldError::new("details", "required", "Tell us what is wrong with the club.")]))
+    }
+
+    let user_id = user.id.clone();
+    db.run(move |conn| {
+        match Club::get_by_id(conn, &id) {
+            Some(club) if club.deleted_at.is_none() => (),
+            _ => return Err(ApiError::NotFound("The club you are trying to report does not exist.".to_owned()))
+        }
+
+        let details = request.details.unwrap_or_default();
+        Ok(Json(ClubReport::file(conn, &id, &user_id, request.reason.as_str(), &details)?))
+    }).await
+}
@@ -0,0 +1,9 @@
+use crate::prelude::*;
+
+//The moderation queue, open reports grouped by club. See update::resolve for acting on them.
+#[get("/reports")]
+pub async fn get_queue(_admin: Admin, db: Db) -> Result<Json<Vec<ReportGroup>>> {
+    db.run(move |conn| {
+        Ok(Json(ClubReport::queue(conn)?))
+    }).await
+}
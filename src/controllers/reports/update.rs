@@ -0,0 +1,119 @@
+use crate::prelude::*;
+use crate::controllers::clubs::delete::soft_delete;
+use crate::models::audit_log_md::{ACTION_CLUB_HIDE, ACTION_CLUB_REPORTS_RESOLVE, TARGET_CLUB};
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportOutcome {
+    Dismiss,
+    Warn,
+    Hide,
+    Delete,
+}
+
+#[derive(Deserialize)]
+pub struct ReportResolutionDTO {
+    pub outcome: ReportOutcome,
+    //Required when warning the moderators or hiding the club, shown to the moderators.
+    pub note: Option<String>,
+}
+
+impl ReportOutcome {
+    //What the reports are closed as.
+    fn status(&self) -> &'static str {
+        match self {
+            ReportOutcome::Dismiss => "dismissed",
+            ReportOutcome::Warn => "warned",
+            ReportOutcome::Hide => "hidden",
+            ReportOutcome::Delete => "deleted",
+        }
+    }
+
+    //What the reporters are told, the note is between the admins and the moderators.
+    fn reporter_message(&self, club_name: &str) -> String {
+        match self {
+            ReportOutcome::Dismiss => format!("Thanks for reporting {}. An admin looked into it and found nothing against the rules.", club_name),
+            ReportOutcome::Warn => format!("Thanks for reporting {}. An admin has warned its moderators.", club_name),
+            ReportOutcome::Hide => format!("Thanks for reporting {}. It has been hidden until an admin has reviewed it.", club_name),
+            ReportOutcome::Delete => format!("Thanks for reporting {}. It has been removed.", club_name),
+        }
+    }
+}
+
+/*
+Acts on every open report on the club at once and lets each reporter
+know. Hiding sends the club back to the review queue, it is listed
+again once an admin approves it through /clubs/<id>/review. Deleting is
+the same soft delete as DELETE /clubs/<id>.
+*/
+#[put("/reports/clubs/<id>", data = "<request>")]
+pub async fn resolve(admin: Admin, db: Db, id: i32, ip: ClientIp, lifecycle: &State<LifecycleConfig>, request: Json<ReportResolutionDTO>) -> Result<Json<Vec<ClubReport>>> {
+    use crate::schema::clubs::dsl::{clubs, approval_status, review_note, reviewed_at};
+
+    let outcome = request.outcome;
+    let note = request.note.as_ref().map(|note| note.trim().to_owned()).filter(|note| !note.is_empty());
+    let note_required = match outcome {
+        ReportOutcome::Warn | ReportOutcome::Hide => true,
+        ReportOutcome::Dismiss | ReportOutcome::Delete => false,
+    };
+    if note_required && note.is_none() {
+        return Err(ApiError::BadRequest("Tell the club's moderators why when warning them or hiding the club.".to_owned()))
+    }
+
+    let admin_id = admin.0.id.clone();
+    let lifecycle = *lifecycle.inner();
+    db.run(move |conn| conn.transaction(|| {
+        let club = Club::get_by_id(conn, &id)
+            .ok_or(ApiError::NotFound("The club you are trying to access does not exist.".to_owned()))?;
+        let open = ClubReport::open_for_club(conn, &id)?;
+        if open.is_empty() {
+            return Err(ApiError::NotFound("There are no open reports on this club.".to_owned()))
+        }
+
+        let note_text = note.clone().unwrap_or_default();
+        match outcome {
+            ReportOutcome::Dismiss => (),
+            ReportOutcome::Warn => {
+                Notification::notify_moderators(conn, &id, "club_warned", &format!(
+                    "An admin has warned {} after it was reported: {}", club.name, note_text
+                ))?;
+            },
+            ReportOutcome::Hide => {
+                let hidden = diesel::update(clubs.find(id))
+                    .set((
+                        approval_status.eq("pending"),
+                        review_note.eq(&note),
+                        reviewed_at.eq(chrono::offset::Utc::now()),
+                    ))
+                    .get_result::<Club>(conn)?;
+                AuditEntry::record_club(conn, admin_id, &ip, ACTION_CLUB_HIDE, id, Some(&club), Some(&hidden))?;
+                Notification::notify_moderators(conn, &id, "club_hidden", &format!(
+                    "{} has been hidden after it was reported and will be reviewed by an admin: {}", club.name, note_text
+                ))?;
+            },
+            ReportOutcome::Delete => {
+                soft_delete(conn, id, admin_id, &ip, &lifecycle)?;
+            },
+        }
+
+        let resolved = ClubReport::resolve(conn, &id, &admin_id, outcome.status(), note.as_deref())?;
+        //Not filed under the club, its head moderator sees the club's log and mustn't learn who reported it.
+        AuditEntry::record(conn, &NewAuditEntry {
+            actor_id: Some(admin_id),
+            action: ACTION_CLUB_REPORTS_RESOLVE,
+            target_type: TARGET_CLUB,
+            target_id: Some(id),
+            club_id: None,
+            before_snapshot: AuditEntry::snapshot(&open),
+            after_snapshot: AuditEntry::snapshot(&resolved),
+            ip: ip.0.clone(),
+        })?;
+
+        let message = outcome.reporter_message(&club.name);
+        for reporter_id in resolved.iter().filter_map(|report| report.reporter_id) {
+            Notification::notify(conn, &reporter_id, Some(&id), "report_resolved", &message)?;
+        }
+
+        Ok(Json(resolved))
+    })).await
+}
@@ -0,0 +1,14 @@
+use crate::prelude::*;
+
+pub const AUTOCOMPLETE_LIMIT: i64 = 10;
+
+//Suggests existing tags for the editor, most used first.
+#[get("/tags/autocomplete?<prefix>")]
+pub async fn autocomplete(db: Db, prefix: String) -> Result<Json<Vec<FacetCount>>> {
+    let prefix = prefix.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    let suggestions = db.run(move |conn| {
+        Tag::autocomplete(conn, &prefix, AUTOCOMPLETE_LIMIT)
+    }).await?;
+
+    Ok(Json(suggestions))
+}
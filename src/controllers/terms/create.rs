@@ -0,0 +1,36 @@
+use crate::prelude::*;
+
+#[derive(Deserialize)]
+pub struct NewTermDTO<'r> {
+    pub name: Cow<'r, str>,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+}
+
+#[post("/terms", data = "<term>")]
+pub async fn create(_admin: Admin, db: Db, term: Json<NewTermDTO<'_>>) -> Result<Json<Term>> {
+    use crate::schema::terms::dsl::{terms};
+
+    let term_name = term.name.trim().to_owned();
+    if term_name.is_empty() {
+        return Err(ApiError::BadRequest("Terms need a name.".to_owned()))
+    }
+
+    if term.end_date <= term.start_date {
+        return Err(ApiError::BadRequest("A term has to end after it starts.".to_owned()))
+    }
+
+    let start_date = term.start_date;
+    let end_date = term.end_date;
+    let created = db.run(move |conn| {
+        insert_into(terms)
+            .values(&NewTerm {
+                name: &term_name,
+                start_date: &start_date,
+                end_date: &end_date,
+            })
+            .get_result::<Term>(conn)
+    }).await?;
+
+    Ok(Json(created))
+}
@@ -0,0 +1,20 @@
+use crate::prelude::*;
+
+#[get("/terms")]
+pub async fn get_all(db: Db) -> Result<Json<Vec<Term>>> {
+    let loaded_terms = db.run(move |conn| {
+        Term::get_all(conn)
+    }).await?;
+
+    Ok(Json(loaded_terms))
+}
+
+//The registrations admins have to review, pass status=pending to only get the open ones.
+#[get("/terms/<id>/registrations?<status>")]
+pub async fn get_registrations(_admin: Admin, db: Db, id: i32, status: Option<String>) -> Result<Json<Vec<ClubRegistration>>> {
+    let loaded_registrations = db.run(move |conn| {
+        ClubRegistration::get_for_term(conn, &id, status.as_deref())
+    }).await?;
+
+    Ok(Json(loaded_registrations))
+}
@@ -0,0 +1,3 @@
+pub mod get;
+pub mod create;
+pub mod update;
\ No newline at end of file
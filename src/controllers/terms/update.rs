@@ -0,0 +1,55 @@
+use crate::prelude::*;
+use crate::models::terms_md::{REGISTRATION_REGISTERED, REGISTRATION_LAPSED};
+use crate::models::audit_log_md::{ACTION_TERM_REGISTRATION_DECIDE, TARGET_TERM};
+
+#[derive(Deserialize)]
+pub struct RegistrationDecisionDTO {
+    pub approve: bool,
+}
+
+/*
+Approves or rejects a club's registration for a term. Approved clubs
+stay listed until the term ends, rejected ones lapse. The club's
+moderators are told either way.
+*/
+#[put("/terms/<id>/registrations/<club_id>", data = "<request>")]
+pub async fn decide(admin: Admin, db: Db, id: i32, club_id: i32, ip: ClientIp, request: Json<RegistrationDecisionDTO>) -> Result<Json<ClubRegistration>> {
+    let decision = if request.approve {REGISTRATION_REGISTERED} else {REGISTRATION_LAPSED};
+
+    let admin_id = admin.0.id.clone();
+    db.run(move |conn| conn.transaction(|| {
+        let term = match Term::get_by_id(conn, &id) {
+            Some(term) => term,
+            None => return Err(ApiError::NotFound("That term does not exist.".to_owned()))
+        };
+
+        let club = match Club::get_by_id(conn, &club_id) {
+            Some(club) => club,
+            None => return Err(ApiError::NotFound("The club you are trying to access does not exist.".to_owned()))
+        };
+
+        let before = match ClubRegistration::get_for_club(conn, &club_id)?.into_iter().find(|registration| registration.term_id == id) {
+            Some(registration) => registration,
+            None => return Err(ApiError::BadRequest("This club has not submitted a registration for that term.".to_owned()))
+        };
+        let registration = ClubRegistration::decide(conn, &club_id, &term, decision)?;
+        AuditEntry::record(conn, &NewAuditEntry {
+            actor_id: Some(admin_id),
+            action: ACTION_TERM_REGISTRATION_DECIDE,
+            target_type: TARGET_TERM,
+            target_id: Some(id),
+            club_id: Some(club_id),
+            before_snapshot: AuditEntry::snapshot(&before),
+            after_snapshot: AuditEntry::snapshot(&registration),
+            ip: ip.0.clone(),
+        })?;
+
+        let message = if request.approve {
+            format!("{} is registered for {}.", club.name, term.name)
+        } else {
+            format!("The registration of {} for {} was not approved.", club.name, term.name)
+        };
+        Notification::notify_moderators(conn, &club_id, "club_registration", &message)?;
+        Ok(Json(registration))
+    })).await
+}
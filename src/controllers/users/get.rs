@@ -0,0 +1,25 @@
+use crate::prelude::*;
+
+/*
+A user's profile. Every field but the name and picture is left out
+unless the user lets the caller see it, guests only get what is shown
+to everyone and never the email.
+*/
+#[get("/users/<id>/profile")]
+pub async fn get_profile(user: Option<User>, db: Db, id: i32) -> Result<Json<UserProfile>> {
+    let viewer = user.map(|user| user.id);
+
+    db.run(move |conn| {
+        let profile = User::get_by_id(conn, &id)
+            .ok_or(ApiError::NotFound("That user does not exist.".to_owned()))?
+            .to_profile(conn, viewer)?;
+
+        Ok(Json(profile))
+    }).await
+}
+
+//The caller's own profile with every field and its visibility, for their settings page.
+#[get("/users/me/profile")]
+pub async fn get_settings(user: User) -> Json<ProfileSettings> {
+    Json(user.to_profile_settings())
+}
@@ -0,0 +1,117 @@
+use crate::prelude::*;
+use crate::validation::validate_visibility;
+use crate::models::audit_log_md::{ACTION_USER_ADMIN, TARGET_USER};
+
+//Replaces the whole profile, blank text fields are cleared.
+#[derive(Deserialize, Validate)]
+pub struct ProfileUpdateDTO {
+    #[validate(length(max = 64))]
+    pub display_name: Option<String>,
+    #[validate(length(max = 32))]
+    pub pronouns: Option<String>,
+    #[validate(length(max = 2000))]
+    pub bio: String,
+    #[validate(length(max = 100))]
+    pub major: Option<String>,
+    #[validate(range(min = 1900, max = 2200))]
+    pub graduation_year: Option<i32>,
+    #[validate(custom = "validate_visibility")]
+    pub email_visibility: String,
+    #[validate(custom = "validate_visibility")]
+    pub display_name_visibility: String,
+    #[validate(custom = "validate_visibility")]
+    pub pronouns_visibility: String,
+    #[validate(custom = "validate_visibility")]
+    pub bio_visibility: String,
+    #[validate(custom = "validate_visibility")]
+    pub major_visibility: String,
+    #[validate(custom = "validate_visibility")]
+    pub graduation_year_visibility: String,
+}
+
+fn blank_to_none(value: Option<String>) -> Option<String> {
+    value.map(|value| value.trim().to_owned()).filter(|value| !value.is_empty())
+}
+
+#[put("/users/me/profile", data = "<request>")]
+pub async fn update_profile(user: User, db: Db, request: Json<ProfileUpdateDTO>) -> Result<Json<ProfileSettings>> {
+    use crate::schema::users::dsl::{
+        users, display_name, pronouns, bio, major, graduation_year, email_visibility, display_name_visibility,
+        pronouns_visibility, bio_visibility, major_visibility, graduation_year_visibility,
+    };
+
+    let mut request = request.into_inner();
+    request.display_name = blank_to_none(request.display_name);
+    request.pronouns = blank_to_none(request.pronouns);
+    request.major = blank_to_none(request.major);
+    request.bio = request.bio.trim().to_owned();
+    request.validate()?;
+
+    let user_id = user.id.clone();
+    db.run(move |conn| {
+        let updated = diesel::update(users.find(user_id))
+            .set((
+                display_name.eq(request.display_name),
+                pronouns.eq(request.pronouns),
+                bio.eq(request.bio),
+                major.eq(request.major),
+                graduation_year.eq(request.graduation_year),
+                email_visibility.eq(request.email_visibility),
+                display_name_visibility.eq(request.display_name_visibility),
+                pronouns_visibility.eq(request.pronouns_visibility),
+                bio_visibility.eq(request.bio_visibility),
+                major_visibility.eq(request.major_visibility),
+                graduation_year_visibility.eq(request.graduation_year_visibility),
+            ))
+            .get_result::<User>(conn)?;
+
+        Ok(Json(updated.to_profile_settings()))
+    }).await
+}
+
+#[derive(Deserialize)]
+pub struct UserAdminDTO {
+    pub is_admin: bool,
+}
+
+//All the audit log keeps of the user, the rest of their row is none of its business.
+#[derive(Serialize)]
+struct UserAdminSnapshot {
+    id: i32,
+    is_admin: bool,
+}
+
+//Admin tool, grants or revokes admin rights. The last admin can't step down.
+#[put("/users/<id>/admin", data = "<request>")]
+pub async fn set_admin(admin: Admin, db: Db, id: i32, ip: ClientIp, request: Json<UserAdminDTO>) -> Result<Json<UserDetails>> {
+    use crate::schema::users::dsl::{users, is_admin};
+
+    let admin_id = admin.0.id.clone();
+    db.run(move |conn| conn.transaction(|| {
+        let user = User::get_by_id(conn, &id).ok_or(ApiError::NotFound("User does not exist.".to_owned()))?;
+
+        if user.is_admin && !request.is_admin {
+            let admins = users.filter(is_admin.eq(true)).count().get_result::<i64>(conn)?;
+            if admins <= 1 {
+                return Err(ApiError::Conflict("Saturn needs at least one admin.".to_owned()))
+            }
+        }
+
+        let updated = diesel::update(users.find(id))
+            .set(is_admin.eq(request.is_admin))
+            .get_result::<User>(conn)?;
+
+        AuditEntry::record(conn, &NewAuditEntry {
+            actor_id: Some(admin_id),
+            action: ACTION_USER_ADMIN,
+            target_type: TARGET_USER,
+            target_id: Some(id),
+            club_id: None,
+            before_snapshot: AuditEntry::snapshot(&UserAdminSnapshot { id: user.id, is_admin: user.is_admin }),
+            after_snapshot: AuditEntry::snapshot(&UserAdminSnapshot { id: updated.id, is_admin: updated.is_admin }),
+            ip: ip.0.clone(),
+        })?;
+
+        Ok(Json(updated.to_user_details(conn, Some(admin_id))?))
+    })).await
+}
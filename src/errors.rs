@@ -0,0 +1,171 @@
+use crate::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use rocket::response::{self, Responder};
+
+/*
+Every handler fails with an ApiError. The client gets the same body no
+matter what went wrong:
+
+    {"code": "conflict", "error": "A club with that name already exists.", "fields": []}
+
+`code` is stable and meant for programs, `error` is meant for people
+and may change. `fields` lists what was wrong with each field of the
+request when validation failed.
+*/
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    Validation(Vec<FieldError>),
+    Unauthorized(String),
+    Forbidden(String),
+    NotFound(String),
+    Conflict(String),
+    Gone(String),
+    PreconditionRequired(String),
+    TooLarge(String),
+    UnsupportedMediaType(String),
+    Internal,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FieldError {
+    pub field: String,
+    //Stable like ApiError codes, e.g. too_long or taken.
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Serialize)]
+pub struct ApiErrorBody {
+    pub code: &'static str,
+    pub error: String,
+    pub fields: Vec<FieldError>,
+}
+
+impl FieldError {
+    pub fn new(field: &str, code: &str, message: &str) -> Self {
+        Self {
+            field: field.to_owned(),
+            code: code.to_owned(),
+            message: message.to_owned(),
+        }
+    }
+}
+
+impl ApiError {
+    pub fn status(&self) -> Status {
+        match self {
+            ApiError::BadRequest(_) => Status::BadRequest,
+            ApiError::Validation(_) => Status::UnprocessableEntity,
+            ApiError::Unauthorized(_) => Status::Unauthorized,
+            ApiError::Forbidden(_) => Status::Forbidden,
+            ApiError::NotFound(_) => Status::NotFound,
+            ApiError::Conflict(_) => Status::Conflict,
+            ApiError::Gone(_) => Status::Gone,
+            ApiError::PreconditionRequired(_) => Status::PreconditionRequired,
+            ApiError::TooLarge(_) => Status::PayloadTooLarge,
+            ApiError::UnsupportedMediaType(_) => Status::UnsupportedMediaType,
+            ApiError::Internal => Status::InternalServerError,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::Validation(_) => "validation_failed",
+            ApiError::Unauthorized(_) => "unauthorized",
+            ApiError::Forbidden(_) => "forbidden",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::Gone(_) => "gone",
+            ApiError::PreconditionRequired(_) => "precondition_required",
+            ApiError::TooLarge(_) => "too_large",
+            ApiError::UnsupportedMediaType(_) => "unsupported_media_type",
+            ApiError::Internal => "internal",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            ApiError::BadRequest(message) |
+            ApiError::Unauthorized(message) |
+            ApiError::Forbidden(message) |
+            ApiError::NotFound(message) |
+            ApiError::Conflict(message) |
+            ApiError::Gone(message) |
+            ApiError::PreconditionRequired(message) |
+            ApiError::TooLarge(message) |
+            ApiError::UnsupportedMediaType(message) => message.clone(),
+            ApiError::Validation(_) => "Some fields are invalid.".to_owned(),
+            ApiError::Internal => "Something went wrong on our end.".to_owned(),
+        }
+    }
+
+    pub fn body(&self) -> ApiErrorBody {
+        ApiErrorBody {
+            code: self.code(),
+            error: self.message(),
+            fields: match self {
+                ApiError::Validation(fields) => fields.clone(),
+                _ => Vec::new(),
+            },
+        }
+    }
+
+    //The error for a status rocket produced on its own, e.g. from a failed guard.
+    pub fn from_status(status: Status) -> Self {
+        match status.code {
+            400 => ApiError::BadRequest("The request could not be understood.".to_owned()),
+            401 => ApiError::Unauthorized("Sign in to do that.".to_owned()),
+            403 => ApiError::Forbidden("Client not authorized to perform that action.".to_owned()),
+            404 => ApiError::NotFound("There is nothing here.".to_owned()),
+            422 => ApiError::Validation(Vec::new()),
+            code if code < 500 => ApiError::BadRequest(status.reason().unwrap_or("Bad request.").to_owned()),
+            _ => ApiError::Internal,
+        }
+    }
+}
+
+impl<'r, 'o: 'r> Responder<'r, 'o> for ApiError {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'o> {
+        status::Custom(self.status(), Json(self.body())).respond_to(req)
+    }
+}
+
+//Names of the unique constraints we know how to explain.
+fn describe_unique_violation(constraint: Option<&str>) -> String {
+    match constraint {
+        Some("clubs_name_key") | Some("clubs_normalized_name_idx") => "A club with that name already exists.",
+        Some("categories_name_key") => "A category with that name already exists.",
+        Some("terms_name_key") => "A term with that name already exists.",
+        Some("users_email_key") => "A user with that email already exists.",
+        Some("one_registration_per_term") => "The club is already registered for that term.",
+        Some("one_membership_per_club") => "That user is already a member of the club.",
+        Some("one_head_per_club") => "The club already has a head moderator.",
+        Some("one_open_report_per_user") => "You already reported this club, an admin will look into it.",
+        _ => "That already exists.",
+    }.to_owned()
+}
+
+impl From<DieselError> for ApiError {
+    fn from(error: DieselError) -> Self {
+        match error {
+            DieselError::NotFound => ApiError::NotFound("The requested resource does not exist.".to_owned()),
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, info) => {
+                ApiError::Conflict(describe_unique_violation(info.constraint_name()))
+            },
+            DieselError::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _) => {
+                ApiError::BadRequest("The request refers to something that does not exist.".to_owned())
+            },
+            error => {
+                eprintln!("Database error, {:?}", error);
+                ApiError::Internal
+            }
+        }
+    }
+}
+
+#[catch(default)]
+pub fn default_catcher(status: Status, _req: &Request<'_>) -> ApiError {
+    ApiError::from_status(status)
+}
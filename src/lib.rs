@@ -1,10 +1,13 @@
 //Meta Modules
 pub mod prelude;
 pub mod schema;
+pub mod errors;
+pub mod validation;
 
 //Domain Modules
 pub mod models;
 pub mod controllers;
+pub mod services;
 
 //Macro Imports
 #[macro_use] extern crate rocket;
@@ -22,7 +25,7 @@ embed_migrations!();
 pub struct Db(diesel::PgConnection);
 
 //Meta Types
-pub type Result<T, E = Debug<diesel::result::Error>> = std::result::Result<T, E>;
+pub type Result<T, E = ApiError> = std::result::Result<T, E>;
 
 //---
 //Meta Functions
@@ -32,14 +35,19 @@ pub fn rocket() -> Rocket<Build>{
     dotenv().ok();
 
     //DB url initialization
+    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
     let db: Map<_, Value> = map! {
-        "url" => env::var("DATABASE_URL").expect("DATABASE_URL must be set").into()
+        "url" => database_url.clone().into()
     };
 
+    //Club lifecycle configuration
+    let lifecycle = LifecycleConfig::from_env();
+
     //Set Limits
     let limits = Limits::default()
-        .limit("file", 0.kibibytes())
-        .limit("file/png", 1.megabytes())
+        .limit("file", 16.mebibytes())
+        .limit("data-form", 17.mebibytes())
+        .limit("logo", 4.mebibytes())
     ;
 
     //Build config
@@ -62,6 +70,10 @@ pub fn rocket() -> Rocket<Build>{
         //Diesel
         .attach(Db::fairing())
         .attach(AdHoc::on_ignite("Diesel Migrations", run_migrations))
+        .attach(AdHoc::on_ignite("Markdown Backfill", render_markdown))
+        //Asset storage
+        .manage(Storage::from_env())
+        .attach(AdHoc::on_ignite("Logo Backfill", process_logos))
         //GoogleKeyState
         .manage(GoogleKeysState {
             lock: Arc::new(RwLock::new(GoogleKeys {
@@ -69,6 +81,28 @@ pub fn rocket() -> Rocket<Build>{
                 expires: chrono::offset::Utc::now(),
             }))
         })
+        //Change notifications
+        .manage(EventBus::new())
+        .attach(AdHoc::on_liftoff("Change Notifications", {
+            let database_url = database_url.clone();
+            |rocket| Box::pin(async move {
+                let bus = rocket.state::<EventBus>().expect("EventBus is managed").clone();
+                rocket::tokio::spawn(bus.listen(database_url));
+            })
+        }))
+        //Club lifecycle
+        .manage(lifecycle)
+        .attach(AdHoc::on_liftoff("Club Lifecycle Job", move |_rocket| Box::pin(async move {
+            services::jobs::spawn_periodic("term registrations", std::time::Duration::from_secs(lifecycle.interval_secs), database_url.clone(), |conn| {
+                ClubRegistration::lapse(conn)
+            });
+            services::jobs::spawn_periodic("club lifecycle", std::time::Duration::from_secs(lifecycle.interval_secs), database_url.clone(), move |conn| {
+                services::lifecycle::sweep(conn, &lifecycle)
+            });
+            services::jobs::spawn_periodic("club purge", std::time::Duration::from_secs(lifecycle.interval_secs), database_url, move |conn| {
+                services::lifecycle::purge(conn, &lifecycle)
+            });
+        })))
         //Startup
         .mount("/api/", routes![
             controllers::clubs::get::get_all,
@@ -82,19 +116,76 @@ pub fn rocket() -> Rocket<Build>{
             controllers::clubs::update::leave,
             controllers::clubs::update::appoint,
             controllers::clubs::update::upload,
+            controllers::clubs::update::set_category,
+            controllers::clubs::update::set_tags,
+            controllers::clubs::update::reactivate_admin,
+            controllers::clubs::update::reactivate_user,
+            controllers::clubs::update::set_lifecycle,
+            controllers::clubs::update::register,
+            controllers::clubs::get::get_registrations,
+            controllers::clubs::get::get_review_queue,
+            controllers::clubs::update::review,
+            controllers::clubs::update::set_verified,
+            controllers::clubs::get::get_headless,
+            controllers::clubs::update::repair_headless,
+            controllers::clubs::update::set_head,
+            controllers::clubs::get::get_revisions,
+            controllers::clubs::get::get_revision_diff,
+            controllers::clubs::update::revert,
             controllers::clubs::delete::delete_admin,
             controllers::clubs::delete::delete_user,
+            controllers::clubs::delete::restore_admin,
+            controllers::clubs::delete::restore_user,
+            controllers::attachments::get::get_all,
+            controllers::attachments::get::get_usage,
+            controllers::attachments::get::get_content,
+            controllers::attachments::create::create,
+            controllers::attachments::update::update,
+            controllers::attachments::update::reorder,
+            controllers::attachments::update::settings,
+            controllers::attachments::update::set_quota,
+            controllers::attachments::delete::delete,
             controllers::auth::login::login,
             controllers::auth::logout::logout,
             controllers::auth::details::details_admin,
             controllers::auth::details::details_user,
+            controllers::events::stream::stream,
+            controllers::categories::get::get_all,
+            controllers::categories::create::create,
+            controllers::categories::delete::delete,
+            controllers::tags::get::autocomplete,
+            controllers::markdown::preview::preview,
+            controllers::notifications::get::get_all,
+            controllers::users::get::get_profile,
+            controllers::users::get::get_settings,
+            controllers::users::update::update_profile,
+            controllers::users::update::set_admin,
+            controllers::account::get::export,
+            controllers::account::delete::delete,
+            controllers::notifications::update::read,
+            controllers::audit::get::get_all,
+            controllers::reports::create::create,
+            controllers::reports::get::get_queue,
+            controllers::reports::update::resolve,
+            controllers::blocklist::get::get_all,
+            controllers::blocklist::get::test_rule,
+            controllers::blocklist::get::get_matches,
+            controllers::blocklist::create::create,
+            controllers::blocklist::update::update,
+            controllers::blocklist::delete::delete,
+            controllers::audit::get::get_for_club,
+            controllers::terms::get::get_all,
+            controllers::terms::get::get_registrations,
+            controllers::terms::create::create,
+            controllers::terms::update::decide,
         ])
         .register("/api", catchers![
-            controllers::auth::details::forbidden_or_details_guest
+            controllers::auth::details::forbidden_or_details_guest,
+            errors::default_catcher,
         ])
         .mount("/.well-known", FileServer::from(relative!(".well-known")))
         .mount("/", FileServer::from(relative!("src/clientapp/dist")).rank(-1))
-        .mount("/assets/clubs", FileServer::from(relative!("uploads")).rank(-2))
+        .mount("/", routes![controllers::assets::get::get])
         .attach(AdHoc::on_response("404 Redirector", |_req, res| Box::pin(async move {
             if res.status() == Status::NotFound {
                 let body = std::fs::read_to_string("src/clientapp/dist/index.html").expect("Index file can't be found.");
@@ -119,6 +210,31 @@ async fn run_migrations(rocket: Rocket<Build>) -> Rocket<Build> {
     rocket
 }
 
+//Clubs from before bodies were rendered on write get their HTML once.
+async fn render_markdown(rocket: Rocket<Build>) -> Rocket<Build> {
+    let conn = Db::get_one(&rocket).await.expect("database connection");
+    match conn.run(|c| services::markdown::backfill(c)).await {
+        Ok(0) => {},
+        Ok(rendered) => println!("Rendered the markdown of {} clubs", rendered),
+        Err(e) => eprintln!("Failed to render club markdown, {:?}", e),
+    }
+
+    rocket
+}
+
+//Logos from before there was asset storage are moved into it once.
+async fn process_logos(rocket: Rocket<Build>) -> Rocket<Build> {
+    let conn = Db::get_one(&rocket).await.expect("database connection");
+    let storage = rocket.state::<Storage>().expect("Storage is managed").clone();
+    match services::logos::backfill(&conn, &storage).await {
+        Ok(0) => {},
+        Ok(processed) => println!("Processed the logos of {} clubs", processed),
+        Err(e) => eprintln!("Failed to process club logos, {:?}", e),
+    }
+
+    rocket
+}
+
 
 
 
@@ -188,6 +304,7 @@ pub struct UserAuthenticator {
     picture: Box<String>,
     first_name: Box<String>,
     last_name: Box<String>,
+    issued_at: DateTime<Utc>,
 }
 
 #[rocket::async_trait]
@@ -200,6 +317,7 @@ impl<'r> FromRequest<'r> for UserAuthenticator {
         let mut picture = String::new();
         let mut first_name = String::new();
         let mut last_name = String::new();
+        let mut issued_at = None;
 
         //Fetch the jwt token from the user's computer and set our validation algorithm.
         let jwt = req.cookies().get_private("user_jwt").map(|cookie| Box::new(cookie.value().to_owned()));
@@ -213,7 +331,7 @@ impl<'r> FromRequest<'r> for UserAuthenticator {
             let jwt = jwt.clone();
             if let Some(jwt) = jwt{
                 match decode::<GoogleClaims>(&jwt, &DecodingKey::from_rsa_components(&key.n, &key.e), &validation) {
-                    Ok(c) => {email = Some(c.claims.email); picture=c.claims.picture; first_name=c.claims.given_name; last_name=c.claims.family_name;},
+                    Ok(c) => {email = Some(c.claims.email); picture=c.claims.picture; first_name=c.claims.given_name; last_name=c.claims.family_name; issued_at=DateTime::from_timestamp(c.claims.iat as i64, 0);},
                     Err(_e) => {}
                 };
             }
@@ -224,15 +342,16 @@ impl<'r> FromRequest<'r> for UserAuthenticator {
         if email.is_none() {req.cookies().remove_private(Cookie::named("user_jwt"));}
 
         //If we were able to decode the email proceed otherwise return forbidden status.
-        match email {
-            None => Outcome::Failure((Status::Forbidden, ())),
-            Some(email) => Outcome::Success(
+        match (email, issued_at) {
+            (Some(email), Some(issued_at)) => Outcome::Success(
                 UserAuthenticator{
                     email: Box::new(email),
                     picture: Box::new(picture),
                     first_name: Box::new(first_name),
-                    last_name: Box::new(last_name)
+                    last_name: Box::new(last_name),
+                    issued_at: issued_at,
                 }),
+            _ => Outcome::Failure((Status::Forbidden, ())),
         }
     }
 }
@@ -243,7 +362,7 @@ impl<'r> FromRequest<'r> for User{
     type Error = ();
 
     async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
-        use crate::schema::users::dsl::{users, email};
+        use crate::schema::users::dsl::{users, email, picture, first_name, last_name};
         let db = try_outcome!(req.guard::<Db>().await);
         let auth = try_outcome!(req.guard::<UserAuthenticator>().await);
 
@@ -252,17 +371,21 @@ impl<'r> FromRequest<'r> for User{
         let used_picture = auth.picture.clone();
         let used_first_name = auth.first_name.clone();
         let used_last_name = auth.last_name.clone();
+        let issued_at = auth.issued_at;
 
         //Search the database users.
-        let user = db.run(move |conn| {
+        let user = match db.run(move |conn| {
             users
                 .filter(email.eq(&auth.email as &str))
                 .first::<User>(conn)
                 .optional()
-        }).await.unwrap();
+        }).await {
+            Ok(user) => user,
+            Err(_) => return Outcome::Failure((Status::InternalServerError, ())),
+        };
 
         //Does user exist? Return it. Otherwise create them.
-        if let Some(mut user) = user {
+        let user = if let Some(mut user) = user {
             //If no changes to email and name just return it.
             if 
                 &user.email == used_email.as_str() &&
@@ -270,21 +393,41 @@ impl<'r> FromRequest<'r> for User{
                 &user.first_name == used_first_name.as_str() &&
                 &user.last_name == used_last_name.as_str()
             {
-                Outcome::Success(user)
+                user
 
-            //Otherwise update the record and return that.
+            //Otherwise update what google tells us and return that, the rest of the row is the user's own.
             } else {
                 user.picture=used_picture.to_string();
                 user.first_name=used_first_name.to_string();
                 user.last_name=used_last_name.to_string();
-            let user = db.run(move |conn| {
-                diesel::update(users).set(&user).get_result(conn)
-            }).await.unwrap();
-                Outcome::Success(user)
+                let updated = db.run(move |conn| {
+                    diesel::update(users.find(user.id))
+                        .set((
+                            picture.eq(&user.picture),
+                            first_name.eq(&user.first_name),
+                            last_name.eq(&user.last_name),
+                        ))
+                        .get_result::<User>(conn)
+                }).await;
+                match updated {
+                    Ok(user) => user,
+                    Err(_) => return Outcome::Failure((Status::InternalServerError, ())),
+                }
             }
         } else {
+            //A token from before the account was deleted doesn't get to bring it back.
+            let checked_email = used_email.clone();
+            match db.run(move |conn| DeletedAccount::revokes(conn, &checked_email, &issued_at)).await {
+                Ok(false) => (),
+                Ok(true) => {
+                    req.cookies().remove_private(Cookie::named("user_jwt"));
+                    return Outcome::Failure((Status::Forbidden, ()))
+                },
+                Err(_) => return Outcome::Failure((Status::InternalServerError, ())),
+            }
+
             //User didn't exist so we're creating them.
-            let user = db.run(move |conn| {
+            let created = db.run(move |conn| {
                 let new_user = NewUser {
                     email: &used_email,
                     picture: &used_picture,
@@ -292,13 +435,29 @@ impl<'r> FromRequest<'r> for User{
                     last_name: &used_last_name,
                     is_admin: &false
                 };
-                insert_into(users)
-                    .values(&new_user.clone())
-                    .get_result(conn)
-                    .optional()
-            }).await.unwrap();
-            Outcome::Success(user.unwrap())
+                match insert_into(users).values(&new_user.clone()).get_result::<User>(conn) {
+                    //A concurrent first request created them already, use that row.
+                    Err(diesel::result::Error::DatabaseError(diesel::result::DatabaseErrorKind::UniqueViolation, _)) => {
+                        users.filter(email.eq(&used_email as &str)).first::<User>(conn)
+                    },
+                    result => result,
+                }
+            }).await;
+            match created {
+                Ok(user) => user,
+                Err(_) => return Outcome::Failure((Status::InternalServerError, ())),
+            }
+        };
+
+        //Copy a new or changed Google picture into storage without holding up the request.
+        if user.needs_avatar_refresh() {
+            let storage = try_outcome!(req.guard::<&State<Storage>>().await).inner().clone();
+            if let Some(conn) = Db::get_one(req.rocket()).await {
+                rocket::tokio::spawn(services::avatars::refresh_user_avatar(conn, storage, user.id, user.picture.clone()));
+            }
         }
+
+        Outcome::Success(user)
     }
 }
 
@@ -322,7 +481,75 @@ impl<'r> FromRequest<'r> for Admin{
     }
 }
 
-#[derive(Serialize)]
-pub struct JsonError{
-    pub error: String,
-}
\ No newline at end of file
+
+/*
+Entity tags are the quoted version of the resource, e.g. "3". Clients
+send them back in If-Match to make sure they are editing what they saw.
+*/
+pub fn etag(version: i32) -> String {
+    format!("\"{}\"", version)
+}
+
+pub enum IfMatch {
+    Missing,
+    Any,
+    Version(i32),
+    //A tag we never hand out, it can't match anything.
+    Unrecognised,
+}
+
+impl IfMatch {
+    pub fn matches(&self, version: i32) -> bool {
+        match self {
+            IfMatch::Any => true,
+            IfMatch::Version(expected) => *expected == version,
+            _ => false,
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for IfMatch {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let header = match req.headers().get_one("If-Match") {
+            Some(header) => header.trim(),
+            None => return Outcome::Success(IfMatch::Missing),
+        };
+
+        if header == "*" {
+            return Outcome::Success(IfMatch::Any)
+        }
+
+        //Weak tags are accepted too, browsers like to add the prefix.
+        let tag = header.trim_start_matches("W/").trim_matches('"');
+        match tag.parse::<i32>() {
+            Ok(version) => Outcome::Success(IfMatch::Version(version)),
+            Err(_) => Outcome::Success(IfMatch::Unrecognised),
+        }
+    }
+}
+
+//The caller's address for the audit log, Rocket reads it from X-Real-IP behind a proxy.
+pub struct ClientIp(pub Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ClientIp {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(ClientIp(req.client_ip().map(|ip| ip.to_string())))
+    }
+}
+
+//Responds with R and the ETag of the version it represents.
+pub struct Versioned<R>(pub R, pub i32);
+
+impl<'r, 'o: 'r, R: rocket::response::Responder<'r, 'o>> rocket::response::Responder<'r, 'o> for Versioned<R> {
+    fn respond_to(self, req: &'r Request<'_>) -> rocket::response::Result<'o> {
+        rocket::Response::build_from(self.0.respond_to(req)?)
+            .raw_header("ETag", etag(self.1))
+            .ok()
+    }
+}
@@ -0,0 +1,268 @@
+use crate::prelude::*;
+use crate::schema::audit_log;
+use rocket::serde::json::serde_json;
+
+/*
+Privileged and moderator actions, who did them, from where and what the
+target looked like before and after. Entries are only ever inserted,
+the database refuses to change or remove them.
+*/
+#[derive(Queryable, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: i32,
+    pub actor_id: Option<i32>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Option<i32>,
+    pub club_id: Option<i32>,
+    pub before_snapshot: Option<String>,
+    pub after_snapshot: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[table_name = "audit_log"]
+pub struct NewAuditEntry<'a> {
+    pub actor_id: Option<i32>,
+    pub action: &'a str,
+    pub target_type: &'a str,
+    pub target_id: Option<i32>,
+    pub club_id: Option<i32>,
+    pub before_snapshot: Option<String>,
+    pub after_snapshot: Option<String>,
+    pub ip: Option<String>,
+}
+
+//An entry as the audit endpoints return it, with the snapshots as JSON rather than text.
+#[derive(Serialize)]
+pub struct AuditEntryDetails {
+    pub id: i32,
+    pub actor_id: Option<i32>,
+    //Missing once the actor deleted their account.
+    pub actor_name: Option<String>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Option<i32>,
+    pub club_id: Option<i32>,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    //Left out of club audit logs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct AuditPage {
+    pub entries: Vec<AuditEntryDetails>,
+    //Pass as cursor to get the next, older, page.
+    pub next_cursor: Option<i32>,
+}
+
+impl AuditPage {
+    //Head moderators see what happened to their club but not where people were when they did it.
+    pub fn without_ips(mut self) -> Self {
+        for entry in self.entries.iter_mut() {
+            entry.ip = None;
+        }
+        self
+    }
+}
+
+#[derive(FromForm, Default)]
+pub struct AuditQuery {
+    //Matched against the action, the target type and both snapshots.
+    pub q: Option<String>,
+    pub actor: Option<i32>,
+    //e.g. club.update
+    pub action: Option<String>,
+    pub target_type: Option<String>,
+    pub target_id: Option<i32>,
+    pub club: Option<i32>,
+    //RFC 3339 timestamps, e.g. 2026-10-19T00:00:00Z
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub cursor: Option<i32>,
+    pub limit: Option<i64>,
+}
+
+//Actions on clubs, the target is the club itself.
+pub const ACTION_CLUB_CREATE: &str = "club.create";
+pub const ACTION_CLUB_UPDATE: &str = "club.update";
+pub const ACTION_CLUB_REVERT: &str = "club.revert";
+pub const ACTION_CLUB_RENEW: &str = "club.renew";
+pub const ACTION_CLUB_APPOINT: &str = "club.appoint";
+pub const ACTION_CLUB_LOGO: &str = "club.logo";
+pub const ACTION_CLUB_DELETE: &str = "club.delete";
+pub const ACTION_CLUB_RESTORE: &str = "club.restore";
+pub const ACTION_CLUB_SET_HEAD: &str = "club.set_head";
+pub const ACTION_CLUB_HIDE: &str = "club.hide";
+pub const ACTION_CLUB_REVIEW: &str = "club.review";
+pub const ACTION_CLUB_VERIFY: &str = "club.verify";
+pub const ACTION_CLUB_LIFECYCLE: &str = "club.lifecycle";
+pub const ACTION_CLUB_REACTIVATE: &str = "club.reactivate";
+pub const ACTION_CLUB_ATTACHMENT_QUOTA: &str = "club.attachment_quota";
+//Promoting a headless club's candidate, the snapshot is the new head's membership.
+pub const ACTION_CLUB_REPAIR_HEAD: &str = "club.repair_head";
+//Closing the club's open reports, the snapshots are the reports.
+pub const ACTION_CLUB_REPORTS_RESOLVE: &str = "club.reports_resolve";
+//Actions on users, the target is the user.
+pub const ACTION_USER_ADMIN: &str = "user.admin";
+//Admins changing the blocklist, the target is the rule.
+pub const ACTION_BLOCKLIST_CREATE: &str = "blocklist.create";
+pub const ACTION_BLOCKLIST_UPDATE: &str = "blocklist.update";
+pub const ACTION_BLOCKLIST_DELETE: &str = "blocklist.delete";
+//Admins deciding on a club's registration, the target is the term and the entry is filed under the club.
+pub const ACTION_TERM_REGISTRATION_DECIDE: &str = "term.registration_decide";
+
+pub const TARGET_CLUB: &str = "club";
+pub const TARGET_USER: &str = "user";
+pub const TARGET_BLOCKLIST_RULE: &str = "blocklist_rule";
+pub const TARGET_TERM: &str = "term";
+
+fn parse_time(field: &str, value: &Option<String>) -> std::result::Result<Option<DateTime<Utc>>, FieldError> {
+    match value.as_deref().map(str::trim).filter(|value| !value.is_empty()) {
+        Some(value) => DateTime::parse_from_rfc3339(value)
+            .map(|time| Some(time.with_timezone(&Utc)))
+            .map_err(|_| FieldError::new(field, "invalid", "Timestamps have to be RFC 3339, like 2026-10-19T00:00:00Z.")),
+        None => Ok(None),
+    }
+}
+
+impl AuditQuery {
+    pub fn search_text(&self) -> Option<String> {
+        self.q.as_ref().map(|q| q.trim().to_owned()).filter(|q| !q.is_empty())
+    }
+
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(crate::models::clubs_md::DEFAULT_PAGE_SIZE).max(1).min(crate::models::clubs_md::MAX_PAGE_SIZE)
+    }
+
+    pub fn range(&self) -> Result<(Option<DateTime<Utc>>, Option<DateTime<Utc>>)> {
+        let mut errors = Vec::new();
+        let since = parse_time("since", &self.since).unwrap_or_else(|e| { errors.push(e); None });
+        let until = parse_time("until", &self.until).unwrap_or_else(|e| { errors.push(e); None });
+
+        if errors.is_empty() {
+            Ok((since, until))
+        } else {
+            Err(ApiError::Validation(errors))
+        }
+    }
+}
+
+impl AuditEntry {
+    //Serialization failing means a bug in a Serialize impl, the entry is still worth keeping without it.
+    pub fn snapshot<T: Serialize>(value: &T) -> Option<String> {
+        serde_json::to_string(value).map_err(|e| eprintln!("Failed to snapshot an audited value, {:?}", e)).ok()
+    }
+
+    pub fn record(conn: &PgConnection, entry: &NewAuditEntry) -> QueryResult<AuditEntry> {
+        use crate::schema::audit_log::dsl::{audit_log};
+
+        insert_into(audit_log)
+            .values(entry)
+            .get_result(conn)
+    }
+
+    //Records an action on a club, with the club's state around it.
+    pub fn record_club<T: Serialize>(conn: &PgConnection, actor_id: i32, ip: &ClientIp, action: &str, club_id: i32, before: Option<&T>, after: Option<&T>) -> QueryResult<AuditEntry> {
+        Self::record(conn, &NewAuditEntry {
+            actor_id: Some(actor_id),
+            action: action,
+            target_type: TARGET_CLUB,
+            target_id: Some(club_id),
+            club_id: Some(club_id),
+            before_snapshot: before.and_then(Self::snapshot),
+            after_snapshot: after.and_then(Self::snapshot),
+            ip: ip.0.clone(),
+        })
+    }
+
+    /*
+    Newest first. Pass a club to only search that club's entries, whatever
+    the query asks for, which is how head moderators see their own club.
+    */
+    pub fn search(conn: &PgConnection, query: &AuditQuery, club: Option<i32>) -> Result<AuditPage> {
+        use crate::schema::audit_log::dsl::{audit_log, id, actor_id, action, target_type, target_id, club_id, before_snapshot, after_snapshot, created_at};
+        use crate::schema::users::dsl::{users, first_name, last_name};
+
+        let (since, until) = query.range()?;
+        let limit = query.limit();
+
+        let mut statement = audit_log
+            .left_join(users)
+            .select((crate::schema::audit_log::all_columns, (first_name, last_name).nullable()))
+            .order(id.desc())
+            .limit(limit + 1)
+            .into_boxed();
+
+        if let Some(text) = query.search_text() {
+            let pattern = format!("%{}%", text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"));
+            statement = statement.filter(
+                action.ilike(pattern.clone())
+                    .or(target_type.ilike(pattern.clone()))
+                    .or(before_snapshot.ilike(pattern.clone()))
+                    .or(after_snapshot.ilike(pattern))
+            );
+        }
+        if let Some(actor) = query.actor {
+            statement = statement.filter(actor_id.eq(actor));
+        }
+        if let Some(req_action) = query.action.as_ref() {
+            statement = statement.filter(action.eq(req_action.clone()));
+        }
+        if let Some(req_target_type) = query.target_type.as_ref() {
+            statement = statement.filter(target_type.eq(req_target_type.clone()));
+        }
+        if let Some(req_target_id) = query.target_id {
+            statement = statement.filter(target_id.eq(req_target_id));
+        }
+        if let Some(req_club) = club.or(query.club) {
+            statement = statement.filter(club_id.eq(req_club));
+        }
+        if let Some(since) = since {
+            statement = statement.filter(created_at.ge(since));
+        }
+        if let Some(until) = until {
+            statement = statement.filter(created_at.lt(until));
+        }
+        if let Some(cursor) = query.cursor {
+            statement = statement.filter(id.lt(cursor));
+        }
+
+        let mut rows = statement.load::<(AuditEntry, Option<(String, String)>)>(conn)?;
+
+        //We fetched one extra row to know whether there is another page.
+        let next_cursor = if rows.len() as i64 > limit {
+            rows.truncate(limit as usize);
+            rows.last().map(|(entry, _)| entry.id)
+        } else {
+            None
+        };
+
+        Ok(AuditPage {
+            entries: rows.into_iter().map(|(entry, actor)| entry.to_details(actor)).collect(),
+            next_cursor: next_cursor,
+        })
+    }
+
+    pub fn to_details(self, actor: Option<(String, String)>) -> AuditEntryDetails {
+        let parse = |snapshot: Option<String>| snapshot.and_then(|snapshot| serde_json::from_str(&snapshot).ok());
+
+        AuditEntryDetails {
+            id: self.id,
+            actor_id: self.actor_id,
+            actor_name: actor.map(|(first, last)| format!("{} {}", first, last)),
+            action: self.action,
+            target_type: self.target_type,
+            target_id: self.target_id,
+            club_id: self.club_id,
+            before: parse(self.before_snapshot),
+            after: parse(self.after_snapshot),
+            ip: self.ip,
+            created_at: self.created_at,
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use crate::prelude::*;
+use crate::schema::blocklist_rules;
+
+#[derive(Queryable, Serialize, Deserialize, Clone)]
+pub struct BlocklistRule {
+    pub id: i32,
+    pub pattern: String,
+    //exact, word or regex, see services::blocklist
+    pub match_type: String,
+    //reject or review
+    pub severity: String,
+    //name, body or both
+    pub applies_to: String,
+    pub enabled: bool,
+    pub note: String,
+    pub created_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[table_name = "blocklist_rules"]
+pub struct NewBlocklistRule<'a> {
+    pub pattern: &'a str,
+    pub match_type: &'a str,
+    pub severity: &'a str,
+    pub applies_to: &'a str,
+    pub enabled: &'a bool,
+    pub note: &'a str,
+    pub created_by: Option<&'a i32>,
+}
+
+pub const MATCH_EXACT: &str = "exact";
+pub const MATCH_WORD: &str = "word";
+pub const MATCH_REGEX: &str = "regex";
+
+pub const SEVERITY_REJECT: &str = "reject";
+pub const SEVERITY_REVIEW: &str = "review";
+
+pub const APPLIES_TO_NAME: &str = "name";
+pub const APPLIES_TO_BODY: &str = "body";
+pub const APPLIES_TO_BOTH: &str = "both";
+
+impl BlocklistRule {
+    pub fn get_all(conn: &PgConnection) -> QueryResult<Vec<BlocklistRule>> {
+        use crate::schema::blocklist_rules::dsl::{blocklist_rules, id};
+
+        blocklist_rules
+            .order(id.asc())
+            .load::<BlocklistRule>(conn)
+    }
+
+    pub fn get_enabled(conn: &PgConnection) -> QueryResult<Vec<BlocklistRule>> {
+        use crate::schema::blocklist_rules::dsl::{blocklist_rules, id, enabled};
+
+        blocklist_rules
+            .filter(enabled.eq(true))
+            .order(id.asc())
+            .load::<BlocklistRule>(conn)
+    }
+
+    pub fn get_by_id(conn: &PgConnection, req_id: &i32) -> Option<BlocklistRule> {
+        use crate::schema::blocklist_rules::dsl::{blocklist_rules};
+
+        blocklist_rules.find(req_id).first(conn).ok()
+    }
+}
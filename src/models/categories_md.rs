@@ -0,0 +1,26 @@
+use crate::prelude::*;
+use crate::schema::categories;
+
+#[derive(Queryable, Serialize, Deserialize, Clone)]
+pub struct Category {
+    pub id: i32,
+    pub name: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "categories"]
+pub struct NewCategory<'a> {
+    pub name: &'a str,
+}
+
+impl Category {
+    pub fn get_all(conn: &PgConnection) -> QueryResult<Vec<Category>> {
+        use crate::schema::categories::dsl::{categories, name};
+        categories.order(name).load::<Category>(conn)
+    }
+
+    pub fn get_by_id(conn: &PgConnection, req_id: &i32) -> Option<Category>{
+        use crate::schema::categories::dsl::{categories, id};
+        categories.filter(id.eq(req_id)).first(conn).ok()
+    }
+}
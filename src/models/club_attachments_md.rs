@@ -0,0 +1,194 @@
+use crate::prelude::*;
+use crate::schema::club_attachments;
+
+pub const KIND_PHOTO: &str = "photo";
+pub const KIND_DOCUMENT: &str = "document";
+pub const VISIBILITY_PUBLIC: &str = "public";
+pub const VISIBILITY_MEMBERS: &str = "members";
+
+//Used for clubs without a quota of their own.
+pub const DEFAULT_QUOTA_MB: i64 = 100;
+
+#[derive(Queryable, Serialize, Deserialize)]
+pub struct ClubAttachment {
+    pub id: i32,
+    pub club_id: i32,
+    pub kind: String,
+    pub storage_key: String,
+    pub thumbnail_key: Option<String>,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub caption: String,
+    pub position: i32,
+    pub visibility: String,
+    pub uploaded_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[table_name = "club_attachments"]
+pub struct NewClubAttachment<'a> {
+    pub club_id: &'a i32,
+    pub kind: &'a str,
+    pub storage_key: &'a str,
+    pub thumbnail_key: Option<&'a str>,
+    pub file_name: &'a str,
+    pub content_type: &'a str,
+    pub size_bytes: &'a i64,
+    pub caption: &'a str,
+    pub position: &'a i32,
+    pub visibility: &'a str,
+    pub uploaded_by: Option<&'a i32>,
+}
+
+//What clients get, storage keys stay on the server so members only files can't be fetched around the permission check.
+#[derive(Serialize, Deserialize)]
+pub struct AttachmentDetails {
+    pub id: i32,
+    pub kind: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub caption: String,
+    pub position: i32,
+    pub visibility: String,
+    pub uploaded_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub url: String,
+    pub thumbnail_url: Option<String>,
+}
+
+//How much of its quota a club has used.
+#[derive(Serialize, Deserialize)]
+pub struct AttachmentUsage {
+    pub used_bytes: i64,
+    pub quota_bytes: i64,
+}
+
+impl ClubAttachment {
+    pub fn get_for_club(conn: &PgConnection, req_club_id: &i32, req_id: &i32) -> Option<ClubAttachment> {
+        use crate::schema::club_attachments::dsl::{club_attachments, club_id, id};
+        club_attachments.filter(club_id.eq(req_club_id)).filter(id.eq(req_id)).first(conn).ok()
+    }
+
+    //The club's photos or documents in order, members only ones left out unless asked for.
+    pub fn list(conn: &PgConnection, req_club_id: &i32, req_kind: &str, include_members_only: bool) -> QueryResult<Vec<ClubAttachment>> {
+        use crate::schema::club_attachments::dsl::{club_attachments, club_id, kind, visibility, position, id};
+
+        let mut query = club_attachments
+            .filter(club_id.eq(req_club_id))
+            .filter(kind.eq(req_kind.to_owned()))
+            .order((position.asc(), id.asc()))
+            .into_boxed();
+
+        if !include_members_only {
+            query = query.filter(visibility.eq(VISIBILITY_PUBLIC));
+        }
+
+        query.load::<ClubAttachment>(conn)
+    }
+
+    pub fn bytes_used(conn: &PgConnection, req_club_id: &i32) -> QueryResult<i64> {
+        use crate::schema::club_attachments::dsl::{club_attachments, club_id};
+
+        club_attachments
+            .filter(club_id.eq(req_club_id))
+            .select(diesel::dsl::sql::<diesel::sql_types::BigInt>("COALESCE(SUM(size_bytes), 0)::BIGINT"))
+            .get_result::<i64>(conn)
+    }
+
+    //Where a new upload goes, after everything else of its kind.
+    pub fn next_position(conn: &PgConnection, req_club_id: &i32, req_kind: &str) -> QueryResult<i32> {
+        use crate::schema::club_attachments::dsl::{club_attachments, club_id, kind, position};
+
+        let last = club_attachments
+            .filter(club_id.eq(req_club_id))
+            .filter(kind.eq(req_kind.to_owned()))
+            .select(diesel::dsl::max(position))
+            .get_result::<Option<i32>>(conn)?;
+
+        Ok(last.map(|last| last + 1).unwrap_or(0))
+    }
+
+    /*
+    Puts the club's photos or documents in the given order. The ids have
+    to be all of them, Ok(false) means they weren't and nothing changed.
+    */
+    pub fn reorder(conn: &PgConnection, req_club_id: &i32, req_kind: &str, ordered_ids: &Vec<i32>) -> QueryResult<bool> {
+        use crate::schema::club_attachments::dsl::{club_attachments, club_id, kind, id, position};
+
+        conn.transaction(|| {
+            let mut current = club_attachments
+                .filter(club_id.eq(req_club_id))
+                .filter(kind.eq(req_kind.to_owned()))
+                .select(id)
+                .load::<i32>(conn)?;
+            let mut requested = ordered_ids.clone();
+            current.sort();
+            requested.sort();
+            if current != requested {
+                return Ok(false)
+            }
+
+            for (index, attachment_id) in ordered_ids.iter().enumerate() {
+                diesel::update(club_attachments.find(attachment_id))
+                    .set(position.eq(index as i32))
+                    .execute(conn)?;
+            }
+
+            Ok(true)
+        })
+    }
+
+    //Whether any other attachment uses the same stored object, keys are content addressed so uploads can share them.
+    pub fn is_key_shared(&self, conn: &PgConnection) -> QueryResult<bool> {
+        use crate::schema::club_attachments::dsl::{club_attachments, id, storage_key};
+
+        let others = club_attachments
+            .filter(storage_key.eq(&self.storage_key))
+            .filter(id.ne(self.id))
+            .count()
+            .get_result::<i64>(conn)?;
+
+        Ok(others > 0)
+    }
+
+    pub fn to_details(self) -> AttachmentDetails {
+        let url = format!("/api/clubs/{}/attachments/{}/content", self.club_id, self.id);
+
+        AttachmentDetails {
+            thumbnail_url: self.thumbnail_key.as_ref().map(|_| format!("{}?thumbnail=true", url)),
+            url: url,
+            id: self.id,
+            kind: self.kind,
+            file_name: self.file_name,
+            content_type: self.content_type,
+            size_bytes: self.size_bytes,
+            caption: self.caption,
+            position: self.position,
+            visibility: self.visibility,
+            uploaded_by: self.uploaded_by,
+            created_at: self.created_at,
+        }
+    }
+}
+
+impl Club {
+    pub fn storage_quota(&self) -> i64 {
+        self.storage_quota_bytes
+            .unwrap_or_else(|| crate::services::lifecycle::env_or("CLUB_STORAGE_QUOTA_MB", DEFAULT_QUOTA_MB) * 1024 * 1024)
+    }
+
+    //Whether someone with the given membership may upload photos or documents.
+    pub fn may_upload(&self, kind: &str, status: &MembershipStatus) -> bool {
+        let role = if kind == KIND_PHOTO { &self.photo_upload_role } else { &self.document_upload_role };
+
+        match (role.as_str(), status) {
+            (_, MembershipStatus::Moderator(true)) => true,
+            ("moderator", MembershipStatus::Moderator(false)) | ("member", MembershipStatus::Moderator(false)) => true,
+            ("member", MembershipStatus::Member) => true,
+            _ => false,
+        }
+    }
+}
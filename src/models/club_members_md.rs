@@ -23,3 +23,110 @@ pub enum MembershipStatus {
     Member,
     Moderator(bool)
 }
+
+//A club nobody heads, with the member who would take over if it were repaired.
+#[derive(QueryableByName, Serialize)]
+pub struct HeadlessClub {
+    #[sql_type = "diesel::sql_types::Integer"]
+    pub club_id: i32,
+    #[sql_type = "diesel::sql_types::Text"]
+    pub name: String,
+    #[sql_type = "diesel::sql_types::BigInt"]
+    pub member_count: i64,
+    #[sql_type = "diesel::sql_types::Nullable<diesel::sql_types::Integer>"]
+    pub candidate_id: Option<i32>,
+}
+
+impl ClubMember {
+    /*
+    Makes the user head of the club, the previous head stays on as a
+    moderator. Users who aren't members yet are added. Runs in a
+    transaction so the club always has exactly one head.
+    */
+    pub fn make_head(conn: &PgConnection, req_club_id: &i32, req_user_id: &i32) -> QueryResult<ClubMember> {
+        use crate::schema::club_members::dsl::{club_members, club_id, user_id, is_moderator};
+
+        conn.transaction(|| {
+            //Demoted first, one_head_per_club is checked after every statement.
+            diesel::update(club_members)
+                .filter(club_id.eq(req_club_id))
+                .filter(is_moderator.eq("head"))
+                .filter(user_id.ne(req_user_id))
+                .set(is_moderator.eq("true"))
+                .execute(conn)?;
+
+            insert_into(club_members)
+                .values(&NewClubMember {
+                    user_id: req_user_id,
+                    club_id: req_club_id,
+                    is_moderator: "head",
+                })
+                .on_conflict((user_id, club_id))
+                .do_update()
+                .set(is_moderator.eq("head"))
+                .get_result::<ClubMember>(conn)
+        })
+    }
+
+    /*
+    Hands the club over to one of its members, checked the way appointing
+    a new head is. Used by appoint and when a head deletes their account.
+    */
+    pub fn hand_over(conn: &PgConnection, req_club_id: &i32, successor: User) -> std::result::Result<ClubMember, ApiError> {
+        let successor_id = successor.id;
+
+        match successor.get_membership_status(conn, req_club_id)? {
+            MembershipStatus::Moderator(true) => Err(ApiError::BadRequest("You already are a head moderator.".to_owned())),
+            MembershipStatus::Unassociated => Err(ApiError::BadRequest("User is not a member.".to_owned())),
+            _ => Ok(Self::make_head(conn, req_club_id, &successor_id)?),
+        }
+    }
+
+    //The head and moderators of the club, what the audit log records around appointments.
+    pub fn moderators(conn: &PgConnection, req_club_id: &i32) -> QueryResult<Vec<ClubMember>> {
+        use crate::schema::club_members::dsl::{club_members, club_id, is_moderator, id};
+
+        club_members
+            .filter(club_id.eq(req_club_id))
+            .filter(is_moderator.ne("false"))
+            .order(id.asc())
+            .load::<ClubMember>(conn)
+    }
+
+    /*
+    Clubs, deleted ones included, that have no head moderator. The
+    candidate is the longest standing moderator, or member if there are
+    no moderators left.
+    */
+    pub fn find_headless(conn: &PgConnection) -> QueryResult<Vec<HeadlessClub>> {
+        diesel::sql_query("\
+            SELECT clubs.id AS club_id, clubs.name, \
+            (SELECT COUNT(*) FROM club_members WHERE club_members.club_id = clubs.id) AS member_count, \
+            (SELECT club_members.user_id FROM club_members WHERE club_members.club_id = clubs.id \
+            ORDER BY club_members.is_moderator = 'true' DESC, club_members.id LIMIT 1) AS candidate_id \
+            FROM clubs WHERE NOT EXISTS (\
+            SELECT 1 FROM club_members WHERE club_members.club_id = clubs.id AND club_members.is_moderator = 'head') \
+            ORDER BY clubs.id")
+            .load::<HeadlessClub>(conn)
+    }
+
+    //Promotes the candidate of every headless club that has one. Returns the new heads and the clubs left without one.
+    pub fn repair_headless(conn: &PgConnection) -> QueryResult<(Vec<ClubMember>, Vec<HeadlessClub>)> {
+        let mut new_heads = Vec::new();
+        let mut unrepaired = Vec::new();
+
+        for club in Self::find_headless(conn)? {
+            match club.candidate_id {
+                Some(candidate_id) => {
+                    new_heads.push(Self::make_head(conn, &club.club_id, &candidate_id)?);
+                    Notification::notify(conn, &candidate_id, Some(&club.club_id), "club_head_repaired", &format!(
+                        "{} had no head moderator, you have been made its head.", club.name
+                    ))?;
+                },
+                None => unrepaired.push(club),
+            }
+        }
+
+        Ok((new_heads, unrepaired))
+    }
+}
@@ -0,0 +1,138 @@
+use crate::prelude::*;
+use crate::schema::club_reports;
+
+#[derive(Queryable, Serialize, Deserialize)]
+pub struct ClubReport {
+    pub id: i32,
+    pub club_id: i32,
+    pub reporter_id: Option<i32>,
+    pub reason: String,
+    pub details: String,
+    //open, dismissed, warned, hidden or deleted
+    pub status: String,
+    pub resolution_note: Option<String>,
+    pub resolved_by: Option<i32>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[table_name = "club_reports"]
+pub struct NewClubReport<'a> {
+    pub club_id: &'a i32,
+    pub reporter_id: Option<&'a i32>,
+    pub reason: &'a str,
+    pub details: &'a str,
+}
+
+#[derive(Serialize)]
+pub struct ReasonCount {
+    pub reason: String,
+    pub count: i64,
+}
+
+//Every open report on one club, an item in the admins' queue.
+#[derive(Serialize)]
+pub struct ReportGroup {
+    pub club_id: i32,
+    pub club_name: String,
+    //Hiding a club sends it back to pending, which takes it out of the listings.
+    pub approval_status: String,
+    pub report_count: i64,
+    pub reasons: Vec<ReasonCount>,
+    pub first_reported_at: DateTime<Utc>,
+    pub last_reported_at: DateTime<Utc>,
+    pub reports: Vec<ClubReport>,
+}
+
+pub const REPORT_OPEN: &str = "open";
+
+impl ClubReport {
+    pub fn file(conn: &PgConnection, req_club_id: &i32, req_reporter_id: &i32, req_reason: &str, req_details: &str) -> QueryResult<ClubReport> {
+        use crate::schema::club_reports::dsl::{club_reports};
+
+        insert_into(club_reports)
+            .values(&NewClubReport {
+                club_id: req_club_id,
+                reporter_id: Some(req_reporter_id),
+                reason: req_reason,
+                details: req_details,
+            })
+            .get_result(conn)
+    }
+
+    pub fn open_for_club(conn: &PgConnection, req_club_id: &i32) -> QueryResult<Vec<ClubReport>> {
+        use crate::schema::club_reports::dsl::{club_reports, club_id, status, created_at};
+
+        club_reports
+            .filter(club_id.eq(req_club_id))
+            .filter(status.eq(REPORT_OPEN))
+            .order(created_at.asc())
+            .load::<ClubReport>(conn)
+    }
+
+    /*
+    The queue: open reports grouped by club, the most reported clubs
+    first and the longest waiting first among equals.
+    */
+    pub fn queue(conn: &PgConnection) -> QueryResult<Vec<ReportGroup>> {
+        use crate::schema::club_reports::dsl::{club_reports, club_id, status, created_at};
+        use crate::schema::clubs::dsl::{clubs, name, approval_status};
+
+        let rows = club_reports
+            .inner_join(clubs)
+            .filter(status.eq(REPORT_OPEN))
+            .order((club_id.asc(), created_at.asc()))
+            .select((crate::schema::club_reports::all_columns, name, approval_status))
+            .load::<(ClubReport, String, String)>(conn)?;
+
+        let mut groups: Vec<ReportGroup> = Vec::new();
+        for (report, club_name, club_approval_status) in rows {
+            if groups.last().map_or(true, |group| group.club_id != report.club_id) {
+                groups.push(ReportGroup {
+                    club_id: report.club_id,
+                    club_name: club_name,
+                    approval_status: club_approval_status,
+                    report_count: 0,
+                    reasons: Vec::new(),
+                    first_reported_at: report.created_at,
+                    last_reported_at: report.created_at,
+                    reports: Vec::new(),
+                });
+            }
+
+            //Rows come ordered by club, so the report belongs to the last group.
+            let group = groups.last_mut().expect("A group was just pushed");
+            group.report_count += 1;
+            group.last_reported_at = report.created_at;
+            match group.reasons.iter_mut().find(|counted| counted.reason == report.reason) {
+                Some(counted) => counted.count += 1,
+                None => group.reasons.push(ReasonCount { reason: report.reason.clone(), count: 1 }),
+            }
+            group.reports.push(report);
+        }
+
+        for group in groups.iter_mut() {
+            group.reasons.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.reason.cmp(&b.reason)));
+        }
+        groups.sort_by(|a, b| b.report_count.cmp(&a.report_count).then_with(|| a.first_reported_at.cmp(&b.first_reported_at)));
+
+        Ok(groups)
+    }
+
+    //Closes every open report on the club with the admin's outcome, returning them.
+    pub fn resolve(conn: &PgConnection, req_club_id: &i32, admin_id: &i32, outcome: &str, note: Option<&str>) -> QueryResult<Vec<ClubReport>> {
+        use crate::schema::club_reports::dsl::{club_reports, club_id, status, resolution_note, resolved_by, resolved_at};
+
+        diesel::update(club_reports)
+            .filter(club_id.eq(req_club_id))
+            .filter(status.eq(REPORT_OPEN))
+            .set((
+                status.eq(outcome),
+                resolution_note.eq(note),
+                resolved_by.eq(admin_id),
+                resolved_at.eq(chrono::offset::Utc::now()),
+            ))
+            .get_results::<ClubReport>(conn)
+    }
+}
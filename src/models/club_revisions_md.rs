@@ -0,0 +1,118 @@
+use crate::prelude::*;
+use crate::schema::club_revisions;
+use similar::{ChangeTag, TextDiff};
+
+#[derive(Queryable, Serialize, Deserialize)]
+pub struct ClubRevision {
+    pub id: i32,
+    pub club_id: i32,
+    pub name: String,
+    pub body: String,
+    pub author_id: Option<i32>,
+    pub reverted_from: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[table_name = "club_revisions"]
+pub struct NewClubRevision<'a> {
+    pub club_id: &'a i32,
+    pub name: &'a str,
+    pub body: &'a str,
+    pub author_id: Option<&'a i32>,
+    pub reverted_from: Option<&'a i32>,
+}
+
+//A revision as listed in a club's history, with its author's details.
+#[derive(Serialize, Deserialize)]
+pub struct ClubRevisionSummary {
+    pub id: i32,
+    pub name: String,
+    pub author: Option<UserDetails>,
+    pub reverted_from: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DiffLine {
+    //One of equal, insert or delete.
+    pub op: String,
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ClubRevisionDiff {
+    pub from: i32,
+    pub to: i32,
+    pub name: Vec<DiffLine>,
+    pub body: Vec<DiffLine>,
+}
+
+impl ClubRevision {
+    //Records the club as it is now. Call after every change to its name or body.
+    pub fn record(conn: &PgConnection, club: &Club, author: Option<&i32>, reverted_from: Option<&i32>) -> QueryResult<ClubRevision> {
+        use crate::schema::club_revisions::dsl::{club_revisions};
+
+        insert_into(club_revisions)
+            .values(&NewClubRevision {
+                club_id: &club.id,
+                name: &club.name,
+                body: &club.body,
+                author_id: author,
+                reverted_from: reverted_from,
+            })
+            .get_result(conn)
+    }
+
+    pub fn get_for_club(conn: &PgConnection, req_club_id: &i32, req_id: &i32) -> Option<ClubRevision> {
+        use crate::schema::club_revisions::dsl::{club_revisions, club_id, id};
+        club_revisions.filter(club_id.eq(req_club_id)).filter(id.eq(req_id)).first(conn).ok()
+    }
+
+    //The club's history, newest first, without the bodies. Authors are shown as the viewer gets to see them.
+    pub fn list(conn: &PgConnection, req_club_id: &i32, viewer: Option<i32>) -> QueryResult<Vec<ClubRevisionSummary>> {
+        use crate::schema::club_revisions::dsl::{club_revisions, club_id, id};
+        use crate::schema::users::dsl::{users};
+
+        let rows = club_revisions
+            .left_join(users)
+            .filter(club_id.eq(req_club_id))
+            .order(id.desc())
+            .load::<(ClubRevision, Option<User>)>(conn)?;
+
+        rows.into_iter().map(|(revision, author)| Ok(ClubRevisionSummary {
+            id: revision.id,
+            name: revision.name,
+            author: match author {
+                Some(author) => Some(author.to_user_details(conn, viewer)?),
+                None => None,
+            },
+            reverted_from: revision.reverted_from,
+            created_at: revision.created_at,
+        })).collect()
+    }
+
+    //Line by line changes going from this revision to the other one.
+    pub fn diff(&self, other: &ClubRevision) -> ClubRevisionDiff {
+        ClubRevisionDiff {
+            from: self.id,
+            to: other.id,
+            name: diff_lines(&self.name, &other.name),
+            body: diff_lines(&self.body, &other.body),
+        }
+    }
+}
+
+fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    TextDiff::from_lines(old, new)
+        .iter_all_changes()
+        .map(|change| DiffLine {
+            op: match change.tag() {
+                ChangeTag::Equal => "equal",
+                ChangeTag::Insert => "insert",
+                ChangeTag::Delete => "delete",
+            }.to_owned(),
+            text: change.value().trim_end_matches('\n').to_owned(),
+        })
+        .collect()
+}
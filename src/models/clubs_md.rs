@@ -1,13 +1,31 @@
 use crate::prelude::*;
 use crate::schema::clubs;
 
-#[derive(Queryable, Serialize, Deserialize)]
+#[derive(Queryable, QueryableByName, Serialize, Deserialize)]
+#[table_name = "clubs"]
 pub struct Club {
     pub id: i32,
     pub name: String,
     pub body: String,
     pub publish_date: DateTime<Utc>,
-    pub expiry_date: DateTime<Utc>
+    pub expiry_date: DateTime<Utc>,
+    pub is_public: bool,
+    pub category_id: Option<i32>,
+    pub renewal_days: Option<i32>,
+    pub expiry_warned_at: Option<DateTime<Utc>>,
+    pub archived_at: Option<DateTime<Utc>>,
+    pub approval_status: String,
+    pub review_note: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub is_verified: bool,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub deleted_by: Option<i32>,
+    pub version: i32,
+    pub body_html: String,
+    pub logo_key: Option<String>,
+    pub photo_upload_role: String,
+    pub document_upload_role: String,
+    pub storage_quota_bytes: Option<i64>,
 }
 
 #[derive(Insertable)]
@@ -15,6 +33,7 @@ pub struct Club {
 pub struct NewClub<'a> {
     pub name: &'a str,
     pub body: &'a str,
+    pub body_html: &'a str,
     pub publish_date: &'a DateTime<Utc>,
     pub expiry_date: &'a DateTime<Utc>
 }
@@ -23,17 +42,448 @@ pub struct NewClub<'a> {
 pub struct ClubDetails{
     pub id: i32,
     pub name: String,
+    //Markdown source, for editing.
     pub body: String,
+    //Sanitized HTML rendered from body, for showing.
+    pub body_html: String,
     pub member_count: i64,
     pub publish_date: DateTime<Utc>,
     pub expiry_date: DateTime<Utc>,
     pub is_member: bool,
     pub is_moderator: String,
+    pub is_public: bool,
+    pub category: Option<String>,
+    pub tags: Vec<String>,
+    pub archived_at: Option<DateTime<Utc>>,
+    //Registration status for the current term, None when no term is running.
+    pub term_status: Option<String>,
+    //One of pending, approved, rejected or changes_requested.
+    pub approval_status: String,
+    pub review_note: Option<String>,
+    pub is_verified: bool,
+    //Also sent as the ETag, send it back as If-Match when editing.
+    pub version: i32,
+    //A generated avatar until a logo is uploaded.
+    pub logo: ClubLogo,
     pub head_moderator: UserDetails,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClubPage {
+    pub clubs: Vec<ClubDetails>,
+    pub next_cursor: Option<String>,
+    //Only computed for the first page.
+    pub facets: Option<ClubFacets>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClubFacets {
+    pub categories: Vec<FacetCount>,
+    pub tags: Vec<FacetCount>,
+}
+
+#[derive(FromFormField, Clone, Copy, PartialEq, Eq)]
+pub enum ClubSort {
+    Relevance,
+    Popular,
+    Newest,
+    #[field(value = "expiring")]
+    ExpiringSoon,
+    Alphabetical,
+}
+
+#[derive(FromFormField, Clone, Copy, PartialEq, Eq)]
+pub enum MembershipFilter {
+    All,
+    Member,
+    Moderator,
+    Unassociated,
+}
+
+#[derive(FromForm, Default)]
+pub struct ClubQuery {
+    pub q: Option<String>,
+    pub filter: Option<MembershipFilter>,
+    pub category: Option<i32>,
+    //Repeat to require several tags, e.g. tag=chess&tag=board%20games
+    pub tag: Vec<String>,
+    //Only clubs registered for this term, defaults to the current term when one is running.
+    pub term: Option<i32>,
+    pub sort: Option<ClubSort>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/*
+Everything ClubDetails needs in one row: the club, its member count,
+its head moderator and the caller's membership. Clubs without a head
+drop out of the inner join just like from_join skips them.
+$1 is always the caller's user id.
+*/
+const DETAILS_COLUMNS: &str = "\
+    clubs.*, \
+    (SELECT COUNT(*) FROM club_members counted WHERE counted.club_id = clubs.id) AS member_count, \
+    me.is_moderator AS caller_is_moderator, \
+    head.id AS head_id, \
+    head.email AS head_email, \
+    head.email_visibility AS head_email_visibility, \
+    head.avatar_key AS head_avatar_key, \
+    head.display_name AS head_display_name, \
+    head.display_name_visibility AS head_display_name_visibility, \
+    head.pronouns AS head_pronouns, \
+    head.pronouns_visibility AS head_pronouns_visibility, \
+    $1::INT AS caller_id, \
+    ($1::INT IS NOT NULL AND are_club_mates($1, head.id)) AS caller_knows_head, \
+    head.first_name AS head_first_name, \
+    head.last_name AS head_last_name, \
+    categories.name AS category_name, \
+    ARRAY(SELECT tags.name FROM club_tags INNER JOIN tags ON tags.id = club_tags.tag_id WHERE club_tags.club_id = clubs.id ORDER BY tags.name) AS tag_names, \
+    (SELECT r.status FROM club_registrations r INNER JOIN terms t ON t.id = r.term_id WHERE r.club_id = clubs.id AND t.start_date <= NOW() AND t.end_date > NOW() ORDER BY t.start_date DESC LIMIT 1) AS term_status";
+
+const DETAILS_FROM: &str = "\
+    clubs \
+    INNER JOIN club_members head_member ON head_member.club_id = clubs.id AND head_member.is_moderator = 'head' \
+    INNER JOIN users head ON head.id = head_member.user_id \
+    LEFT JOIN club_members me ON me.club_id = clubs.id AND me.user_id = $1 \
+    LEFT JOIN categories ON categories.id = clubs.category_id";
+
+//Guests ($1 is null) only get to see public clubs.
+const VISIBLE: &str = "($1::INT IS NOT NULL OR clubs.is_public)";
+
+//Archived clubs and clubs admins haven't approved stay out of listings, only their members can still open them.
+//Deleted clubs are gone for everyone until they are restored.
+const ACTIVE: &str = "(clubs.deleted_at IS NULL AND clubs.archived_at IS NULL AND clubs.approval_status = 'approved')";
+const ACTIVE_OR_MEMBER: &str = "(clubs.deleted_at IS NULL AND ((clubs.archived_at IS NULL AND clubs.approval_status = 'approved') OR me.id IS NOT NULL))";
+const NOT_DELETED: &str = "clubs.deleted_at IS NULL";
+
+/*
+Conditions shared by the page and its facets. $2 is the search text,
+$3 the category and $4 the tags a club must all have.
+*/
+const MATCHES: &str = "\
+    ($2::TEXT IS NULL OR clubs.search_vector @@ websearch_to_tsquery('english', $2) OR $2 <% clubs.name) \
+    AND ($3::INT IS NULL OR clubs.category_id = $3) \
+    AND NOT EXISTS ( \
+        SELECT 1 FROM unnest($4::TEXT[]) AS wanted(name) \
+        WHERE wanted.name NOT IN (SELECT tags.name FROM club_tags INNER JOIN tags ON tags.id = club_tags.tag_id WHERE club_tags.club_id = clubs.id) \
+    )";
+
+//$5 is the term clubs have to be registered for, if any.
+const REGISTERED: &str = "\
+    ($5::INT IS NULL OR EXISTS ( \
+        SELECT 1 FROM club_registrations registered \
+        WHERE registered.club_id = clubs.id AND registered.term_id = $5 AND registered.status = 'registered' \
+    ))";
+
+pub const MAX_TAG_FACETS: i64 = 20;
+
+//Defined in the club_name_validation migration, see validation::normalize_name.
+sql_function!(fn normalize_club_name(name: diesel::sql_types::Text) -> diesel::sql_types::Text);
+
+#[derive(QueryableByName)]
+pub struct ClubDetailsRow {
+    #[diesel(embed)]
+    club: Club,
+    #[sql_type = "diesel::sql_types::BigInt"]
+    member_count: i64,
+    #[sql_type = "diesel::sql_types::Nullable<diesel::sql_types::Text>"]
+    caller_is_moderator: Option<String>,
+    #[sql_type = "diesel::sql_types::Integer"]
+    head_id: i32,
+    #[sql_type = "diesel::sql_types::Text"]
+    head_email: String,
+    #[sql_type = "diesel::sql_types::Text"]
+    head_email_visibility: String,
+    #[sql_type = "diesel::sql_types::Nullable<diesel::sql_types::Text>"]
+    head_avatar_key: Option<String>,
+    #[sql_type = "diesel::sql_types::Nullable<diesel::sql_types::Text>"]
+    head_display_name: Option<String>,
+    #[sql_type = "diesel::sql_types::Text"]
+    head_display_name_visibility: String,
+    #[sql_type = "diesel::sql_types::Nullable<diesel::sql_types::Text>"]
+    head_pronouns: Option<String>,
+    #[sql_type = "diesel::sql_types::Text"]
+    head_pronouns_visibility: String,
+    #[sql_type = "diesel::sql_types::Nullable<diesel::sql_types::Integer>"]
+    caller_id: Option<i32>,
+    #[sql_type = "diesel::sql_types::Bool"]
+    caller_knows_head: bool,
+    #[sql_type = "diesel::sql_types::Text"]
+    head_first_name: String,
+    #[sql_type = "diesel::sql_types::Text"]
+    head_last_name: String,
+    #[sql_type = "diesel::sql_types::Nullable<diesel::sql_types::Text>"]
+    category_name: Option<String>,
+    #[sql_type = "diesel::sql_types::Array<diesel::sql_types::Text>"]
+    tag_names: Vec<String>,
+    #[sql_type = "diesel::sql_types::Nullable<diesel::sql_types::Text>"]
+    term_status: Option<String>,
+}
+
+#[derive(QueryableByName)]
+struct ClubSearchRow {
+    #[diesel(embed)]
+    details: ClubDetailsRow,
+    #[sql_type = "diesel::sql_types::Text"]
+    sort_key: String,
+}
+
+pub const DEFAULT_PAGE_SIZE: i64 = 24;
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+impl ClubSort {
+    //Sort key expression, its postgres type and whether it sorts descending. $2 is the search text.
+    fn key(&self) -> (&'static str, &'static str, bool) {
+        match self {
+            ClubSort::Relevance => ("(ts_rank(clubs.search_vector, websearch_to_tsquery('english', $2)) + word_similarity($2, clubs.name))", "REAL", true),
+            ClubSort::Popular => ("(SELECT COUNT(*) FROM club_members WHERE club_members.club_id = clubs.id)", "BIGINT", true),
+            ClubSort::Newest => ("clubs.publish_date", "TIMESTAMPTZ", true),
+            ClubSort::ExpiringSoon => ("clubs.expiry_date", "TIMESTAMPTZ", false),
+            ClubSort::Alphabetical => ("clubs.name", "TEXT", false),
+        }
+    }
+
+    //How the sort key goes into a cursor. Timestamps are spelled out in UTC, ::TEXT would follow the session's TimeZone.
+    fn cursor_key(&self) -> String {
+        match self.key() {
+            (key, "TIMESTAMPTZ", _) => format!("to_char(({}) AT TIME ZONE 'UTC', 'YYYY-MM-DD\"T\"HH24:MI:SS.US\"Z\"')", key),
+            (key, _, _) => format!("({})::TEXT", key),
+        }
+    }
+}
+
+impl MembershipFilter {
+    //Conditions on the caller's membership row joined in by DETAILS_FROM.
+    fn clause(&self) -> &'static str {
+        match self {
+            MembershipFilter::All => "TRUE",
+            MembershipFilter::Member => "me.id IS NOT NULL",
+            MembershipFilter::Moderator => "me.is_moderator IN ('true', 'head')",
+            MembershipFilter::Unassociated => "me.id IS NULL",
+        }
+    }
+}
+
+impl ClubQuery {
+    pub fn search_text(&self) -> Option<String> {
+        self.q.as_ref().map(|q| q.trim().to_owned()).filter(|q| !q.is_empty())
+    }
+
+    pub fn sort(&self) -> ClubSort {
+        match (self.sort, self.search_text()) {
+            (Some(ClubSort::Relevance), None) => ClubSort::Newest,
+            (Some(sort), _) => sort,
+            (None, Some(_)) => ClubSort::Relevance,
+            (None, None) => ClubSort::Newest,
+        }
+    }
+
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_PAGE_SIZE).max(1).min(MAX_PAGE_SIZE)
+    }
+
+    //Tags are compared in their normalised form, anything that can't be a tag can't match.
+    pub fn tags(&self) -> Vec<String> {
+        self.tag.iter().map(|tag| Tag::normalize(tag).unwrap_or_else(|_| tag.clone())).collect()
+    }
+
+    //The term asked for, or the one running right now.
+    pub fn term(&self, conn: &PgConnection) -> QueryResult<Option<i32>> {
+        match self.term {
+            Some(term) => Ok(Some(term)),
+            None => Ok(Term::current(conn)?.map(|term| term.id)),
+        }
+    }
+
+    //Cursors are the sort key of the last club on a page followed by its id.
+    //The key is checked against the sort's type here, postgres would only fail the whole query on it.
+    pub fn cursor(&self) -> Result<Option<(String, i32)>> {
+        let cursor = match self.cursor.as_ref() {
+            Some(cursor) => cursor,
+            None => return Ok(None),
+        };
+        let malformed = || ApiError::BadRequest("That cursor is not valid for this sort order.".to_owned());
+        let mut parts = cursor.rsplitn(2, '_');
+        let id = parts.next().and_then(|id| id.parse::<i32>().ok()).ok_or_else(malformed)?;
+        let key = parts.next().ok_or_else(malformed)?;
+        let valid = match self.sort().key().1 {
+            "REAL" => key.parse::<f32>().map(|key| key.is_finite()).unwrap_or(false),
+            "BIGINT" => key.parse::<i64>().is_ok(),
+            "TIMESTAMPTZ" => DateTime::parse_from_rfc3339(key).is_ok(),
+            _ => true,
+        };
+        if !valid {
+            return Err(malformed())
+        }
+        Ok(Some((key.to_owned(), id)))
+    }
+}
+
 impl Club {
-    pub fn to_club_details(self, conn: &PgConnection, user_id: &i32)  -> ClubDetails {
+    /*
+    Every piece of user input is bound as a parameter, the SQL text itself
+    is only ever assembled from the static fragments above. All eight
+    parameters are always bound so the statement shape only varies with
+    the sort and filter. The whole page comes back from a single query.
+    Pass no user to browse as a guest.
+    */
+    pub fn search(conn: &PgConnection, user_id: Option<i32>, query: &ClubQuery) -> Result<ClubPage> {
+        use diesel::sql_types::{Array, BigInt, Integer, Nullable, Text};
+
+        let sort = query.sort();
+        let (key, key_type, descending) = sort.key();
+        let (comparison, direction) = if descending {("<", "DESC")} else {(">", "ASC")};
+        let conditions = Self::search_conditions(query);
+        let (cursor_key, cursor_id) = match query.cursor()? {
+            Some((cursor_key, cursor_id)) => (Some(cursor_key), cursor_id),
+            None => (None, 0),
+        };
+        let limit = query.limit();
+        let term = query.term(conn)?;
+
+        let statement = format!("\
+            SELECT {columns}, {cursor_key} AS sort_key FROM {from} \
+            WHERE {conditions} \
+            AND ($6::TEXT IS NULL OR (({key}), clubs.id) {comparison} (CAST($6 AS {key_type}), $7)) \
+            ORDER BY ({key}) {direction}, clubs.id {direction} \
+            LIMIT $8",
+            columns = DETAILS_COLUMNS, from = DETAILS_FROM, conditions = conditions,
+            key = key, cursor_key = sort.cursor_key(), key_type = key_type, comparison = comparison, direction = direction
+        );
+
+        let mut rows = diesel::sql_query(statement)
+            .bind::<Nullable<Integer>, _>(user_id)
+            .bind::<Nullable<Text>, _>(query.search_text())
+            .bind::<Nullable<Integer>, _>(query.category)
+            .bind::<Array<Text>, _>(query.tags())
+            .bind::<Nullable<Integer>, _>(term)
+            .bind::<Nullable<Text>, _>(cursor_key.clone())
+            .bind::<Integer, _>(cursor_id)
+            .bind::<BigInt, _>(limit + 1)
+            .load::<ClubSearchRow>(conn)?;
+
+        //We fetched one extra row to know whether there is another page.
+        let next_cursor = if rows.len() as i64 > limit {
+            rows.truncate(limit as usize);
+            rows.last().map(|row| format!("{}_{}", row.sort_key, row.details.club.id))
+        } else {
+            None
+        };
+
+        let facets = if cursor_key.is_none() {
+            Some(Self::search_facets(conn, user_id, query, term)?)
+        } else {
+            None
+        };
+
+        Ok(ClubPage {
+            clubs: rows.into_iter().map(|row| ClubDetails::from(row.details).visible_to(user_id)).collect(),
+            next_cursor: next_cursor,
+            facets: facets,
+        })
+    }
+
+    fn search_conditions(query: &ClubQuery) -> String {
+        let filter = query.filter.unwrap_or(MembershipFilter::All).clause();
+
+        format!("{} AND {} AND {} AND {} AND {}", VISIBLE, ACTIVE, MATCHES, REGISTERED, filter)
+    }
+
+    /*
+    Counts per category and per tag over everything the query matches.
+    Category counts ignore the selected category so the other choices
+    stay visible, tag counts narrow down as tags are added.
+    */
+    fn search_facets(conn: &PgConnection, user_id: Option<i32>, query: &ClubQuery, term: Option<i32>) -> QueryResult<ClubFacets> {
+        use diesel::sql_types::{Array, BigInt, Integer, Nullable, Text};
+
+        let conditions = Self::search_conditions(query);
+
+        let categories = diesel::sql_query(format!("\
+            SELECT categories.id, categories.name, COUNT(*) AS count FROM {} \
+            WHERE {} AND categories.id IS NOT NULL \
+            GROUP BY categories.id \
+            ORDER BY count DESC, categories.name",
+            DETAILS_FROM, conditions))
+            .bind::<Nullable<Integer>, _>(user_id)
+            .bind::<Nullable<Text>, _>(query.search_text())
+            .bind::<Nullable<Integer>, _>(None::<i32>)
+            .bind::<Array<Text>, _>(query.tags())
+            .bind::<Nullable<Integer>, _>(term)
+            .load::<FacetCount>(conn)?;
+
+        let tags = diesel::sql_query(format!("\
+            SELECT facet.id, facet.name, COUNT(*) AS count FROM {} \
+            INNER JOIN club_tags facet_club ON facet_club.club_id = clubs.id \
+            INNER JOIN tags facet ON facet.id = facet_club.tag_id \
+            WHERE {} \
+            GROUP BY facet.id \
+            ORDER BY count DESC, facet.name \
+            LIMIT $6",
+            DETAILS_FROM, conditions))
+            .bind::<Nullable<Integer>, _>(user_id)
+            .bind::<Nullable<Text>, _>(query.search_text())
+            .bind::<Nullable<Integer>, _>(query.category)
+            .bind::<Array<Text>, _>(query.tags())
+            .bind::<Nullable<Integer>, _>(term)
+            .bind::<BigInt, _>(MAX_TAG_FACETS)
+            .load::<FacetCount>(conn)?;
+
+        Ok(ClubFacets {
+            categories: categories,
+            tags: tags,
+        })
+    }
+
+    //Whether the club has been deleted and can't be restored anymore.
+    pub fn is_purgeable(&self, config: &LifecycleConfig) -> bool {
+        match self.deleted_at {
+            Some(deleted_at) => deleted_at + config.restore_window() <= chrono::offset::Utc::now(),
+            None => false,
+        }
+    }
+
+    //Brings an archived club back with a fresh expiry date. Like renewals this is only for clubs that aren't registered per term.
+    pub fn reactivate(conn: &PgConnection, req_id: &i32, config: &LifecycleConfig) -> Result<Club> {
+        use crate::schema::clubs::dsl::{clubs, expiry_date, expiry_warned_at, archived_at, deleted_at};
+
+        let club = clubs.find(req_id).filter(deleted_at.is_null()).get_result::<Club>(conn).optional()?
+            .ok_or(ApiError::NotFound("The club you are trying to reactivate does not exist.".to_owned()))?;
+        if club.archived_at.is_none() {
+            return Err(ApiError::BadRequest("This club has not been archived, it has to be renewed instead.".to_owned()))
+        }
+        if Term::terms_in_use(conn)? {
+            return Err(ApiError::BadRequest("Clubs are registered per term now, re-register the club for the upcoming term instead.".to_owned()))
+        }
+
+        diesel::update(clubs.find(req_id).filter(archived_at.is_not_null()).filter(deleted_at.is_null()))
+            .set((
+                expiry_date.eq(config.next_expiry(club.renewal_days)),
+                expiry_warned_at.eq(None::<DateTime<Utc>>),
+                archived_at.eq(None::<DateTime<Utc>>),
+            ))
+            .get_result::<Club>(conn)
+            .optional()?
+            .ok_or(ApiError::Conflict("The club was changed while it was being reactivated, try again.".to_owned()))
+    }
+
+    //Whether another club, deleted ones included, already uses the name once case and spacing are ignored.
+    pub fn name_taken(conn: &PgConnection, req_name: &str, except_id: Option<i32>) -> QueryResult<bool> {
+        use crate::schema::clubs::dsl::{clubs, id, name};
+
+        let mut query = clubs
+            .filter(normalize_club_name(name).eq(normalize_club_name(req_name)))
+            .into_boxed();
+
+        if let Some(except_id) = except_id {
+            query = query.filter(id.ne(except_id));
+        }
+
+        let taken = query.count().get_result::<i64>(conn)?;
+        Ok(taken > 0)
+    }
+
+    pub fn to_club_details(self, conn: &PgConnection, user_id: &i32)  -> Result<ClubDetails> {
         ClubDetails::from_club(conn, self, user_id)
     }
 
@@ -48,6 +498,12 @@ impl Club {
         }
     }
 
+    //The club as long as it hasn't been deleted. Anything that changes a club loads it through here.
+    pub fn get_active(conn: &PgConnection, req_id: &i32) -> Option<Club>{
+        use crate::schema::clubs::dsl::{clubs, id, deleted_at};
+        clubs.filter(id.eq(req_id)).filter(deleted_at.is_null()).first(conn).ok()
+    }
+
     pub async fn get_by_id_async(db: &Db, req_id: &i32) -> Option<Club>{
         let req_id = req_id.clone();
         let result = db.run( move |conn| {
@@ -59,20 +515,28 @@ impl Club {
 }
 
 impl ClubDetails {
-    pub fn from_club(conn: &PgConnection, club : Club, user_id: &i32) -> Self{
+    //Fails for clubs without a head moderator, those need an admin to name one first.
+    pub fn from_club(conn: &PgConnection, club : Club, user_id: &i32) -> Result<Self>{
         use crate::schema::club_members::dsl::{club_members, club_id, is_moderator, user_id as club_members_user_id};
 
-        let member_count = club_members.filter(club_id.eq(club.id)).count().first::<i64>(conn).unwrap();
+        let member_count = club_members.filter(club_id.eq(club.id)).count().first::<i64>(conn)?;
 
-        let req_id = club_members.filter(club_id.eq(club.id)).filter(is_moderator.eq("head")).select(club_members_user_id).first::<i32>(conn).unwrap();
-        let user = User::get_by_id(conn, &req_id).unwrap();
-        let status = User::get_by_id(conn, user_id).unwrap()
-            .get_membership_status(conn, &club.id);
+        let req_id = club_members.filter(club_id.eq(club.id)).filter(is_moderator.eq("head")).select(club_members_user_id).first::<i32>(conn)
+            .optional()?
+            .ok_or(ApiError::Conflict("The club has no head moderator, an admin has to name one.".to_owned()))?;
+        let user = User::get_by_id(conn, &req_id).ok_or(ApiError::Internal)?;
+        let status = User::get_by_id(conn, user_id)
+            .ok_or(ApiError::NotFound("User does not exist.".to_owned()))?
+            .get_membership_status(conn, &club.id)?;
+        let (category, tags) = Self::load_taxonomy(conn, &club);
+        let term_status = Self::load_term_status(conn, &club.id);
+        let logo = ClubLogo::for_club(club.id, &club.name, club.logo_key.as_deref());
 
-        Self {
+        Ok(Self {
             id: club.id,
             name: club.name,
             body: club.body,
+            body_html: club.body_html,
             publish_date: club.publish_date,
             expiry_date: club.expiry_date,
             member_count: member_count,
@@ -88,9 +552,101 @@ impl ClubDetails {
                 } else { 
                     "false".to_owned() 
                 },
+            is_public: club.is_public,
+            category: category,
+            tags: tags,
+            archived_at: club.archived_at,
+            term_status: term_status,
+            approval_status: club.approval_status,
+            review_note: club.review_note,
+            is_verified: club.is_verified,
+            version: club.version,
+            logo: logo,
             head_moderator:
-                user.to_user_details()
+                user.to_user_details(conn, Some(*user_id))?
+        })
+    }
+
+    //Loads the details of a single club for the given user, or a guest, in one query.
+    pub fn get_for_user(conn: &PgConnection, club_id: &i32, user_id: Option<i32>) -> QueryResult<Option<Self>> {
+        use diesel::sql_types::{Integer, Nullable};
+
+        let statement = format!("SELECT {} FROM {} WHERE clubs.id = $2 AND {} AND {}", DETAILS_COLUMNS, DETAILS_FROM, VISIBLE, ACTIVE_OR_MEMBER);
+        let row = diesel::sql_query(statement)
+            .bind::<Nullable<Integer>, _>(user_id)
+            .bind::<Integer, _>(*club_id)
+            .get_result::<ClubDetailsRow>(conn)
+            .optional()?;
+
+        Ok(row.map(|row| ClubDetails::from(row).visible_to(user_id)))
+    }
+
+    //Strips the fields only signed in users get to see when there is no user.
+    pub fn visible_to(mut self, user_id: Option<i32>) -> Self {
+        if user_id.is_none() {
+            self.head_moderator.email = None;
         }
+        self
+    }
+
+    //Loads every club the user is associated with according to filter, alphabetically, in one query.
+    pub fn load_by_membership(conn: &PgConnection, user_id: &i32, filter: MembershipFilter) -> QueryResult<Vec<Self>> {
+        use diesel::sql_types::Integer;
+
+        let statement = format!("SELECT {} FROM {} WHERE {} AND {} ORDER BY clubs.name, clubs.id", DETAILS_COLUMNS, DETAILS_FROM, NOT_DELETED, filter.clause());
+        let rows = diesel::sql_query(statement)
+            .bind::<Integer, _>(*user_id)
+            .load::<ClubDetailsRow>(conn)?;
+
+        Ok(rows.into_iter().map(|row| row.into()).collect())
+    }
+
+    //Clubs in the given review state with the admin as caller, oldest first.
+    pub fn load_for_review(conn: &PgConnection, user_id: &i32, approval_status: &str) -> QueryResult<Vec<Self>> {
+        use diesel::sql_types::{Integer, Text};
+
+        let statement = format!("SELECT {} FROM {} WHERE {} AND clubs.approval_status = $2 ORDER BY clubs.publish_date, clubs.id", DETAILS_COLUMNS, DETAILS_FROM, NOT_DELETED);
+        let rows = diesel::sql_query(statement)
+            .bind::<Integer, _>(*user_id)
+            .bind::<Text, _>(approval_status)
+            .load::<ClubDetailsRow>(conn)?;
+
+        Ok(rows.into_iter().map(|row| row.into()).collect())
+    }
+
+    //Category name and sorted tag names of a club loaded on its own.
+    pub fn load_taxonomy(conn: &PgConnection, club: &Club) -> (Option<String>, Vec<String>) {
+        use crate::schema::categories::dsl::{categories, name as category_name};
+        use crate::schema::tags::dsl::{tags, name as tag_name};
+        use crate::schema::club_tags::dsl::{club_tags, club_id};
+
+        let category = match club.category_id {
+            Some(category_id) => categories.find(category_id).select(category_name).first::<String>(conn).ok(),
+            None => None,
+        };
+
+        let tag_names = club_tags
+            .inner_join(tags)
+            .filter(club_id.eq(club.id))
+            .select(tag_name)
+            .order(tag_name)
+            .load::<String>(conn)
+            .unwrap_or_default();
+
+        (category, tag_names)
+    }
+
+    //The club's registration status for the current term.
+    pub fn load_term_status(conn: &PgConnection, req_club_id: &i32) -> Option<String> {
+        use crate::schema::club_registrations::dsl::{club_registrations, club_id, term_id, status};
+
+        let term = Term::current(conn).ok()??;
+        club_registrations
+            .filter(club_id.eq(req_club_id))
+            .filter(term_id.eq(term.id))
+            .select(status)
+            .first::<String>(conn)
+            .ok()
     }
 
     pub async fn from_join_async(join: (ClubMember, Club), user_id: i32, db: Db) -> Option<Self> {
@@ -106,16 +662,20 @@ impl ClubDetails {
 
         let arg_club_id = join.1.id.clone();
 
-        let member_count = club_members.filter(club_id.eq(arg_club_id)).count().first::<i64>(conn).unwrap();
+        let member_count = club_members.filter(club_id.eq(arg_club_id)).count().first::<i64>(conn).ok()?;
 
         if let Ok(req_id) = club_members.filter(club_id.eq(arg_club_id)).filter(is_moderator.eq("head")).select(club_members_user_id).first::<i32>(conn){
-            let user = User::get_by_id(conn, &req_id).unwrap();
+            let user = User::get_by_id(conn, &req_id)?;
+            let (category, tags) = Self::load_taxonomy(conn, &join.1);
+            let term_status = Self::load_term_status(conn, &join.1.id);
+            let logo = ClubLogo::for_club(join.1.id, &join.1.name, join.1.logo_key.as_deref());
 
             Some(
             ClubDetails {
                 id: join.1.id,
                 name: join.1.name,
                 body: join.1.body,
+                body_html: join.1.body_html,
                 publish_date: join.1.publish_date,
                 expiry_date: join.1.expiry_date,
                 member_count: member_count,
@@ -126,11 +686,64 @@ impl ClubDetails {
                     if join.0.user_id == user_id && 
                     join.0.club_id==join.1.id {
                     join.0.is_moderator } else { "false".to_owned() },
+                is_public: join.1.is_public,
+                category: category,
+                tags: tags,
+                archived_at: join.1.archived_at,
+                term_status: term_status,
+                approval_status: join.1.approval_status,
+                review_note: join.1.review_note,
+                is_verified: join.1.is_verified,
+                version: join.1.version,
+                logo: logo,
                 head_moderator:
-                    user.to_user_details()
+                    user.to_user_details(conn, Some(user_id)).ok()?
             })
         } else {
+            eprintln!("Club {} has no head moderator, repair it through /api/clubs/headless.", arg_club_id);
             None
         }
     }
-}
\ No newline at end of file
+}
+
+impl From<ClubDetailsRow> for ClubDetails {
+    fn from(row: ClubDetailsRow) -> Self {
+        let is_moderator = row.caller_is_moderator.clone().unwrap_or("false".to_owned());
+        let logo = ClubLogo::for_club(row.club.id, &row.club.name, row.club.logo_key.as_deref());
+        let head_relation = Relation::between(row.caller_id, row.head_id, row.caller_knows_head);
+
+        Self {
+            id: row.club.id,
+            name: row.club.name,
+            body: row.club.body,
+            body_html: row.club.body_html,
+            publish_date: row.club.publish_date,
+            expiry_date: row.club.expiry_date,
+            member_count: row.member_count,
+            is_member: row.caller_is_moderator.is_some(),
+            is_moderator: is_moderator,
+            is_public: row.club.is_public,
+            category: row.category_name,
+            tags: row.tag_names,
+            archived_at: row.club.archived_at,
+            term_status: row.term_status,
+            approval_status: row.club.approval_status,
+            review_note: row.club.review_note,
+            is_verified: row.club.is_verified,
+            version: row.club.version,
+            logo: logo,
+            head_moderator: UserCard {
+                id: row.head_id,
+                email: row.head_email,
+                email_visibility: row.head_email_visibility,
+                first_name: row.head_first_name,
+                last_name: row.head_last_name,
+                avatar_key: row.head_avatar_key,
+                display_name: row.head_display_name,
+                display_name_visibility: row.head_display_name_visibility,
+                pronouns: row.head_pronouns,
+                pronouns_visibility: row.head_pronouns_visibility,
+            }.shown_to(head_relation)
+        }
+    }
+}
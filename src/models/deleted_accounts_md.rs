@@ -0,0 +1,48 @@
+use crate::prelude::*;
+use crate::schema::deleted_accounts;
+use sha2::{Digest, Sha256};
+
+/*
+Remembers that an account was deleted by its owner. Only a hash of the
+email is kept. Google tokens stay valid for a while after the account is
+gone, the user guard would otherwise sign them straight back up.
+*/
+#[derive(Queryable, Insertable)]
+#[table_name = "deleted_accounts"]
+pub struct DeletedAccount {
+    pub email_hash: String,
+    pub deleted_at: DateTime<Utc>,
+}
+
+impl DeletedAccount {
+    fn hash(email: &str) -> String {
+        hex::encode(Sha256::digest(email.trim().to_lowercase().as_bytes()))
+    }
+
+    pub fn record(conn: &PgConnection, email: &str) -> QueryResult<DeletedAccount> {
+        use crate::schema::deleted_accounts::dsl::{deleted_accounts, deleted_at};
+
+        let now = chrono::offset::Utc::now();
+        insert_into(deleted_accounts)
+            .values(&DeletedAccount {
+                email_hash: Self::hash(email),
+                deleted_at: now,
+            })
+            .on_conflict(crate::schema::deleted_accounts::email_hash)
+            .do_update()
+            .set(deleted_at.eq(now))
+            .get_result::<DeletedAccount>(conn)
+    }
+
+    //Whether a token issued at issued_at was signed before the account with this email was deleted.
+    pub fn revokes(conn: &PgConnection, email: &str, issued_at: &DateTime<Utc>) -> QueryResult<bool> {
+        use crate::schema::deleted_accounts::dsl::{deleted_accounts, email_hash, deleted_at};
+
+        let revoking = deleted_accounts
+            .filter(email_hash.eq(Self::hash(email)))
+            .filter(deleted_at.ge(issued_at))
+            .count()
+            .get_result::<i64>(conn)?;
+        Ok(revoking > 0)
+    }
+}
@@ -1,3 +1,13 @@
 pub mod clubs_md;
 pub mod users_md;
-pub mod club_members_md;
\ No newline at end of file
+pub mod club_members_md;
+pub mod categories_md;
+pub mod tags_md;
+pub mod notifications_md;
+pub mod terms_md;
+pub mod club_revisions_md;
+pub mod club_attachments_md;
+pub mod audit_log_md;
+pub mod club_reports_md;
+pub mod blocklist_md;
+pub mod deleted_accounts_md;
\ No newline at end of file
@@ -0,0 +1,104 @@
+use crate::prelude::*;
+use crate::schema::notifications;
+
+#[derive(Queryable, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: i32,
+    pub user_id: i32,
+    pub club_id: Option<i32>,
+    pub kind: String,
+    pub message: String,
+    pub created_at: DateTime<Utc>,
+    pub read_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Insertable)]
+#[table_name = "notifications"]
+pub struct NewNotification<'a> {
+    pub user_id: &'a i32,
+    pub club_id: Option<&'a i32>,
+    pub kind: &'a str,
+    pub message: &'a str,
+}
+
+impl Notification {
+    pub fn notify(conn: &PgConnection, user_id: &i32, club_id: Option<&i32>, kind: &str, message: &str) -> QueryResult<Notification> {
+        use crate::schema::notifications::dsl::{notifications};
+
+        insert_into(notifications)
+            .values(&NewNotification {
+                user_id: user_id,
+                club_id: club_id,
+                kind: kind,
+                message: message,
+            })
+            .get_result(conn)
+    }
+
+    //Sends the same notification to the head and every other moderator of the club.
+    pub fn notify_moderators(conn: &PgConnection, club_id: &i32, kind: &str, message: &str) -> QueryResult<usize> {
+        use crate::schema::club_members::dsl::{club_members, club_id as member_club_id, user_id, is_moderator};
+        use crate::schema::notifications::dsl::{notifications};
+
+        let moderators = club_members
+            .filter(member_club_id.eq(club_id))
+            .filter(is_moderator.ne("false"))
+            .select(user_id)
+            .load::<i32>(conn)?;
+
+        let new_notifications = moderators.iter().map(|moderator| NewNotification {
+            user_id: moderator,
+            club_id: Some(club_id),
+            kind: kind,
+            message: message,
+        }).collect::<Vec<_>>();
+
+        insert_into(notifications)
+            .values(&new_notifications)
+            .execute(conn)
+    }
+
+    //Notifies the head moderator, who is the club's creator until they hand it over. Headless clubs have no one to tell.
+    pub fn notify_head(conn: &PgConnection, club_id: &i32, kind: &str, message: &str) -> QueryResult<Option<Notification>> {
+        use crate::schema::club_members::dsl::{club_members, club_id as member_club_id, user_id, is_moderator};
+
+        let head = club_members
+            .filter(member_club_id.eq(club_id))
+            .filter(is_moderator.eq("head"))
+            .select(user_id)
+            .first::<i32>(conn)
+            .optional()?;
+
+        match head {
+            Some(head) => Self::notify(conn, &head, Some(club_id), kind, message).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_for_user(conn: &PgConnection, req_user_id: &i32, unread_only: bool) -> QueryResult<Vec<Notification>> {
+        use crate::schema::notifications::dsl::{notifications, user_id, created_at, read_at};
+
+        let mut query = notifications
+            .filter(user_id.eq(req_user_id))
+            .order(created_at.desc())
+            .limit(100)
+            .into_boxed();
+
+        if unread_only {
+            query = query.filter(read_at.is_null());
+        }
+
+        query.load::<Notification>(conn)
+    }
+
+    //Returns false when the notification doesn't exist or belongs to someone else.
+    pub fn mark_read(conn: &PgConnection, req_id: &i32, req_user_id: &i32) -> QueryResult<bool> {
+        use crate::schema::notifications::dsl::{notifications, id, user_id, read_at};
+
+        let updated = diesel::update(notifications.filter(id.eq(req_id)).filter(user_id.eq(req_user_id)))
+            .set(read_at.eq(chrono::offset::Utc::now()))
+            .execute(conn)?;
+
+        Ok(updated > 0)
+    }
+}
@@ -0,0 +1,96 @@
+use crate::prelude::*;
+use crate::schema::{tags, club_tags};
+
+pub const MAX_TAGS_PER_CLUB: usize = 10;
+pub const MAX_TAG_LENGTH: usize = 32;
+
+#[derive(Queryable, Serialize, Deserialize, Clone)]
+pub struct Tag {
+    pub id: i32,
+    pub name: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "tags"]
+pub struct NewTag<'a> {
+    pub name: &'a str,
+}
+
+#[derive(Queryable, Insertable, Serialize, Deserialize)]
+#[table_name = "club_tags"]
+pub struct ClubTag {
+    pub club_id: i32,
+    pub tag_id: i32,
+}
+
+//A facet value and how many clubs have it, used for tag autocomplete and list facets.
+#[derive(QueryableByName, Debug, Serialize, Deserialize, Clone)]
+pub struct FacetCount {
+    #[sql_type = "diesel::sql_types::Nullable<diesel::sql_types::Integer>"]
+    pub id: Option<i32>,
+    #[sql_type = "diesel::sql_types::Text"]
+    pub name: String,
+    #[sql_type = "diesel::sql_types::BigInt"]
+    pub count: i64,
+}
+
+impl Tag {
+    //Lowercases, trims and collapses whitespace so "Board  Games" and "board games" are the same tag.
+    pub fn normalize(raw: &str) -> std::result::Result<String, String> {
+        let normalized = raw.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+
+        if normalized.is_empty() {
+            Err("Tags can't be empty.".to_owned())
+        } else if normalized.chars().count() > MAX_TAG_LENGTH {
+            Err(format!("Tags can be at most {} characters long.", MAX_TAG_LENGTH))
+        } else if !normalized.chars().all(|c| c.is_alphanumeric() || c == ' ' || c == '-') {
+            Err(format!("The tag \"{}\" may only contain letters, numbers, spaces and dashes.", normalized))
+        } else {
+            Ok(normalized)
+        }
+    }
+
+    //Replaces every tag on the club with the given, already normalised, names.
+    pub fn set_for_club(conn: &PgConnection, req_club_id: &i32, names: &Vec<String>) -> QueryResult<Vec<String>> {
+        use crate::schema::tags::dsl::{tags, name};
+        use crate::schema::club_tags::dsl::{club_tags, club_id};
+
+        conn.transaction(|| {
+            diesel::delete(club_tags.filter(club_id.eq(req_club_id))).execute(conn)?;
+
+            let new_tags = names.iter().map(|tag| NewTag { name: tag }).collect::<Vec<_>>();
+            insert_into(tags)
+                .values(&new_tags)
+                .on_conflict(name)
+                .do_nothing()
+                .execute(conn)?;
+
+            let tag_ids = tags.filter(name.eq_any(names)).load::<Tag>(conn)?;
+            let new_club_tags = tag_ids.iter().map(|tag| ClubTag { club_id: *req_club_id, tag_id: tag.id }).collect::<Vec<_>>();
+            insert_into(club_tags)
+                .values(&new_club_tags)
+                .execute(conn)?;
+
+            let mut names = tag_ids.into_iter().map(|tag| tag.name).collect::<Vec<_>>();
+            names.sort();
+            Ok(names)
+        })
+    }
+
+    //Most used tags starting with the prefix.
+    pub fn autocomplete(conn: &PgConnection, prefix: &str, limit: i64) -> QueryResult<Vec<FacetCount>> {
+        use diesel::sql_types::{BigInt, Text};
+
+        let pattern = format!("{}%", prefix.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"));
+        diesel::sql_query("\
+            SELECT tags.id, tags.name, COUNT(club_tags.club_id) AS count FROM tags \
+            LEFT JOIN club_tags ON club_tags.tag_id = tags.id \
+            WHERE tags.name LIKE $1 \
+            GROUP BY tags.id \
+            ORDER BY count DESC, tags.name \
+            LIMIT $2")
+            .bind::<Text, _>(pattern)
+            .bind::<BigInt, _>(limit)
+            .load::<FacetCount>(conn)
+    }
+}
@@ -0,0 +1,215 @@
+use crate::prelude::*;
+use crate::schema::{terms, club_registrations};
+
+pub const REGISTRATION_PENDING: &str = "pending";
+pub const REGISTRATION_REGISTERED: &str = "registered";
+pub const REGISTRATION_LAPSED: &str = "lapsed";
+
+#[derive(Queryable, Serialize, Deserialize, Clone)]
+pub struct Term {
+    pub id: i32,
+    pub name: String,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[table_name = "terms"]
+pub struct NewTerm<'a> {
+    pub name: &'a str,
+    pub start_date: &'a DateTime<Utc>,
+    pub end_date: &'a DateTime<Utc>,
+}
+
+#[derive(Queryable, Serialize, Deserialize)]
+pub struct ClubRegistration {
+    pub id: i32,
+    pub club_id: i32,
+    pub term_id: i32,
+    pub status: String,
+    pub officer_ids: Vec<i32>,
+    pub submitted_by: Option<i32>,
+    pub submitted_at: DateTime<Utc>,
+    pub decided_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Insertable)]
+#[table_name = "club_registrations"]
+pub struct NewClubRegistration<'a> {
+    pub club_id: &'a i32,
+    pub term_id: &'a i32,
+    pub status: &'a str,
+    pub officer_ids: &'a Vec<i32>,
+    pub submitted_by: Option<&'a i32>,
+}
+
+impl Term {
+    pub fn get_all(conn: &PgConnection) -> QueryResult<Vec<Term>> {
+        use crate::schema::terms::dsl::{terms, start_date};
+        terms.order(start_date.desc()).load::<Term>(conn)
+    }
+
+    pub fn get_by_id(conn: &PgConnection, req_id: &i32) -> Option<Term> {
+        use crate::schema::terms::dsl::{terms, id};
+        terms.filter(id.eq(req_id)).first(conn).ok()
+    }
+
+    //The term running right now, if any.
+    pub fn current(conn: &PgConnection) -> QueryResult<Option<Term>> {
+        use crate::schema::terms::dsl::{terms, start_date, end_date};
+        let now = chrono::offset::Utc::now();
+        terms
+            .filter(start_date.le(now))
+            .filter(end_date.gt(now))
+            .order(start_date.desc())
+            .first::<Term>(conn)
+            .optional()
+    }
+
+    //The next term to start, which is what clubs re-register for.
+    pub fn upcoming(conn: &PgConnection) -> QueryResult<Option<Term>> {
+        use crate::schema::terms::dsl::{terms, start_date};
+        terms
+            .filter(start_date.gt(chrono::offset::Utc::now()))
+            .order(start_date.asc())
+            .first::<Term>(conn)
+            .optional()
+    }
+
+    //Once any term is defined clubs follow terms instead of the renewal period.
+    pub fn terms_in_use(conn: &PgConnection) -> QueryResult<bool> {
+        use crate::schema::terms::dsl::{terms, end_date};
+        let remaining = terms
+            .filter(end_date.gt(chrono::offset::Utc::now()))
+            .count()
+            .get_result::<i64>(conn)?;
+        Ok(remaining > 0)
+    }
+}
+
+impl ClubRegistration {
+    pub fn get_for_club(conn: &PgConnection, req_club_id: &i32) -> QueryResult<Vec<ClubRegistration>> {
+        use crate::schema::club_registrations::dsl::{club_registrations, club_id, submitted_at};
+        club_registrations
+            .filter(club_id.eq(req_club_id))
+            .order(submitted_at.desc())
+            .load::<ClubRegistration>(conn)
+    }
+
+    pub fn get_for_term(conn: &PgConnection, req_term_id: &i32, req_status: Option<&str>) -> QueryResult<Vec<ClubRegistration>> {
+        use crate::schema::club_registrations::dsl::{club_registrations, term_id, status, submitted_at};
+
+        let mut query = club_registrations
+            .filter(term_id.eq(req_term_id))
+            .order(submitted_at.asc())
+            .into_boxed();
+
+        if let Some(req_status) = req_status {
+            query = query.filter(status.eq(req_status.to_owned()));
+        }
+
+        query.load::<ClubRegistration>(conn)
+    }
+
+    /*
+    Records the head moderator's re-registration for a term. Submitting
+    again replaces the pending submission. The officers only take over
+    once an admin registers the club.
+    */
+    pub fn submit(conn: &PgConnection, req_club_id: &i32, term: &Term, submitter: &i32, officers: &Vec<i32>) -> QueryResult<ClubRegistration> {
+        use crate::schema::club_registrations::dsl::{club_registrations, club_id, term_id, status, officer_ids, submitted_by, submitted_at, decided_at};
+
+        insert_into(club_registrations)
+            .values(&NewClubRegistration {
+                club_id: req_club_id,
+                term_id: &term.id,
+                status: REGISTRATION_PENDING,
+                officer_ids: officers,
+                submitted_by: Some(submitter),
+            })
+            .on_conflict((club_id, term_id))
+            .do_update()
+            .set((
+                status.eq(REGISTRATION_PENDING),
+                officer_ids.eq(officers),
+                submitted_by.eq(Some(*submitter)),
+                submitted_at.eq(chrono::offset::Utc::now()),
+                decided_at.eq(None::<DateTime<Utc>>),
+            ))
+            .get_result::<ClubRegistration>(conn)
+    }
+
+    /*
+    An admin's decision. Registering a club carries it through to the
+    end of the term, so the lifecycle job archives it then unless it has
+    been registered for the next one. The officers listed in the
+    registration become the club's moderators, anyone else loses
+    moderator rights. Officers who left the club in the meantime are
+    skipped.
+    */
+    pub fn decide(conn: &PgConnection, req_club_id: &i32, term: &Term, decision: &str) -> QueryResult<ClubRegistration> {
+        use crate::schema::club_registrations::dsl::{club_registrations, club_id, term_id, status, decided_at};
+        use crate::schema::clubs::dsl::{clubs, expiry_date, expiry_warned_at, archived_at};
+        use crate::schema::club_members::dsl::{club_members, club_id as member_club_id, user_id, is_moderator};
+
+        conn.transaction(|| {
+            let registration = diesel::update(club_registrations)
+                .filter(club_id.eq(req_club_id))
+                .filter(term_id.eq(term.id))
+                .set((
+                    status.eq(decision),
+                    decided_at.eq(chrono::offset::Utc::now()),
+                ))
+                .get_result::<ClubRegistration>(conn)?;
+
+            if decision == REGISTRATION_REGISTERED {
+                let club = clubs.find(req_club_id).get_result::<Club>(conn)?;
+                let through = if club.expiry_date > term.end_date {club.expiry_date} else {term.end_date};
+                diesel::update(clubs.find(req_club_id))
+                    .set((
+                        expiry_date.eq(through),
+                        expiry_warned_at.eq(None::<DateTime<Utc>>),
+                        archived_at.eq(None::<DateTime<Utc>>),
+                    ))
+                    .execute(conn)?;
+
+                diesel::update(club_members)
+                    .filter(member_club_id.eq(req_club_id))
+                    .filter(is_moderator.eq("true"))
+                    .filter(user_id.ne_all(&registration.officer_ids))
+                    .set(is_moderator.eq("false"))
+                    .execute(conn)?;
+                diesel::update(club_members)
+                    .filter(member_club_id.eq(req_club_id))
+                    .filter(is_moderator.eq("false"))
+                    .filter(user_id.eq_any(&registration.officer_ids))
+                    .set(is_moderator.eq("true"))
+                    .execute(conn)?;
+            }
+
+            Ok(registration)
+        })
+    }
+
+    /*
+    Once a term has started every club without a registration for it
+    has lapsed, as has any submission still pending when the term ends.
+    */
+    pub fn lapse(conn: &PgConnection) -> QueryResult<()> {
+        diesel::sql_query("\
+            INSERT INTO club_registrations (club_id, term_id, status) \
+            SELECT clubs.id, terms.id, 'lapsed' FROM clubs CROSS JOIN terms \
+            WHERE terms.start_date <= NOW() AND terms.end_date > NOW() \
+            AND clubs.archived_at IS NULL AND clubs.deleted_at IS NULL \
+            ON CONFLICT (club_id, term_id) DO NOTHING")
+            .execute(conn)?;
+
+        diesel::sql_query("\
+            UPDATE club_registrations SET status = 'lapsed', decided_at = NOW() \
+            FROM terms WHERE terms.id = club_registrations.term_id \
+            AND terms.end_date <= NOW() AND club_registrations.status = 'pending'")
+            .execute(conn)?;
+
+        Ok(())
+    }
+}
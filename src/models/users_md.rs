@@ -9,14 +9,137 @@ pub struct User {
     pub first_name: String,
     pub last_name: String,
     pub is_admin: bool,
+    pub avatar_key: Option<String>,
+    //The Google picture avatar_key was fetched from.
+    pub avatar_source: Option<String>,
+    pub display_name: Option<String>,
+    pub pronouns: Option<String>,
+    pub bio: String,
+    pub major: Option<String>,
+    pub graduation_year: Option<i32>,
+    pub email_visibility: String,
+    pub display_name_visibility: String,
+    pub pronouns_visibility: String,
+    pub bio_visibility: String,
+    pub major_visibility: String,
+    pub graduation_year_visibility: String,
+}
+
+//Who gets to see a profile field.
+pub const VISIBILITY_EVERYONE: &str = "everyone";
+pub const VISIBILITY_CLUB_MATES: &str = "club_mates";
+pub const VISIBILITY_ONLY_ME: &str = "only_me";
+
+//Defined in the user_profiles migration.
+sql_function!(fn are_club_mates(a: diesel::sql_types::Integer, b: diesel::sql_types::Integer) -> diesel::sql_types::Bool);
+
+//How whoever is looking relates to the user they are looking at.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum Relation {
+    Guest,
+    Stranger,
+    ClubMate,
+    Myself,
+}
+
+impl Relation {
+    pub fn between(viewer: Option<i32>, user_id: i32, club_mates: bool) -> Self {
+        match viewer {
+            None => Relation::Guest,
+            Some(viewer) if viewer == user_id => Relation::Myself,
+            Some(_) if club_mates => Relation::ClubMate,
+            Some(_) => Relation::Stranger,
+        }
+    }
+
+    pub fn can_see(&self, visibility: &str) -> bool {
+        match (self, visibility) {
+            (Relation::Myself, _) => true,
+            (_, VISIBILITY_EVERYONE) => true,
+            (Relation::ClubMate, VISIBILITY_CLUB_MATES) => true,
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UserDetails{
-    pub email: String,
+    pub id: i32,
+    //Left out unless the user's settings allow it, and always for guests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    //Served by us, see services::avatars.
     pub picture: String,
     pub first_name: String,
     pub last_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pronouns: Option<String>,
+}
+
+//The fields UserDetails is made of, before visibility is applied.
+pub struct UserCard {
+    pub id: i32,
+    pub email: String,
+    pub email_visibility: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub avatar_key: Option<String>,
+    pub display_name: Option<String>,
+    pub display_name_visibility: String,
+    pub pronouns: Option<String>,
+    pub pronouns_visibility: String,
+}
+
+impl UserCard {
+    pub fn shown_to(self, relation: Relation) -> UserDetails {
+        let show_email = relation != Relation::Guest && relation.can_see(&self.email_visibility);
+        let show_display_name = relation.can_see(&self.display_name_visibility);
+        let show_pronouns = relation.can_see(&self.pronouns_visibility);
+
+        UserDetails {
+            picture: crate::services::avatars::user_avatar_url(self.id, &self.first_name, &self.last_name, self.avatar_key.as_deref()),
+            id: self.id,
+            email: Some(self.email).filter(|_| show_email),
+            first_name: self.first_name,
+            last_name: self.last_name,
+            display_name: self.display_name.filter(|_| show_display_name),
+            pronouns: self.pronouns.filter(|_| show_pronouns),
+        }
+    }
+}
+
+//A user's public profile, only with what the caller may see.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UserProfile {
+    #[serde(flatten)]
+    pub details: UserDetails,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bio: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub major: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub graduation_year: Option<i32>,
+}
+
+//Everything the user can change about their profile, for their settings page.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProfileSettings {
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub display_name: Option<String>,
+    pub pronouns: Option<String>,
+    pub bio: String,
+    pub major: Option<String>,
+    pub graduation_year: Option<i32>,
+    pub email_visibility: String,
+    pub display_name_visibility: String,
+    pub pronouns_visibility: String,
+    pub bio_visibility: String,
+    pub major_visibility: String,
+    pub graduation_year_visibility: String,
 }
 
 impl User{
@@ -40,28 +163,24 @@ impl User{
         result
     }
 
-    pub fn get_membership_status(self, conn: &PgConnection, club_id: &i32) -> MembershipStatus {
+    pub fn get_membership_status(self, conn: &PgConnection, club_id: &i32) -> QueryResult<MembershipStatus> {
         use crate::schema::club_members::dsl::{club_members, club_id as db_club_id, user_id};
         let club_id = club_id.clone();
         let relation = club_members
             .filter(db_club_id.eq(club_id))
             .filter(user_id.eq(self.id))
-            .limit(1).load::<ClubMember>(conn).unwrap();
-
-        if relation.len() == 1 {
-            if relation.get(0).unwrap().is_moderator == "head" {
-                MembershipStatus::Moderator(true)
-            } else if relation.get(0).unwrap().is_moderator == "true" {
-                MembershipStatus::Moderator(false)
-            } else {
-                MembershipStatus::Member
-            }
-        } else {
-            MembershipStatus::Unassociated
-        }
+            .first::<ClubMember>(conn)
+            .optional()?;
+
+        Ok(match relation {
+            Some(relation) if relation.is_moderator == "head" => MembershipStatus::Moderator(true),
+            Some(relation) if relation.is_moderator == "true" => MembershipStatus::Moderator(false),
+            Some(_) => MembershipStatus::Member,
+            None => MembershipStatus::Unassociated,
+        })
     }
 
-    pub async fn get_membership_status_async(self, db: &Db, club_id: &i32) -> MembershipStatus {
+    pub async fn get_membership_status_async(self, db: &Db, club_id: &i32) -> QueryResult<MembershipStatus> {
         let club_id = club_id.clone();
         let result = db.run(move |conn| {
             self.get_membership_status(conn, &club_id)
@@ -70,12 +189,71 @@ impl User{
         result
     }
 
-    pub fn to_user_details(&self) -> UserDetails{
-        UserDetails{
+    pub fn avatar_url(&self) -> String {
+        crate::services::avatars::user_avatar_url(self.id, &self.first_name, &self.last_name, self.avatar_key.as_deref())
+    }
+
+    //Whether the Google picture changed since it was last fetched.
+    pub fn needs_avatar_refresh(&self) -> bool {
+        !self.picture.is_empty() && self.avatar_source.as_deref() != Some(self.picture.as_str())
+    }
+
+    pub fn relation_to(&self, conn: &PgConnection, viewer: Option<i32>) -> QueryResult<Relation> {
+        let club_mates = match viewer {
+            Some(viewer) if viewer != self.id => diesel::select(are_club_mates(viewer, self.id)).get_result::<bool>(conn)?,
+            _ => false,
+        };
+
+        Ok(Relation::between(viewer, self.id, club_mates))
+    }
+
+    pub fn to_user_card(&self) -> UserCard {
+        UserCard {
+            id: self.id,
+            email: self.email.clone(),
+            email_visibility: self.email_visibility.clone(),
+            first_name: self.first_name.clone(),
+            last_name: self.last_name.clone(),
+            avatar_key: self.avatar_key.clone(),
+            display_name: self.display_name.clone(),
+            display_name_visibility: self.display_name_visibility.clone(),
+            pronouns: self.pronouns.clone(),
+            pronouns_visibility: self.pronouns_visibility.clone(),
+        }
+    }
+
+    //The user as the viewer, None for guests, gets to see them.
+    pub fn to_user_details(&self, conn: &PgConnection, viewer: Option<i32>) -> QueryResult<UserDetails> {
+        Ok(self.to_user_card().shown_to(self.relation_to(conn, viewer)?))
+    }
+
+    pub fn to_profile(&self, conn: &PgConnection, viewer: Option<i32>) -> QueryResult<UserProfile> {
+        let relation = self.relation_to(conn, viewer)?;
+
+        Ok(UserProfile {
+            details: self.to_user_card().shown_to(relation),
+            bio: Some(self.bio.clone()).filter(|bio| !bio.is_empty() && relation.can_see(&self.bio_visibility)),
+            major: self.major.clone().filter(|_| relation.can_see(&self.major_visibility)),
+            graduation_year: self.graduation_year.filter(|_| relation.can_see(&self.graduation_year_visibility)),
+        })
+    }
+
+    pub fn to_profile_settings(&self) -> ProfileSettings {
+        ProfileSettings {
             email: self.email.clone(),
-            picture: self.picture.clone(),
+            first_name: self.first_name.clone(),
             last_name: self.last_name.clone(),
-            first_name: self.first_name.clone()
+            display_name: self.display_name.clone(),
+            pronouns: self.pronouns.clone(),
+            bio: self.bio.clone(),
+            major: self.major.clone(),
+            graduation_year: self.graduation_year,
+            email_visibility: self.email_visibility.clone(),
+            display_name_visibility: self.display_name_visibility.clone(),
+            pronouns_visibility: self.pronouns_visibility.clone(),
+            bio_visibility: self.bio_visibility.clone(),
+            major_visibility: self.major_visibility.clone(),
+            graduation_year_visibility: self.graduation_year_visibility.clone(),
         }
     }
 }
@@ -2,20 +2,69 @@
 pub use crate::models::clubs_md::Club;
 pub use crate::models::clubs_md::NewClub;
 pub use crate::models::clubs_md::ClubDetails;
+pub use crate::models::clubs_md::ClubPage;
+pub use crate::models::clubs_md::ClubFacets;
+pub use crate::models::clubs_md::ClubQuery;
+pub use crate::models::clubs_md::ClubSort;
+pub use crate::models::clubs_md::MembershipFilter;
 pub use crate::models::users_md::User;
 pub use crate::models::users_md::UserDetails;
+pub use crate::models::users_md::UserCard;
+pub use crate::models::users_md::UserProfile;
+pub use crate::models::users_md::ProfileSettings;
+pub use crate::models::users_md::Relation;
 pub use crate::models::users_md::NewUser;
 pub use crate::models::users_md::Admin;
 pub use crate::models::club_members_md::MembershipStatus;
 pub use crate::models::club_members_md::ClubMember;
 pub use crate::models::club_members_md::NewClubMember;
+pub use crate::models::club_members_md::HeadlessClub;
+pub use crate::models::categories_md::Category;
+pub use crate::models::categories_md::NewCategory;
+pub use crate::models::tags_md::Tag;
+pub use crate::models::tags_md::ClubTag;
+pub use crate::models::tags_md::FacetCount;
+pub use crate::models::notifications_md::Notification;
+pub use crate::models::notifications_md::NewNotification;
+pub use crate::models::terms_md::Term;
+pub use crate::models::terms_md::NewTerm;
+pub use crate::models::terms_md::ClubRegistration;
+pub use crate::models::terms_md::NewClubRegistration;
+pub use crate::models::club_revisions_md::ClubRevision;
+pub use crate::models::club_revisions_md::NewClubRevision;
+pub use crate::models::club_revisions_md::ClubRevisionSummary;
+pub use crate::models::club_revisions_md::ClubRevisionDiff;
+pub use crate::models::club_attachments_md::ClubAttachment;
+pub use crate::models::club_attachments_md::NewClubAttachment;
+pub use crate::models::club_attachments_md::AttachmentDetails;
+pub use crate::models::club_attachments_md::AttachmentUsage;
+pub use crate::models::club_reports_md::ClubReport;
+pub use crate::models::club_reports_md::NewClubReport;
+pub use crate::models::club_reports_md::ReportGroup;
+pub use crate::models::blocklist_md::BlocklistRule;
+pub use crate::models::blocklist_md::NewBlocklistRule;
+pub use crate::models::audit_log_md::AuditEntry;
+pub use crate::models::audit_log_md::NewAuditEntry;
+pub use crate::models::audit_log_md::AuditQuery;
+pub use crate::models::audit_log_md::AuditPage;
+pub use crate::models::deleted_accounts_md::DeletedAccount;
 pub use crate::Db;
 pub use crate::Result;
 pub use crate::schema;
 pub use crate::UserAuthenticator;
-pub use crate::JsonError;
+pub use crate::errors::ApiError;
+pub use crate::errors::FieldError;
+pub use validator::Validate;
+pub use crate::IfMatch;
+pub use crate::ClientIp;
+pub use crate::Versioned;
 pub use crate::GoogleClaims;
 pub use crate::GoogleKeysState;
+pub use crate::services::events::EventBus;
+pub use crate::services::events::ChangeEvent;
+pub use crate::services::lifecycle::LifecycleConfig;
+pub use crate::services::logos::ClubLogo;
+pub use crate::services::storage::Storage;
 //Self SB imports
 
 
@@ -42,7 +91,7 @@ pub use rocket::form::Form;
 pub use rocket::response::Redirect;
 pub use rocket::State;
 pub use rocket::data::Capped;
-pub use rocket::data::{Limits, ToByteUnit};
+pub use rocket::data::{Data, Limits, ToByteUnit};
 pub use rocket::fs::TempFile;
 //Diesel
 pub use diesel::prelude::*;
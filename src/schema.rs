@@ -1,3 +1,57 @@
+table! {
+    audit_log (id) {
+        id -> Int4,
+        actor_id -> Nullable<Int4>,
+        action -> Text,
+        target_type -> Text,
+        target_id -> Nullable<Int4>,
+        club_id -> Nullable<Int4>,
+        before_snapshot -> Nullable<Text>,
+        after_snapshot -> Nullable<Text>,
+        ip -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    blocklist_rules (id) {
+        id -> Int4,
+        pattern -> Text,
+        match_type -> Text,
+        severity -> Text,
+        applies_to -> Text,
+        enabled -> Bool,
+        note -> Text,
+        created_by -> Nullable<Int4>,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    categories (id) {
+        id -> Int4,
+        name -> Text,
+    }
+}
+
+table! {
+    club_attachments (id) {
+        id -> Int4,
+        club_id -> Int4,
+        kind -> Text,
+        storage_key -> Text,
+        thumbnail_key -> Nullable<Text>,
+        file_name -> Text,
+        content_type -> Text,
+        size_bytes -> Int8,
+        caption -> Text,
+        position -> Int4,
+        visibility -> Text,
+        uploaded_by -> Nullable<Int4>,
+        created_at -> Timestamptz,
+    }
+}
+
 table! {
     club_members (id) {
         id -> Int4,
@@ -14,6 +68,105 @@ table! {
         body -> Text,
         publish_date -> Timestamptz,
         expiry_date -> Timestamptz,
+        is_public -> Bool,
+        category_id -> Nullable<Int4>,
+        renewal_days -> Nullable<Int4>,
+        expiry_warned_at -> Nullable<Timestamptz>,
+        archived_at -> Nullable<Timestamptz>,
+        approval_status -> Text,
+        review_note -> Nullable<Text>,
+        reviewed_at -> Nullable<Timestamptz>,
+        is_verified -> Bool,
+        deleted_at -> Nullable<Timestamptz>,
+        deleted_by -> Nullable<Int4>,
+        version -> Int4,
+        body_html -> Text,
+        logo_key -> Nullable<Text>,
+        photo_upload_role -> Text,
+        document_upload_role -> Text,
+        storage_quota_bytes -> Nullable<Int8>,
+    }
+}
+
+table! {
+    club_registrations (id) {
+        id -> Int4,
+        club_id -> Int4,
+        term_id -> Int4,
+        status -> Text,
+        officer_ids -> Array<Int4>,
+        submitted_by -> Nullable<Int4>,
+        submitted_at -> Timestamptz,
+        decided_at -> Nullable<Timestamptz>,
+    }
+}
+
+table! {
+    club_reports (id) {
+        id -> Int4,
+        club_id -> Int4,
+        reporter_id -> Nullable<Int4>,
+        reason -> Text,
+        details -> Text,
+        status -> Text,
+        resolution_note -> Nullable<Text>,
+        resolved_by -> Nullable<Int4>,
+        resolved_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    club_revisions (id) {
+        id -> Int4,
+        club_id -> Int4,
+        name -> Text,
+        body -> Text,
+        author_id -> Nullable<Int4>,
+        reverted_from -> Nullable<Int4>,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    club_tags (club_id, tag_id) {
+        club_id -> Int4,
+        tag_id -> Int4,
+    }
+}
+
+table! {
+    deleted_accounts (email_hash) {
+        email_hash -> Text,
+        deleted_at -> Timestamptz,
+    }
+}
+
+table! {
+    notifications (id) {
+        id -> Int4,
+        user_id -> Int4,
+        club_id -> Nullable<Int4>,
+        kind -> Text,
+        message -> Text,
+        created_at -> Timestamptz,
+        read_at -> Nullable<Timestamptz>,
+    }
+}
+
+table! {
+    tags (id) {
+        id -> Int4,
+        name -> Text,
+    }
+}
+
+table! {
+    terms (id) {
+        id -> Int4,
+        name -> Text,
+        start_date -> Timestamptz,
+        end_date -> Timestamptz,
     }
 }
 
@@ -25,14 +178,54 @@ table! {
         first_name -> Text,
         last_name -> Text,
         is_admin -> Bool,
+        avatar_key -> Nullable<Text>,
+        avatar_source -> Nullable<Text>,
+        display_name -> Nullable<Text>,
+        pronouns -> Nullable<Text>,
+        bio -> Text,
+        major -> Nullable<Text>,
+        graduation_year -> Nullable<Int4>,
+        email_visibility -> Text,
+        display_name_visibility -> Text,
+        pronouns_visibility -> Text,
+        bio_visibility -> Text,
+        major_visibility -> Text,
+        graduation_year_visibility -> Text,
     }
 }
 
+joinable!(audit_log -> users (actor_id));
+joinable!(blocklist_rules -> users (created_by));
+joinable!(club_attachments -> clubs (club_id));
+joinable!(club_attachments -> users (uploaded_by));
 joinable!(club_members -> clubs (club_id));
 joinable!(club_members -> users (user_id));
+joinable!(club_registrations -> clubs (club_id));
+joinable!(club_registrations -> terms (term_id));
+joinable!(club_registrations -> users (submitted_by));
+joinable!(club_reports -> clubs (club_id));
+joinable!(club_revisions -> clubs (club_id));
+joinable!(club_revisions -> users (author_id));
+joinable!(club_tags -> clubs (club_id));
+joinable!(club_tags -> tags (tag_id));
+joinable!(clubs -> categories (category_id));
+joinable!(notifications -> clubs (club_id));
+joinable!(notifications -> users (user_id));
 
 allow_tables_to_appear_in_same_query!(
+    audit_log,
+    blocklist_rules,
+    categories,
+    club_attachments,
     club_members,
+    club_registrations,
+    club_reports,
+    club_revisions,
+    club_tags,
     clubs,
+    deleted_accounts,
+    notifications,
+    tags,
+    terms,
     users,
 );
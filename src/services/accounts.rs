@@ -0,0 +1,279 @@
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+/*
+Self service for what we store about a user. An export is a zip with
+everything in account.json plus the files the user uploaded. Deleting
+an account removes the user, their memberships and notifications, and
+leaves what they contributed to clubs (edits, attachments, registrations)
+in place without their name on it. The audit log keeps their id, it
+can't be changed. There are no events or posts yet, they belong in both
+once there are.
+*/
+pub const EXPORT_JSON: &str = "account.json";
+
+#[derive(Serialize)]
+pub struct ExportedAccount {
+    pub id: i32,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    //Google's URL, as it was last signed in with.
+    pub google_picture: String,
+    pub is_admin: bool,
+}
+
+#[derive(Serialize)]
+pub struct ExportedMembership {
+    pub club_id: i32,
+    pub club_name: String,
+    //head, moderator or member
+    pub role: String,
+}
+
+#[derive(Serialize)]
+pub struct ExportedRevision {
+    pub id: i32,
+    pub club_id: i32,
+    pub name: String,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct ExportedAttachment {
+    pub id: i32,
+    pub club_id: i32,
+    pub kind: String,
+    pub file_name: String,
+    pub caption: String,
+    pub visibility: String,
+    pub created_at: DateTime<Utc>,
+    //Where the file is in the archive.
+    pub path: String,
+}
+
+#[derive(Serialize)]
+pub struct AccountExport {
+    pub exported_at: DateTime<Utc>,
+    pub account: ExportedAccount,
+    pub profile: ProfileSettings,
+    //Also where the avatar is in the archive, if there is one.
+    pub avatar: Option<String>,
+    pub memberships: Vec<ExportedMembership>,
+    pub club_edits: Vec<ExportedRevision>,
+    pub attachments: Vec<ExportedAttachment>,
+    //Registrations the user submitted or is an officer in.
+    pub registrations: Vec<ClubRegistration>,
+    pub reports: Vec<ClubReport>,
+    pub notifications: Vec<Notification>,
+}
+
+//What the archive holds besides account.json, as archive path and storage key.
+pub struct ExportFiles {
+    pub export: AccountExport,
+    pub files: Vec<(String, String)>,
+}
+
+pub fn collect(conn: &PgConnection, user: &User) -> QueryResult<ExportFiles> {
+    use crate::schema::club_members::dsl::{club_members, user_id as member_user_id};
+    use crate::schema::clubs::dsl::{clubs, name as clubs_name};
+    use crate::schema::club_revisions::dsl::{club_revisions, author_id, id as revisions_id};
+    use crate::schema::club_attachments::dsl::{club_attachments, uploaded_by, id as attachments_id};
+    use crate::schema::club_registrations::dsl::{club_registrations, submitted_by, officer_ids, id as registrations_id};
+    use crate::schema::notifications::dsl::{notifications, user_id as notification_user_id, id as notifications_id};
+    use crate::schema::club_reports::dsl::{club_reports, reporter_id, id as reports_id};
+
+    let memberships = club_members
+        .inner_join(clubs)
+        .filter(member_user_id.eq(user.id))
+        .order(clubs_name.asc())
+        .load::<(ClubMember, Club)>(conn)?
+        .into_iter()
+        .map(|(member, club)| ExportedMembership {
+            club_id: club.id,
+            club_name: club.name,
+            role: match member.is_moderator.as_str() {
+                "head" => "head",
+                "true" => "moderator",
+                _ => "member",
+            }.to_owned(),
+        })
+        .collect();
+
+    let club_edits = club_revisions
+        .filter(author_id.eq(user.id))
+        .order(revisions_id.asc())
+        .load::<ClubRevision>(conn)?
+        .into_iter()
+        .map(|revision| ExportedRevision {
+            id: revision.id,
+            club_id: revision.club_id,
+            name: revision.name,
+            body: revision.body,
+            created_at: revision.created_at,
+        })
+        .collect();
+
+    let mut files = Vec::new();
+    let attachments = club_attachments
+        .filter(uploaded_by.eq(user.id))
+        .order(attachments_id.asc())
+        .load::<ClubAttachment>(conn)?
+        .into_iter()
+        .map(|attachment| {
+            let path = format!("attachments/{}-{}", attachment.id, attachment.file_name);
+            files.push((path.clone(), attachment.storage_key.clone()));
+
+            ExportedAttachment {
+                id: attachment.id,
+                club_id: attachment.club_id,
+                kind: attachment.kind,
+                file_name: attachment.file_name,
+                caption: attachment.caption,
+                visibility: attachment.visibility,
+                created_at: attachment.created_at,
+                path,
+            }
+        })
+        .collect();
+
+    let avatar = user.avatar_key.as_ref().map(|avatar_key| {
+        let path = format!("avatar.{}", avatar_key.rsplit('.').next().unwrap_or("png"));
+        files.push((path.clone(), avatar_key.clone()));
+        path
+    });
+
+    let registrations = club_registrations
+        .filter(submitted_by.eq(user.id).or(officer_ids.contains(vec![user.id])))
+        .order(registrations_id.asc())
+        .load::<ClubRegistration>(conn)?;
+
+    let reports = club_reports
+        .filter(reporter_id.eq(user.id))
+        .order(reports_id.asc())
+        .load::<ClubReport>(conn)?;
+
+    let user_notifications = notifications
+        .filter(notification_user_id.eq(user.id))
+        .order(notifications_id.asc())
+        .load::<Notification>(conn)?;
+
+    Ok(ExportFiles {
+        export: AccountExport {
+            exported_at: chrono::offset::Utc::now(),
+            account: ExportedAccount {
+                id: user.id,
+                email: user.email.clone(),
+                first_name: user.first_name.clone(),
+                last_name: user.last_name.clone(),
+                google_picture: user.picture.clone(),
+                is_admin: user.is_admin,
+            },
+            profile: user.to_profile_settings(),
+            avatar,
+            memberships,
+            club_edits,
+            attachments,
+            registrations,
+            reports,
+            notifications: user_notifications,
+        },
+        files,
+    })
+}
+
+//Zips account.json and the files, which are (archive path, bytes). CPU heavy, run it on a blocking thread.
+pub fn archive(export: &AccountExport, files: Vec<(String, Vec<u8>)>) -> io::Result<Vec<u8>> {
+    let to_io = |e: zip::result::ZipError| io::Error::new(io::ErrorKind::Other, e);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut writer = ZipWriter::new(io::Cursor::new(Vec::new()));
+
+    writer.start_file(EXPORT_JSON, options).map_err(to_io)?;
+    rocket::serde::json::serde_json::to_writer_pretty(&mut writer, export).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+
+    for (path, bytes) in files {
+        //Stored files are already compressed more often than not.
+        writer.start_file(path, options.compression_method(CompressionMethod::Stored)).map_err(to_io)?;
+        writer.write_all(&bytes)?;
+    }
+
+    Ok(writer.finish().map_err(to_io)?.into_inner())
+}
+
+/*
+Deletes the user. Clubs they head have to be handed over first, to the
+member given for the club in successors, the same way appoint does it.
+Clubs nobody else is in are deleted along with the account. Fails with
+a field error per club still missing a successor, in which case nothing
+changes. Returns the storage key of the avatar when nobody else uses it.
+The deletion is recorded so sign ins from before it can't recreate the
+account, see DeletedAccount.
+*/
+pub fn delete(conn: &PgConnection, user: &User, successors: &HashMap<i32, i32>) -> std::result::Result<Option<String>, ApiError> {
+    use crate::schema::club_members::dsl::{club_members, user_id as member_user_id, club_id as member_club_id, is_moderator};
+    use crate::schema::clubs::dsl::{clubs, deleted_at};
+    use crate::schema::users::dsl::{users, avatar_key};
+
+    conn.transaction(|| {
+        let headed = club_members
+            .inner_join(clubs)
+            .filter(member_user_id.eq(user.id))
+            .filter(is_moderator.eq("head"))
+            .select(crate::schema::clubs::all_columns)
+            .load::<Club>(conn)?;
+
+        let mut missing = Vec::new();
+        for club in headed {
+            let others = club_members
+                .filter(member_club_id.eq(club.id))
+                .filter(member_user_id.ne(user.id))
+                .count()
+                .get_result::<i64>(conn)?;
+
+            match successors.get(&club.id) {
+                _ if others == 0 => {
+                    if club.deleted_at.is_none() {
+                        diesel::update(clubs.find(club.id))
+                            .set(deleted_at.eq(chrono::offset::Utc::now()))
+                            .execute(conn)?;
+                    }
+                },
+                Some(successor_id) if *successor_id != user.id => {
+                    let successor = User::get_by_id(conn, successor_id)
+                        .ok_or(ApiError::NotFound("User does not exist.".to_owned()))?;
+                    ClubMember::hand_over(conn, &club.id, successor)?;
+                },
+                _ => missing.push(FieldError::new(
+                    &format!("successors.{}", club.id),
+                    "head_of_club",
+                    &format!("You head {}, hand it over to another member first.", club.name),
+                )),
+            }
+        }
+
+        if !missing.is_empty() {
+            return Err(ApiError::Validation(missing))
+        }
+
+        diesel::sql_query("UPDATE club_registrations SET officer_ids = array_remove(officer_ids, $1) WHERE $1 = ANY(officer_ids)")
+            .bind::<diesel::sql_types::Integer, _>(user.id)
+            .execute(conn)?;
+
+        //Memberships and notifications go with the row, everything else keeps a NULL author.
+        diesel::delete(users.find(user.id)).execute(conn)?;
+        DeletedAccount::record(conn, &user.email)?;
+
+        let unused_avatar = match &user.avatar_key {
+            Some(key) => {
+                let shared = users.filter(avatar_key.eq(key)).count().get_result::<i64>(conn)?;
+                if shared == 0 { Some(key.clone()) } else { None }
+            },
+            None => None,
+        };
+
+        Ok(unused_avatar)
+    })
+}
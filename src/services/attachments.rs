@@ -0,0 +1,135 @@
+use crate::prelude::*;
+use crate::models::club_attachments_md::{KIND_DOCUMENT, KIND_PHOTO};
+use crate::services::logos;
+use crate::services::storage::content_key;
+use image::{codecs::jpeg::JpegEncoder, imageops::FilterType, DynamicImage};
+use std::io;
+
+/*
+Turns uploads into what gets stored. Photos go through the same checks
+as logos, are scaled down and re-encoded as JPEG, which also drops their
+metadata, and get a thumbnail for the gallery. Documents are stored as
+uploaded once their bytes match one of the types in DOCUMENT_TYPES.
+*/
+pub const PHOTO_DIMENSION: u32 = 2048;
+//Attachments are only served through the attachment routes, which check their visibility, never as plain assets.
+pub const ATTACHMENT_PREFIXES: [&str; 2] = ["photos", "documents"];
+pub const PHOTO_THUMBNAIL_DIMENSION: u32 = 480;
+const JPEG_QUALITY: u8 = 85;
+
+//Content type, extension and a check of the bytes.
+const DOCUMENT_TYPES: [(&str, &str, fn(&[u8]) -> bool); 5] = [
+    ("application/pdf", "pdf", is_pdf),
+    ("text/plain", "txt", is_text),
+    ("text/markdown", "md", is_text),
+    ("application/vnd.openxmlformats-officedocument.wordprocessingml.document", "docx", is_zip),
+    ("application/vnd.oasis.opendocument.text", "odt", is_zip),
+];
+
+pub fn is_attachment_key(key: &str) -> bool {
+    ATTACHMENT_PREFIXES.iter().any(|prefix| key.split('/').next() == Some(*prefix))
+}
+
+fn is_pdf(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"%PDF-")
+}
+
+fn is_zip(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"PK\x03\x04")
+}
+
+fn is_text(bytes: &[u8]) -> bool {
+    std::str::from_utf8(bytes).map(|text| !text.contains('\0')).unwrap_or(false)
+}
+
+pub struct StoredObject {
+    pub key: String,
+    pub bytes: Vec<u8>,
+    pub content_type: String,
+}
+
+pub struct ProcessedUpload {
+    pub file: StoredObject,
+    pub thumbnail: Option<StoredObject>,
+    pub extension: &'static str,
+}
+
+fn encode_jpeg(image: &DynamicImage) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    JpegEncoder::new_with_quality(&mut bytes, JPEG_QUALITY)
+        .encode_image(&DynamicImage::ImageRgb8(image.to_rgb8()))
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    Ok(bytes)
+}
+
+fn process_photo(bytes: &[u8], declared: Option<&ContentType>) -> std::result::Result<ProcessedUpload, ApiError> {
+    let image = logos::decode(bytes, declared)?;
+    let encoding_failed = |e: io::Error| {
+        eprintln!("Failed to encode a photo, {:?}", e);
+        ApiError::Internal
+    };
+
+    let scaled = if image.width() > PHOTO_DIMENSION || image.height() > PHOTO_DIMENSION {
+        image.resize(PHOTO_DIMENSION, PHOTO_DIMENSION, FilterType::Lanczos3)
+    } else {
+        image.clone()
+    };
+    let photo = encode_jpeg(&scaled).map_err(encoding_failed)?;
+    let thumbnail = encode_jpeg(&image.thumbnail(PHOTO_THUMBNAIL_DIMENSION, PHOTO_THUMBNAIL_DIMENSION)).map_err(encoding_failed)?;
+
+    Ok(ProcessedUpload {
+        file: StoredObject {
+            key: content_key(ATTACHMENT_PREFIXES[0], &photo, "jpg"),
+            bytes: photo,
+            content_type: "image/jpeg".to_owned(),
+        },
+        thumbnail: Some(StoredObject {
+            key: content_key(ATTACHMENT_PREFIXES[0], &thumbnail, "jpg"),
+            bytes: thumbnail,
+            content_type: "image/jpeg".to_owned(),
+        }),
+        extension: "jpg",
+    })
+}
+
+fn process_document(bytes: Vec<u8>, declared: Option<&ContentType>) -> std::result::Result<ProcessedUpload, ApiError> {
+    let declared = declared.map(|declared| format!("{}/{}", declared.top(), declared.sub()).to_lowercase());
+    let accepted = DOCUMENT_TYPES.iter().find(|(content_type, _, _)| declared.as_deref() == Some(*content_type));
+
+    match accepted {
+        Some((content_type, extension, check)) if check(&bytes) => Ok(ProcessedUpload {
+            file: StoredObject {
+                key: content_key(ATTACHMENT_PREFIXES[1], &bytes, extension),
+                bytes: bytes,
+                content_type: content_type.to_string(),
+            },
+            thumbnail: None,
+            extension: *extension,
+        }),
+        Some(_) => Err(ApiError::UnsupportedMediaType("The document isn't the kind of file its content type says.".to_owned())),
+        None => Err(ApiError::UnsupportedMediaType("Documents have to be PDF, plain text, markdown, Word or OpenDocument files.".to_owned())),
+    }
+}
+
+//Checks an upload of the given kind and prepares it for storage. CPU heavy for photos, run it on a blocking thread.
+pub fn process(kind: &str, bytes: Vec<u8>, declared: Option<&ContentType>) -> std::result::Result<ProcessedUpload, ApiError> {
+    match kind {
+        KIND_PHOTO => process_photo(&bytes, declared),
+        KIND_DOCUMENT => process_document(bytes, declared),
+        _ => Err(ApiError::BadRequest("Attachments are either photos or documents.".to_owned())),
+    }
+}
+
+//Rocket keeps small uploads in memory and bigger ones on disk, this reads either.
+pub async fn read_temp_file(file: &mut TempFile<'_>) -> io::Result<Vec<u8>> {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or(0);
+    let path = std::env::temp_dir().join(format!("saturn-upload-{}-{}", std::process::id(), nanos));
+
+    file.move_copy_to(&path).await?;
+    let bytes = rocket::tokio::fs::read(&path).await;
+    let _res = rocket::tokio::fs::remove_file(&path).await;
+    bytes
+}
@@ -0,0 +1,173 @@
+use crate::prelude::*;
+use crate::services::logos;
+use crate::services::storage::{content_key, Storage};
+use sha2::{Digest, Sha256};
+use std::io;
+
+/*
+Clubs without a logo get a generated one, their initials on a colour
+seeded by the club's id. The avatar is addressed by a hash of its SVG
+like stored assets are by their content, so it can be cached just as
+long and a renamed club gets a new URL. Users get the same from their
+names until their Google picture has been copied into storage, see
+refresh_user_avatar.
+*/
+pub const AVATAR_PREFIX: &str = "avatars/clubs/";
+pub const USER_AVATAR_PREFIX: &str = "avatars/users/";
+//Where copies of Google pictures are stored.
+pub const PICTURE_PREFIX: &str = "avatars/pictures";
+pub const PICTURE_SIZE: u32 = 256;
+pub const MAX_PICTURE_BYTES: usize = 5 * 1024 * 1024;
+
+pub struct Avatar {
+    //Storage style key, served under /assets like stored assets.
+    pub key: String,
+    pub svg: String,
+}
+
+//Up to two initials from the first words of the name that start with a letter or digit.
+fn initials(name: &str) -> String {
+    let initials = name
+        .split_whitespace()
+        .filter_map(|word| word.chars().find(|c| c.is_alphanumeric()))
+        .take(2)
+        .flat_map(char::to_uppercase)
+        .collect::<String>();
+
+    if initials.is_empty() { "#".to_owned() } else { initials }
+}
+
+//Spreads consecutive ids around the colour wheel.
+fn hue(club_id: i32) -> u32 {
+    (club_id as u32).wrapping_mul(2654435761) % 360
+}
+
+fn initials_avatar(prefix: &str, id: i32, name: &str) -> Avatar {
+    let svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"256\" height=\"256\" viewBox=\"0 0 256 256\">\
+        <rect width=\"256\" height=\"256\" fill=\"hsl({}, 55%, 45%)\"/>\
+        <text x=\"50%\" y=\"50%\" dy=\".35em\" text-anchor=\"middle\" font-family=\"sans-serif\" font-size=\"112\" font-weight=\"600\" fill=\"#ffffff\">{}</text>\
+        </svg>",
+        hue(id), initials(name)
+    );
+    let digest = hex::encode(Sha256::digest(svg.as_bytes()));
+
+    Avatar {
+        key: format!("{}{}/{}.svg", prefix, id, &digest[..16]),
+        svg,
+    }
+}
+
+pub fn club_avatar(club_id: i32, name: &str) -> Avatar {
+    initials_avatar(AVATAR_PREFIX, club_id, name)
+}
+
+pub fn user_avatar(user_id: i32, first_name: &str, last_name: &str) -> Avatar {
+    initials_avatar(USER_AVATAR_PREFIX, user_id, &format!("{} {}", first_name, last_name))
+}
+
+//What clients get as a user's picture, never Google's URL so viewers don't load anything from Google.
+pub fn user_avatar_url(user_id: i32, first_name: &str, last_name: &str, avatar_key: Option<&str>) -> String {
+    match avatar_key {
+        Some(avatar_key) => format!("/assets/{}", avatar_key),
+        None => format!("/assets/{}", user_avatar(user_id, first_name, last_name).key),
+    }
+}
+
+fn id_from_key(prefix: &str, key: &str) -> Option<i32> {
+    key.strip_prefix(prefix)?
+        .split('/')
+        .next()?
+        .parse()
+        .ok()
+}
+
+//The club an avatar key belongs to, None for any other key.
+pub fn club_id_from_key(key: &str) -> Option<i32> {
+    id_from_key(AVATAR_PREFIX, key)
+}
+
+//Likewise for generated user avatars.
+pub fn user_id_from_key(key: &str) -> Option<i32> {
+    id_from_key(USER_AVATAR_PREFIX, key)
+}
+
+fn fetch_failed<E: std::fmt::Debug>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, format!("{:?}", e))
+}
+
+//Downloads a picture, scales it to PICTURE_SIZE and stores it as PNG, which also drops its metadata.
+async fn fetch_picture(storage: &Storage, url: &str) -> io::Result<String> {
+    if !url.starts_with("https://") {
+        return Err(fetch_failed("Only https pictures are fetched"))
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .map_err(fetch_failed)?;
+    let mut response = client.get(url).send().await
+        .and_then(|response| response.error_for_status())
+        .map_err(fetch_failed)?;
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(fetch_failed)? {
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() > MAX_PICTURE_BYTES {
+            return Err(fetch_failed("The picture is too large"))
+        }
+    }
+
+    let png = rocket::tokio::task::spawn_blocking(move || {
+        let image = logos::decode(&bytes, None).map_err(fetch_failed)?;
+        logos::encode_png(&image.resize_to_fill(PICTURE_SIZE, PICTURE_SIZE, image::imageops::FilterType::Lanczos3))
+    }).await.map_err(fetch_failed)??;
+
+    let key = content_key(PICTURE_PREFIX, &png, "png");
+    storage.put(&key, png, "image/png").await?;
+    Ok(key)
+}
+
+/*
+Copies the user's Google picture into storage. Started by the User guard
+whenever the picture differs from avatar_source, which is claimed first
+so concurrent requests fetch it only once. A picture that can't be
+fetched leaves the user with their generated avatar until it changes.
+*/
+pub async fn refresh_user_avatar(db: Db, storage: Storage, user_id: i32, picture: String) {
+    use crate::schema::users::dsl::{users, avatar_key, avatar_source};
+
+    let claimed_picture = picture.clone();
+    let claimed = db.run(move |conn| {
+        diesel::update(users.find(user_id))
+            .filter(avatar_source.is_distinct_from(&claimed_picture))
+            .set(avatar_source.eq(&claimed_picture))
+            .execute(conn)
+    }).await;
+
+    match claimed {
+        Ok(0) => return,
+        Ok(_) => (),
+        Err(e) => return eprintln!("Failed to claim the picture of user {}, {:?}", user_id, e),
+    }
+
+    let key = match fetch_picture(&storage, &picture).await {
+        Ok(key) => Some(key),
+        Err(e) => {
+            eprintln!("Failed to fetch the picture of user {}, {:?}", user_id, e);
+            None
+        }
+    };
+
+    //Skipped if the picture changed again in the meantime, the newer fetch wins.
+    let result = db.run(move |conn| {
+        diesel::update(users.find(user_id))
+            .filter(avatar_source.eq(&picture))
+            .set(avatar_key.eq(key))
+            .execute(conn)
+    }).await;
+
+    if let Err(e) = result {
+        eprintln!("Failed to save the picture of user {}, {:?}", user_id, e);
+    }
+}
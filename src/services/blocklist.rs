@@ -0,0 +1,199 @@
+use crate::prelude::*;
+use crate::models::blocklist_md::*;
+use crate::validation::normalize_name;
+use regex::{Regex, RegexBuilder};
+
+/*
+Screens club names and descriptions against the admins' blocklist.
+Matching ignores case and runs of whitespace. exact rules match a whole
+field, so "Bad Club" blocks that name and nothing else, word rules match
+whole words anywhere, and regex rules are searched for anywhere in the
+field. The regex crate runs in linear time so no rule can stall a
+request, compiled patterns are capped in size on top of that.
+*/
+const MAX_REGEX_SIZE: usize = 1 << 20;
+pub const MAX_PATTERN_LENGTH: usize = 500;
+
+#[derive(Serialize)]
+pub struct RuleMatch {
+    //name or body
+    pub field: &'static str,
+    //The text the rule matched.
+    pub matched: String,
+}
+
+//A club a rule catches, for trying rules out before enabling them.
+#[derive(Serialize)]
+pub struct ClubMatch {
+    pub club_id: i32,
+    pub name: String,
+    pub matches: Vec<RuleMatch>,
+}
+
+pub struct CompiledRule {
+    pub applies_to: String,
+    regex: Regex,
+}
+
+//What the enabled rules make of a submission.
+#[derive(Default)]
+pub struct Screening {
+    pub rejected: Vec<FieldError>,
+    //Ids of the review rules that matched.
+    pub review: Vec<i32>,
+}
+
+fn compile(pattern: &str, match_type: &str) -> std::result::Result<Regex, FieldError> {
+    let source = match match_type {
+        MATCH_EXACT => format!(r"^{}$", regex::escape(&normalize_name(pattern))),
+        //Not \b, so patterns starting or ending with punctuation still work.
+        MATCH_WORD => format!(r"(?:^|\W){}(?:\W|$)", regex::escape(&normalize_name(pattern)).replace(' ', r"\s+")),
+        MATCH_REGEX => pattern.to_owned(),
+        _ => return Err(FieldError::new("match_type", "invalid", "Rules match exact, word or regex.")),
+    };
+
+    RegexBuilder::new(&source)
+        .case_insensitive(true)
+        .size_limit(MAX_REGEX_SIZE)
+        .build()
+        .map_err(|e| FieldError::new("pattern", "invalid", &format!("The pattern can't be used: {}", e)))
+}
+
+//Everything wrong with a rule an admin is saving or testing, in one go.
+pub fn validate_rule(pattern: &str, match_type: &str, severity: &str, applies_to: &str) -> Result<CompiledRule> {
+    let mut errors = Vec::new();
+
+    if pattern.trim().is_empty() {
+        errors.push(FieldError::new("pattern", "required", "The pattern can't be empty."));
+    } else if pattern.chars().count() > MAX_PATTERN_LENGTH {
+        errors.push(FieldError::new("pattern", "length", &format!("Patterns can be at most {} characters long.", MAX_PATTERN_LENGTH)));
+    }
+    if severity != SEVERITY_REJECT && severity != SEVERITY_REVIEW {
+        errors.push(FieldError::new("severity", "invalid", "Matches are either rejected or sent for review."));
+    }
+    if applies_to != APPLIES_TO_NAME && applies_to != APPLIES_TO_BODY && applies_to != APPLIES_TO_BOTH {
+        errors.push(FieldError::new("applies_to", "invalid", "Rules apply to the name, the body or both."));
+    }
+
+    let regex = match compile(pattern, match_type) {
+        Ok(regex) => Some(regex),
+        Err(error) => {
+            errors.push(error);
+            None
+        }
+    };
+
+    match regex {
+        Some(regex) if errors.is_empty() => Ok(CompiledRule {
+            applies_to: applies_to.to_owned(),
+            regex: regex,
+        }),
+        _ => Err(ApiError::Validation(errors)),
+    }
+}
+
+impl CompiledRule {
+    pub fn from_rule(rule: &BlocklistRule) -> std::result::Result<Self, FieldError> {
+        Ok(CompiledRule {
+            applies_to: rule.applies_to.clone(),
+            regex: compile(&rule.pattern, &rule.match_type)?,
+        })
+    }
+
+    fn find_in(&self, field: &'static str, text: &str) -> Option<RuleMatch> {
+        let applies = match field {
+            "name" => self.applies_to != APPLIES_TO_BODY,
+            _ => self.applies_to != APPLIES_TO_NAME,
+        };
+        if !applies {
+            return None
+        }
+
+        let text = normalize_name(text);
+        self.regex.find(&text).map(|found| RuleMatch {
+            field: field,
+            matched: found.as_str().trim().to_owned(),
+        })
+    }
+
+    pub fn find(&self, name: &str, body: &str) -> Vec<RuleMatch> {
+        self.find_in("name", name).into_iter()
+            .chain(self.find_in("body", body))
+            .collect()
+    }
+
+    //The clubs, deleted ones aside, the rule would have caught.
+    pub fn test(&self, conn: &PgConnection) -> QueryResult<Vec<ClubMatch>> {
+        use crate::schema::clubs::dsl::{clubs, id, name, body, deleted_at};
+
+        let candidates = clubs
+            .filter(deleted_at.is_null())
+            .order(id.asc())
+            .select((id, name, body))
+            .load::<(i32, String, String)>(conn)?;
+
+        Ok(candidates.into_iter().filter_map(|(club_id, club_name, club_body)| {
+            let matches = self.find(&club_name, &club_body);
+            if matches.is_empty() {
+                None
+            } else {
+                Some(ClubMatch {
+                    club_id: club_id,
+                    name: club_name,
+                    matches: matches,
+                })
+            }
+        }).collect())
+    }
+}
+
+impl Screening {
+    //Fails with a field error for each field a reject rule matched.
+    pub fn check(&self) -> Result<()> {
+        if self.rejected.is_empty() {
+            Ok(())
+        } else {
+            Err(ApiError::Validation(self.rejected.clone()))
+        }
+    }
+
+    pub fn needs_review(&self) -> bool {
+        !self.review.is_empty()
+    }
+
+    //Left on the club for the admin reviewing it.
+    pub fn review_note(&self) -> String {
+        let rules = self.review.iter().map(|rule_id| format!("#{}", rule_id)).collect::<Vec<_>>().join(", ");
+        format!("Held for review by blocklist rule {}.", rules)
+    }
+}
+
+pub fn screen(conn: &PgConnection, name: &str, body: &str) -> QueryResult<Screening> {
+    let mut screening = Screening::default();
+
+    for rule in BlocklistRule::get_enabled(conn)? {
+        let compiled = match CompiledRule::from_rule(&rule) {
+            Ok(compiled) => compiled,
+            Err(e) => {
+                eprintln!("Skipping blocklist rule #{} which no longer compiles, {:?}", rule.id, e);
+                continue
+            }
+        };
+
+        for found in compiled.find(name, body) {
+            if rule.severity == SEVERITY_REJECT {
+                if !screening.rejected.iter().any(|error| error.field == found.field) {
+                    let message = match found.field {
+                        "name" => "The name contains a term that isn't allowed.",
+                        _ => "The description contains a term that isn't allowed.",
+                    };
+                    screening.rejected.push(FieldError::new(found.field, "blocked", message));
+                }
+            } else if !screening.review.contains(&rule.id) {
+                screening.review.push(rule.id);
+            }
+        }
+    }
+
+    Ok(screening)
+}
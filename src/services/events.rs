@@ -0,0 +1,121 @@
+use crate::prelude::*;
+use rocket::tokio::sync::{broadcast, mpsc};
+use rocket::tokio::time::{sleep, Duration};
+use tokio_postgres::{AsyncMessage, NoTls};
+use futures::{stream, StreamExt};
+
+//The postgres channel every instance listens on. Must match the triggers in the change_notifications migration.
+pub const CHANNEL: &str = "saturn_events";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChangeEvent {
+    pub table: String,
+    pub op: String,
+    pub id: i32,
+    pub club_id: Option<i32>,
+}
+
+impl ChangeEvent {
+    pub fn new(table: &str, op: &str, id: i32, club_id: Option<i32>) -> Self {
+        Self {
+            table: table.to_owned(),
+            op: op.to_owned(),
+            id: id,
+            club_id: club_id,
+        }
+    }
+
+    //The club this event concerns, if any.
+    pub fn club(&self) -> Option<i32> {
+        if self.table == "clubs" {
+            Some(self.id)
+        } else {
+            self.club_id
+        }
+    }
+}
+
+/*
+Every instance keeps one dedicated postgres connection LISTENing on
+the saturn_events channel and rebroadcasts whatever arrives to local
+subscribers. Row changes are published by triggers, so anything that
+writes to the database is covered. Events that don't map to a row can be
+sent with `publish`.
+
+For now the only subscribers are the realtime streams. GoogleKeysState is
+the only other in-memory state and it mirrors Google's key set, expiring
+with it, so instances can't disagree about anything stored in the
+database. Caches of database rows added later should subscribe here and
+drop what an event touches.
+*/
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<ChangeEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(1024);
+        Self { sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ChangeEvent> {
+        self.sender.subscribe()
+    }
+
+    //Sends an event to every instance, including this one, through postgres.
+    pub fn publish(conn: &PgConnection, event: &ChangeEvent) -> QueryResult<()> {
+        use diesel::sql_types::Text;
+
+        let payload = rocket::serde::json::serde_json::to_string(event).expect("Change events always serialize.");
+        diesel::sql_query("SELECT pg_notify($1, $2)")
+            .bind::<Text, _>(CHANNEL)
+            .bind::<Text, _>(payload)
+            .execute(conn)?;
+        Ok(())
+    }
+
+    //Runs forever, reconnecting whenever the listening connection drops.
+    pub async fn listen(self, url: String) {
+        loop {
+            if let Err(e) = self.listen_once(&url).await {
+                eprintln!("Change notification listener disconnected, {:?}", e);
+            }
+            sleep(Duration::from_secs(5)).await;
+        }
+    }
+
+    async fn listen_once(&self, url: &str) -> std::result::Result<(), tokio_postgres::Error> {
+        let (client, mut connection) = tokio_postgres::connect(url, NoTls).await?;
+
+        //The connection only makes progress while polled, including for our own LISTEN, so drive it from its own task.
+        let (messages, mut received) = mpsc::unbounded_channel();
+        rocket::tokio::spawn(async move {
+            let mut polled = stream::poll_fn(move |cx| connection.poll_message(cx));
+            while let Some(message) = polled.next().await {
+                if messages.send(message).is_err() {
+                    break
+                }
+            }
+        });
+
+        client.batch_execute(&format!("LISTEN {}", CHANNEL)).await?;
+
+        //Ends when the connection closes, the client has to live until then to keep it open.
+        while let Some(message) = received.recv().await {
+            match message? {
+                AsyncMessage::Notification(n) => {
+                    match rocket::serde::json::from_str::<ChangeEvent>(n.payload()) {
+                        //Sending only fails when nobody is subscribed which is fine.
+                        Ok(event) => {let _ = self.sender.send(event);},
+                        Err(e) => eprintln!("Ignoring malformed change notification {:?}, {:?}", n.payload(), e),
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        drop(client);
+        Ok(())
+    }
+}
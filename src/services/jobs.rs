@@ -0,0 +1,35 @@
+use crate::prelude::*;
+use rocket::tokio::task::spawn_blocking;
+use rocket::tokio::time::{interval, Duration};
+
+/*
+Runs a job against the database every `period`, starting right away.
+Each run gets its own connection on a blocking thread so a slow job
+never holds up requests or the pool. Errors are logged and the job is
+tried again next period.
+*/
+pub fn spawn_periodic<F>(name: &'static str, period: Duration, database_url: String, job: F)
+where F: Fn(&PgConnection) -> QueryResult<()> + Send + Sync + 'static
+{
+    let job = Arc::new(job);
+
+    rocket::tokio::spawn(async move {
+        let mut ticker = interval(period);
+        loop {
+            ticker.tick().await;
+
+            let job = job.clone();
+            let database_url = database_url.clone();
+            let result = spawn_blocking(move || {
+                let conn = PgConnection::establish(&database_url).map_err(|e| format!("{:?}", e))?;
+                job(&conn).map_err(|e| format!("{:?}", e))
+            }).await;
+
+            match result {
+                Ok(Ok(())) => {},
+                Ok(Err(e)) => eprintln!("Job {} failed, {}", name, e),
+                Err(e) => eprintln!("Job {} panicked, {:?}", name, e),
+            }
+        }
+    });
+}
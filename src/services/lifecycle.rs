@@ -0,0 +1,136 @@
+use crate::prelude::*;
+
+/*
+How long clubs live between renewals and when their moderators hear
+about it. Read from the environment with these defaults:
+
+    CLUB_RENEWAL_DAYS=3
+    CLUB_EXPIRY_WARNING_DAYS=1
+    CLUB_LIFECYCLE_INTERVAL_SECS=3600
+    CLUB_RESTORE_DAYS=14
+*/
+#[derive(Clone, Copy)]
+pub struct LifecycleConfig {
+    pub renewal_days: i64,
+    pub warning_days: i64,
+    pub interval_secs: u64,
+    pub restore_days: i64,
+}
+
+impl LifecycleConfig {
+    pub fn from_env() -> Self {
+        let config = Self {
+            renewal_days: env_or("CLUB_RENEWAL_DAYS", 3),
+            warning_days: env_or("CLUB_EXPIRY_WARNING_DAYS", 1),
+            interval_secs: env_or("CLUB_LIFECYCLE_INTERVAL_SECS", 3600),
+            restore_days: env_or("CLUB_RESTORE_DAYS", 14),
+        };
+
+        //The jobs would spin without ever sleeping.
+        if config.interval_secs == 0 {
+            panic!("CLUB_LIFECYCLE_INTERVAL_SECS must be greater than 0");
+        }
+
+        config
+    }
+
+    //The club's own renewal period if it has one, the global one otherwise.
+    pub fn renewal_period(&self, club_renewal_days: Option<i32>) -> chrono::Duration {
+        chrono::Duration::days(club_renewal_days.map(i64::from).unwrap_or(self.renewal_days))
+    }
+
+    //How long deleted clubs can still be restored.
+    pub fn restore_window(&self) -> chrono::Duration {
+        chrono::Duration::days(self.restore_days)
+    }
+
+    pub fn next_expiry(&self, club_renewal_days: Option<i32>) -> DateTime<Utc> {
+        chrono::offset::Utc::now() + self.renewal_period(club_renewal_days)
+    }
+}
+
+pub fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    env::var(key).ok().and_then(|value| value.parse().ok()).unwrap_or(default)
+}
+
+/*
+Warns the moderators of clubs about to expire, then archives the
+clubs that already have. Each club is handled in its own transaction
+so one bad row doesn't stop the rest. The updates repeat the conditions
+so a club another instance already handled, or that was renewed in the
+meantime, is skipped rather than notified about twice.
+*/
+pub fn sweep(conn: &PgConnection, config: &LifecycleConfig) -> QueryResult<()> {
+    use crate::schema::clubs::dsl::{clubs, expiry_date, expiry_warned_at, archived_at, deleted_at};
+
+    let now = chrono::offset::Utc::now();
+
+    let expiring = clubs
+        .filter(deleted_at.is_null())
+        .filter(archived_at.is_null())
+        .filter(expiry_warned_at.is_null())
+        .filter(expiry_date.gt(now))
+        .filter(expiry_date.le(now + chrono::Duration::days(config.warning_days)))
+        .load::<Club>(conn)?;
+
+    for club in expiring {
+        let result = conn.transaction::<_, diesel::result::Error, _>(|| {
+            let warned = diesel::update(clubs.find(club.id))
+                .filter(expiry_warned_at.is_null())
+                .filter(archived_at.is_null())
+                .set(expiry_warned_at.eq(now))
+                .execute(conn)?;
+            if warned == 1 {
+                Notification::notify_moderators(conn, &club.id, "club_expiring", &format!(
+                    "{} expires on {}. Renew it to keep it listed.", club.name, club.expiry_date.format("%A, %B %e %Y")
+                ))?;
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            eprintln!("Failed to warn the moderators of club {} about its expiry, {:?}", club.id, e);
+            continue
+        }
+    }
+
+    let expired = clubs
+        .filter(deleted_at.is_null())
+        .filter(archived_at.is_null())
+        .filter(expiry_date.le(now))
+        .load::<Club>(conn)?;
+
+    for club in expired {
+        let result = conn.transaction::<_, diesel::result::Error, _>(|| {
+            let archived = diesel::update(clubs.find(club.id))
+                .filter(archived_at.is_null())
+                .filter(expiry_date.le(now))
+                .set(archived_at.eq(now))
+                .execute(conn)?;
+            if archived == 1 {
+                Notification::notify_moderators(conn, &club.id, "club_archived", &format!(
+                    "{} expired and has been archived. Ask the head moderator or an admin to reactivate it.", club.name
+                ))?;
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            eprintln!("Failed to archive club {}, {:?}", club.id, e);
+            continue
+        }
+    }
+
+    Ok(())
+}
+
+//Removes deleted clubs for good once their restore window has passed. Members, tags and registrations go with them.
+pub fn purge(conn: &PgConnection, config: &LifecycleConfig) -> QueryResult<()> {
+    use crate::schema::clubs::dsl::{clubs, deleted_at};
+
+    let cutoff = chrono::offset::Utc::now() - config.restore_window();
+    let purged = diesel::delete(clubs.filter(deleted_at.le(cutoff))).execute(conn)?;
+    if purged > 0 {
+        eprintln!("Purged {} deleted clubs.", purged);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,206 @@
+use crate::prelude::*;
+use crate::services::avatars;
+use crate::services::storage::{content_key, Storage};
+use image::{imageops::FilterType, io::Limits as ImageLimits, DynamicImage, ImageFormat};
+use std::io;
+
+/*
+Club logos. Uploads are sniffed by their bytes rather than trusted by
+their content type, decoded under size limits so a small file can't
+expand into gigabytes of pixels, and re-encoded as PNG. Re-encoding drops
+any metadata the original carried, EXIF locations included. Next to the
+logo itself a square thumbnail is kept for every size in THUMBNAIL_SIZES.
+
+The logo goes into storage under its content address and clubs.logo_key
+points at it. Thumbnails are keyed after the logo they were made from,
+so a new logo always means new URLs and browsers never show a stale one.
+*/
+pub const THUMBNAIL_SIZES: [u32; 3] = [64, 128, 256];
+//Uploads bigger than this in either direction are refused before decoding.
+pub const MAX_DIMENSION: u32 = 4096;
+//Stored logos are scaled down to fit.
+pub const STORED_DIMENSION: u32 = 1024;
+//What a decoded upload may take up in memory.
+pub const MAX_DECODED_BYTES: u64 = 64 * 1024 * 1024;
+//Used when the "logo" limit isn't configured.
+pub const MAX_UPLOAD_BYTES: u64 = 4 * 1024 * 1024;
+//Where logos were written before there was asset storage, named after the club.
+pub const LEGACY_UPLOAD_DIR: &str = "uploads";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LogoThumbnail {
+    pub size: u32,
+    pub url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClubLogo {
+    pub url: String,
+    //Smallest first.
+    pub thumbnails: Vec<LogoThumbnail>,
+    //True while no logo has been uploaded and this is the club's generated avatar.
+    pub is_generated: bool,
+}
+
+impl ClubLogo {
+    pub fn for_club(club_id: i32, club_name: &str, logo_key: Option<&str>) -> Self {
+        match logo_key {
+            Some(logo_key) => ClubLogo {
+                url: format!("/assets/{}", logo_key),
+                thumbnails: THUMBNAIL_SIZES.iter().map(|size| LogoThumbnail {
+                    size: *size,
+                    url: format!("/assets/{}", thumbnail_key(logo_key, *size)),
+                }).collect(),
+                is_generated: false,
+            },
+            //SVG scales, every size is the same avatar.
+            None => {
+                let url = format!("/assets/{}", avatars::club_avatar(club_id, club_name).key);
+                ClubLogo {
+                    thumbnails: THUMBNAIL_SIZES.iter().map(|size| LogoThumbnail {
+                        size: *size,
+                        url: url.clone(),
+                    }).collect(),
+                    url,
+                    is_generated: true,
+                }
+            }
+        }
+    }
+}
+
+//logos/<hash>.png becomes logos/<hash>-64.png.
+fn thumbnail_key(logo_key: &str, size: u32) -> String {
+    format!("{}-{}.png", logo_key.trim_end_matches(".png"), size)
+}
+
+//Only the formats browsers show everywhere.
+fn accepted_format(bytes: &[u8]) -> Option<ImageFormat> {
+    match image::guess_format(bytes) {
+        Ok(format @ ImageFormat::Png) | Ok(format @ ImageFormat::Jpeg) | Ok(format @ ImageFormat::WebP) => Some(format),
+        _ => None,
+    }
+}
+
+fn declared_format(content_type: &ContentType) -> Option<ImageFormat> {
+    match (content_type.top().as_str(), content_type.sub().as_str()) {
+        ("image", "png") => Some(ImageFormat::Png),
+        ("image", "jpeg") | ("image", "jpg") => Some(ImageFormat::Jpeg),
+        ("image", "webp") => Some(ImageFormat::WebP),
+        _ => None,
+    }
+}
+
+//Checks and decodes an uploaded image, declared is the request's content type. Gallery photos go through here too.
+pub fn decode(bytes: &[u8], declared: Option<&ContentType>) -> std::result::Result<DynamicImage, ApiError> {
+    let format = accepted_format(bytes)
+        .ok_or(ApiError::UnsupportedMediaType("Images have to be PNG, JPEG or WebP.".to_owned()))?;
+
+    if let Some(declared) = declared {
+        if declared_format(declared) != Some(format) {
+            return Err(ApiError::UnsupportedMediaType("The image isn't the kind its content type says.".to_owned()))
+        }
+    }
+
+    let mut reader = image::io::Reader::with_format(std::io::Cursor::new(bytes), format);
+    let mut limits = ImageLimits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+    limits.max_alloc = Some(MAX_DECODED_BYTES);
+    reader.limits(limits);
+
+    reader.decode().map_err(|e| match e {
+        image::ImageError::Limits(_) => ApiError::TooLarge(format!("Images can be at most {} by {} pixels.", MAX_DIMENSION, MAX_DIMENSION)),
+        _ => ApiError::BadRequest("The image could not be read.".to_owned()),
+    })
+}
+
+pub fn encode_png(image: &DynamicImage) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    image.write_to(&mut io::Cursor::new(&mut bytes), ImageFormat::Png)
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    Ok(bytes)
+}
+
+//The logo and its thumbnails as keys and PNG bytes, logo first.
+fn render(image: &DynamicImage) -> io::Result<Vec<(String, Vec<u8>)>> {
+    let stored = if image.width() > STORED_DIMENSION || image.height() > STORED_DIMENSION {
+        image.resize(STORED_DIMENSION, STORED_DIMENSION, FilterType::Lanczos3)
+    } else {
+        image.clone()
+    };
+
+    let logo = encode_png(&stored)?;
+    let logo_key = content_key("logos", &logo, "png");
+
+    let mut objects = Vec::new();
+    for size in THUMBNAIL_SIZES.iter() {
+        objects.push((thumbnail_key(&logo_key, *size), encode_png(&stored.resize_to_fill(*size, *size, FilterType::Lanczos3))?));
+    }
+    objects.insert(0, (logo_key, logo));
+
+    Ok(objects)
+}
+
+//Puts the logo and its thumbnails into storage, returns the logo's key.
+pub async fn store(storage: &Storage, image: DynamicImage) -> io::Result<String> {
+    let objects = rocket::tokio::task::spawn_blocking(move || render(&image))
+        .await
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))??;
+    let logo_key = objects[0].0.clone();
+
+    for (key, bytes) in objects {
+        storage.put(&key, bytes, "image/png").await?;
+    }
+
+    Ok(logo_key)
+}
+
+pub fn set_logo(conn: &PgConnection, club_id: &i32, key: &str) -> QueryResult<Club> {
+    use crate::schema::clubs::dsl::{clubs, logo_key};
+
+    diesel::update(clubs.find(club_id))
+        .set(logo_key.eq(key))
+        .get_result::<Club>(conn)
+}
+
+/*
+Logos uploaded before there was asset storage only exist as files named
+after their club. Those get moved into storage, with thumbnails, once.
+*/
+pub async fn backfill(db: &Db, storage: &Storage) -> QueryResult<usize> {
+    use crate::schema::clubs::dsl::{clubs, id, logo_key};
+
+    let without_logo = db.run(|conn| {
+        clubs
+            .filter(logo_key.is_null())
+            .select(id)
+            .load::<i32>(conn)
+    }).await?;
+
+    let mut processed = 0;
+    for club_id in without_logo {
+        let bytes = match rocket::tokio::fs::read(std::path::Path::new(LEGACY_UPLOAD_DIR).join(format!("{}.png", club_id))).await {
+            Ok(bytes) => bytes,
+            Err(_) => continue,
+        };
+
+        let image = match decode(&bytes, None) {
+            Ok(image) => image,
+            Err(e) => {
+                eprintln!("The logo of club {} is unusable, {:?}", club_id, e);
+                continue
+            }
+        };
+
+        match store(storage, image).await {
+            Ok(key) => {
+                db.run(move |conn| set_logo(conn, &club_id, &key)).await?;
+                processed += 1;
+            },
+            Err(e) => eprintln!("Failed to store the logo of club {}, {:?}", club_id, e),
+        }
+    }
+
+    Ok(processed)
+}
@@ -0,0 +1,45 @@
+use crate::prelude::*;
+use comrak::{markdown_to_html, ComrakExtensionOptions, ComrakOptions};
+
+/*
+Club bodies are markdown. They are rendered once when they are written
+and the sanitized HTML is stored next to the source in clubs.body_html,
+clients show it as is. The extension set is fixed so drafts rendered by
+the preview endpoint look exactly like the saved club.
+*/
+fn options() -> ComrakOptions {
+    ComrakOptions {
+        extension: ComrakExtensionOptions {
+            strikethrough: true,
+            table: true,
+            autolink: true,
+            tasklist: true,
+            tagfilter: true,
+            ..ComrakExtensionOptions::default()
+        },
+        ..ComrakOptions::default()
+    }
+}
+
+pub fn render(source: &str) -> String {
+    ammonia::clean(&markdown_to_html(source, &options()))
+}
+
+//Renders the clubs written before bodies were rendered on write.
+pub fn backfill(conn: &PgConnection) -> QueryResult<usize> {
+    use crate::schema::clubs::dsl::{clubs, id, body, body_html};
+
+    let pending = clubs
+        .filter(body_html.eq(""))
+        .filter(body.ne(""))
+        .select((id, body))
+        .load::<(i32, String)>(conn)?;
+
+    for (club_id, source) in &pending {
+        diesel::update(clubs.find(club_id))
+            .set(body_html.eq(render(source)))
+            .execute(conn)?;
+    }
+
+    Ok(pending.len())
+}
@@ -0,0 +1,11 @@
+pub mod events;
+pub mod jobs;
+pub mod lifecycle;
+
+pub mod markdown;
+pub mod logos;
+pub mod storage;
+pub mod avatars;
+pub mod attachments;
+pub mod accounts;
+pub mod blocklist;
\ No newline at end of file
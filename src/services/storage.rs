@@ -0,0 +1,269 @@
+use crate::prelude::*;
+use crate::services::lifecycle::env_or;
+use rocket::tokio::fs;
+use s3::{bucket::Bucket, creds::Credentials, region::Region};
+use sha2::{Digest, Sha256};
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/*
+Where uploaded assets live. Every instance has to see the same objects,
+so anything but a single server should use the S3 driver. Read from the
+environment:
+
+    STORAGE_BACKEND=local           local or s3
+    STORAGE_DIR=uploads             local only
+    S3_BUCKET, S3_REGION=us-east-1
+    S3_ENDPOINT                     for S3 compatible servers, e.g. http://localhost:9000 for MinIO
+    S3_ACCESS_KEY, S3_SECRET_KEY
+    S3_PATH_STYLE=false             MinIO wants true
+    S3_PRESIGN_SECS=3600            how long redirects to the bucket stay valid
+
+Keys are content addressed, see content_key, so an object never changes
+once written and can be cached forever.
+*/
+#[rocket::async_trait]
+pub trait AssetStorage: Send + Sync {
+    //Writing a key that already exists is fine, it holds the same bytes.
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> io::Result<()>;
+    async fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+    async fn delete(&self, key: &str) -> io::Result<()>;
+
+    //A URL clients can fetch the object from themselves, None when assets have to be served by us.
+    async fn presigned_url(&self, _key: &str) -> io::Result<Option<String>> {
+        Ok(None)
+    }
+}
+
+//The configured driver, managed by rocket. Cheap to clone into background work.
+#[derive(Clone)]
+pub struct Storage(pub Arc<dyn AssetStorage>);
+
+impl Storage {
+    pub fn from_env() -> Self {
+        match env::var("STORAGE_BACKEND").unwrap_or("local".to_owned()).as_str() {
+            "s3" => Storage(Arc::new(S3Storage::from_env())),
+            "local" => Storage(Arc::new(LocalStorage::new(env::var("STORAGE_DIR").unwrap_or("uploads".to_owned())))),
+            other => panic!("Unknown STORAGE_BACKEND {}, use local or s3", other),
+        }
+    }
+}
+
+impl std::ops::Deref for Storage {
+    type Target = dyn AssetStorage;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+//E.g. logos/3a7bd3e2360a3d29eea436fcfb7e44c735d117c42d1c1835420b6b9942dd4f1b.png
+pub fn content_key(prefix: &str, bytes: &[u8], extension: &str) -> String {
+    format!("{}/{}.{}", prefix, hex::encode(Sha256::digest(bytes)), extension)
+}
+
+//Keys only ever come from content_key, anything else in a request is refused before it reaches a driver.
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && !key.starts_with('/')
+        && !key.split('/').any(|segment| segment.is_empty() || segment.starts_with('.'))
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '/' || c == '-' || c == '.' || c == '_')
+}
+
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+//Tells apart the partial files of uploads racing for the same key.
+static PARTIAL_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+impl LocalStorage {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.root.join(key)
+    }
+}
+
+#[rocket::async_trait]
+impl AssetStorage for LocalStorage {
+    async fn put(&self, key: &str, bytes: Vec<u8>, _content_type: &str) -> io::Result<()> {
+        let path = self.path(key);
+        if fs::metadata(&path).await.is_ok() {
+            return Ok(())
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        //Written next to the destination first so readers never see half an object. Every
+        //writer gets its own partial file, the dot keeps it out of reach of any valid key.
+        let partial = path.with_file_name(format!(
+            ".{}.{}-{}.partial",
+            path.file_name().and_then(|name| name.to_str()).unwrap_or("object"),
+            std::process::id(),
+            PARTIAL_COUNTER.fetch_add(1, Ordering::Relaxed),
+        ));
+        fs::write(&partial, bytes).await?;
+        fs::rename(&partial, &path).await
+    }
+
+    async fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(self.path(key)).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> io::Result<()> {
+        match fs::remove_file(self.path(key)).await {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+pub struct S3Storage {
+    bucket: Bucket,
+    presign_secs: u32,
+}
+
+fn s3_error<E: std::fmt::Debug>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, format!("{:?}", e))
+}
+
+impl S3Storage {
+    pub fn from_env() -> Self {
+        let name = env::var("S3_BUCKET").expect("S3_BUCKET must be set");
+        let region_name = env::var("S3_REGION").unwrap_or("us-east-1".to_owned());
+        let region = match env::var("S3_ENDPOINT") {
+            Ok(endpoint) => Region::Custom { region: region_name, endpoint },
+            Err(_) => region_name.parse().expect("S3_REGION must be a valid region"),
+        };
+        let credentials = Credentials::new(
+            Some(&env::var("S3_ACCESS_KEY").expect("S3_ACCESS_KEY must be set")),
+            Some(&env::var("S3_SECRET_KEY").expect("S3_SECRET_KEY must be set")),
+            None, None, None,
+        ).expect("S3 credentials");
+
+        let bucket = if env_or("S3_PATH_STYLE", false) {
+            Bucket::new_with_path_style(&name, region, credentials)
+        } else {
+            Bucket::new(&name, region, credentials)
+        }.expect("S3 bucket");
+
+        Self {
+            bucket,
+            presign_secs: env_or("S3_PRESIGN_SECS", 3600),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl AssetStorage for S3Storage {
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> io::Result<()> {
+        let (_, code) = self.bucket.put_object_with_content_type(key, &bytes, content_type).await.map_err(s3_error)?;
+        match code {
+            200..=299 => Ok(()),
+            code => Err(s3_error(format!("Storing {} failed with status {}", key, code))),
+        }
+    }
+
+    async fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        let (bytes, code) = self.bucket.get_object(key).await.map_err(s3_error)?;
+        match code {
+            200..=299 => Ok(Some(bytes)),
+            404 => Ok(None),
+            code => Err(s3_error(format!("Fetching {} failed with status {}", key, code))),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> io::Result<()> {
+        let (_, code) = self.bucket.delete_object(key).await.map_err(s3_error)?;
+        match code {
+            200..=299 | 404 => Ok(()),
+            code => Err(s3_error(format!("Deleting {} failed with status {}", key, code))),
+        }
+    }
+
+    async fn presigned_url(&self, key: &str) -> io::Result<Option<String>> {
+        self.bucket.presign_get(key, self.presign_secs).map(Some).map_err(s3_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_keys_are_the_sha256_of_the_bytes() {
+        assert_eq!(
+            content_key("logos", b"abc", "png"),
+            "logos/ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad.png"
+        );
+        assert_ne!(content_key("logos", b"abc", "png"), content_key("logos", b"abd", "png"));
+    }
+
+    #[test]
+    fn content_keys_are_valid_keys() {
+        assert!(is_valid_key(&content_key("photos/thumbnails", b"abc", "webp")));
+    }
+
+    #[test]
+    fn valid_keys() {
+        assert!(is_valid_key("logos/abc.png"));
+        assert!(is_valid_key("documents/a_b-c.pdf"));
+        assert!(is_valid_key("file"));
+    }
+
+    #[test]
+    fn invalid_keys() {
+        assert!(!is_valid_key(""));
+        assert!(!is_valid_key("/etc/passwd"));
+        assert!(!is_valid_key("logos/../secret"));
+        assert!(!is_valid_key("logos/.hidden"));
+        assert!(!is_valid_key("logos//abc.png"));
+        assert!(!is_valid_key("logos/"));
+        assert!(!is_valid_key("logos\\abc.png"));
+        assert!(!is_valid_key("logos/abc png"));
+        assert!(!is_valid_key("logos/ä.png"));
+    }
+
+    #[rocket::async_test]
+    async fn local_puts_of_the_same_key_all_succeed() {
+        let root = std::env::temp_dir().join(format!("saturn-storage-{}", std::process::id()));
+        let storage = Arc::new(LocalStorage::new(&root));
+        let key = content_key("logos", b"abc", "png");
+
+        let writers = (0..8).map(|_| {
+            let storage = storage.clone();
+            let key = key.clone();
+            rocket::tokio::spawn(async move { storage.put(&key, b"abc".to_vec(), "image/png").await })
+        }).collect::<Vec<_>>();
+        for writer in writers {
+            writer.await.expect("writer panicked").expect("put failed");
+        }
+
+        assert_eq!(storage.get(&key).await.expect("get failed"), Some(b"abc".to_vec()));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    //Needs a bucket, e.g. MinIO, and the S3_* variables. Run with cargo test -- --ignored
+    #[rocket::async_test]
+    #[ignore]
+    async fn s3_round_trip() {
+        let storage = S3Storage::from_env();
+        let key = content_key("tests", b"saturn storage test", "txt");
+
+        storage.put(&key, b"saturn storage test".to_vec(), "text/plain").await.expect("put failed");
+        assert_eq!(storage.get(&key).await.expect("get failed"), Some(b"saturn storage test".to_vec()));
+        assert!(storage.presigned_url(&key).await.expect("presign failed").is_some());
+
+        storage.delete(&key).await.expect("delete failed");
+        assert_eq!(storage.get(&key).await.expect("get failed"), None);
+    }
+}
@@ -0,0 +1,84 @@
+use crate::prelude::*;
+use validator::{ValidationError, ValidationErrors};
+
+/*
+Rules for what clients send us. DTOs declare their rules with
+#[derive(Validate)], checks that need the database live next to the
+handlers. Failures go out as ApiError::Validation with one FieldError
+per problem so forms can show them next to the right input.
+*/
+
+//Punctuation allowed in club names besides letters, digits and spaces.
+const CLUB_NAME_PUNCTUATION: &str = "'&-.,!?()#:+/";
+
+//Trims a name and collapses runs of whitespace, so "Chess  Club " and "Chess Club" are the same name.
+pub fn normalize_name(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+pub fn validate_club_name(name: &str) -> std::result::Result<(), ValidationError> {
+    let allowed = |c: char| c.is_alphanumeric() || c == ' ' || CLUB_NAME_PUNCTUATION.contains(c);
+
+    if !name.chars().all(allowed) {
+        let mut error = ValidationError::new("invalid_characters");
+        error.message = Some(format!("Club names may only use letters, digits, spaces and {}", CLUB_NAME_PUNCTUATION).into());
+        return Err(error)
+    }
+
+    if !name.chars().any(char::is_alphanumeric) {
+        let mut error = ValidationError::new("no_letters");
+        error.message = Some("Club names need at least one letter or digit.".into());
+        return Err(error)
+    }
+
+    Ok(())
+}
+
+//Profile fields are shown to everyone, club_mates or only_me, see users_md::Relation.
+pub fn validate_visibility(visibility: &str) -> std::result::Result<(), ValidationError> {
+    use crate::models::users_md::{VISIBILITY_CLUB_MATES, VISIBILITY_EVERYONE, VISIBILITY_ONLY_ME};
+
+    match visibility {
+        VISIBILITY_EVERYONE | VISIBILITY_CLUB_MATES | VISIBILITY_ONLY_ME => Ok(()),
+        _ => {
+            let mut error = ValidationError::new("invalid_visibility");
+            error.message = Some("Fields are shown to everyone, club_mates or only_me.".into());
+            Err(error)
+        }
+    }
+}
+
+impl From<ValidationErrors> for ApiError {
+    fn from(errors: ValidationErrors) -> Self {
+        let mut fields = errors.field_errors()
+            .into_iter()
+            .flat_map(|(field, errors)| errors.iter().map(move |error| {
+                let message = match &error.message {
+                    Some(message) => message.to_string(),
+                    None => describe(field, error),
+                };
+                FieldError::new(field, &error.code, &message)
+            }))
+            .collect::<Vec<_>>();
+        //field_errors is a HashMap, keep the order stable for clients.
+        fields.sort_by(|a, b| a.field.cmp(&b.field));
+        ApiError::Validation(fields)
+    }
+}
+
+//A message for the built in rules, which only come with a code.
+fn describe(field: &str, error: &ValidationError) -> String {
+    match error.code.as_ref() {
+        "length" => match (error.params.get("min"), error.params.get("max")) {
+            (Some(min), Some(max)) => format!("The {} must be between {} and {} characters.", field, min, max),
+            (Some(min), None) => format!("The {} must be at least {} characters.", field, min),
+            (None, Some(max)) => format!("The {} must be at most {} characters.", field, max),
+            (None, None) => format!("The {} has the wrong length.", field),
+        },
+        "range" => match (error.params.get("min"), error.params.get("max")) {
+            (Some(min), Some(max)) => format!("The {} must be between {} and {}.", field, min, max),
+            _ => format!("The {} is out of range.", field),
+        },
+        code => format!("The {} is invalid ({}).", field, code),
+    }
+}